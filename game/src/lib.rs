@@ -1,8 +1,11 @@
-use steadfast_core::def::engine::Application;
+use steadfast_core::def::engine::{self, Application, ApplicationConfig};
+use steadfast_core::log;
 use steadfast_core::module::game::GameExports;
-use steadfast_core::module::{init_module, Host};
+use steadfast_core::module::{init_module, module_state, Host, ReloadEvent};
 
-struct State {}
+module_state! {
+    struct State {}
+}
 
 init_module! {
     state: State,
@@ -14,18 +17,51 @@ init_module! {
     deinit: deinit,
 }
 
+struct Game {
+    ticks: u64,
+}
+
+impl Application for Game {
+    fn config(&self) -> ApplicationConfig {
+        ApplicationConfig {
+            title: String::from("Steadfast Game"),
+            ..ApplicationConfig::default()
+        }
+    }
+
+    fn on_start(&mut self) {
+        log::info!("Game started");
+    }
+
+    fn on_fixed_update(&mut self, _dt: f64) {
+        self.ticks += 1;
+    }
+
+    fn on_shutdown(&mut self) {
+        log::info!("Game ran for {} ticks", self.ticks);
+    }
+}
+
+#[no_mangle]
+fn create_application() -> Box<dyn Application> {
+    Box::new(Game { ticks: 0 })
+}
+
 #[no_mangle]
-fn create_application() -> Application {
-    Application { num: 17 }
+fn resume_application(application: *mut ()) -> Box<dyn Application> {
+    unsafe { engine::resume::<Game>(application) }
 }
 
 fn init(_state: &mut State) {}
 
 fn reload(_state: &mut State) -> GameExports {
-    GameExports { create_application }
+    GameExports {
+        create_application,
+        resume_application,
+    }
 }
 
-fn update(_host: &mut Host, _state: &mut State) {}
+fn update(_host: &mut Host, _event: &ReloadEvent, _state: &mut State) {}
 
 fn unload(_state: &mut State) {}
 
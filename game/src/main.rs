@@ -1,4 +1,3 @@
-use steadfast_core::module::load_modules;
 use steadfast_runtime::*;
 
 steadfast_entry!();
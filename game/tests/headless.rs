@@ -0,0 +1,217 @@
+#![cfg(not(feature = "shipping"))]
+
+use std::path::{Path, PathBuf};
+use std::process::{Command, Output};
+use std::sync::Once;
+
+static BUILD: Once = Once::new();
+static BUILD_TAGS: Once = Once::new();
+
+const MANIFEST: &str = r#"
+[[module]]
+name = "libgame"
+path = { debug = "target/debug/libgame" }
+
+[[module]]
+name = "libengine"
+path = { debug = "target/debug/libengine" }
+dependencies = ["libgame"]
+"#;
+
+/// Builds the game executable and the libraries of its' modules, in their
+/// own target directory as the tests' target directory is locked, and
+/// writes a manifest for them. Returns the directory the game should be
+/// run in.
+fn build_game() -> PathBuf {
+    build_game_in(&BUILD, "headless", &[])
+}
+
+/// Builds the game like [`build_game`], with the allocator tagging every
+/// allocation.
+fn build_tagged_game() -> PathBuf {
+    build_game_in(&BUILD_TAGS, "headless_tags", &["steadfast_allocator/tags"])
+}
+
+fn build_game_in(build: &Once, name: &str, features: &[&str]) -> PathBuf {
+    let dir = Path::new(env!("CARGO_TARGET_TMPDIR")).join(name);
+    let target_dir = dir.join("target");
+
+    build.call_once(|| {
+        let status = Command::new(env!("CARGO"))
+            .arg("build")
+            .arg("--quiet")
+            .arg("--manifest-path")
+            .arg(Path::new(env!("CARGO_MANIFEST_DIR")).join("../Cargo.toml"))
+            .arg("--target-dir")
+            .arg(&target_dir)
+            .args(["-p", "game", "-p", "steadfast_engine"])
+            // Features of the allocator can only be enabled with it selected
+            .args(["-p", "steadfast_allocator"])
+            .args(features.iter().flat_map(|feature| ["--features", feature]))
+            .status()
+            .expect("Failed to run cargo");
+
+        assert!(status.success(), "Failed to build the game");
+
+        std::fs::write(dir.join("modules.toml"), MANIFEST).unwrap();
+    });
+
+    dir
+}
+
+/// Where the game keeps the user's settings and logs in [`test`], rather
+/// than their own config and data directories.
+fn user_dir(test: &str) -> PathBuf {
+    Path::new(env!("CARGO_TARGET_TMPDIR"))
+        .join("headless_config")
+        .join(test)
+}
+
+fn run_game(args: &[&str], test: &str) -> Output {
+    run_game_in(&build_game(), args, test, &[])
+}
+
+/// Runs the game built in [`dir`], with the variables in [`env`] set.
+fn run_game_in(dir: &Path, args: &[&str], test: &str, env: &[(&str, &str)]) -> Output {
+    let executable = format!("target/debug/game{}", std::env::consts::EXE_SUFFIX);
+
+    Command::new(dir.join(executable))
+        .args(args)
+        .current_dir(dir)
+        .env("XDG_CONFIG_HOME", user_dir(test))
+        .env("XDG_DATA_HOME", user_dir(test))
+        .env("HOME", user_dir(test))
+        .env("APPDATA", user_dir(test))
+        .env("LOCALAPPDATA", user_dir(test))
+        .env_remove("RUST_LOG")
+        .env_remove("DISPLAY")
+        .env_remove("WAYLAND_DISPLAY")
+        .envs(env.iter().copied())
+        .output()
+        .expect("Failed to run the game")
+}
+
+/// Reads the file at [`path`] in the game's directory in [`test`]'s user
+/// directory, wherever it is on this platform.
+fn read_user_file(test: &str, path: &str) -> Option<String> {
+    let app_dirs = [
+        "game",
+        ".config/game",
+        ".local/share/game",
+        "Library/Application Support/game",
+    ];
+
+    app_dirs
+        .iter()
+        .find_map(|dir| std::fs::read_to_string(user_dir(test).join(dir).join(path)).ok())
+}
+
+#[test]
+fn headless_runs_exit_after_a_number_of_frames() {
+    let output = run_game(&["--headless", "--frames", "30"], "frames");
+
+    assert!(output.status.success(), "{:?}", output);
+}
+
+#[test]
+fn headless_runs_exit_after_a_number_of_seconds() {
+    let output = run_game(&["--headless", "--seconds", "0.25"], "seconds");
+
+    assert!(output.status.success(), "{:?}", output);
+}
+
+#[test]
+fn guarded_runs_share_the_allocator_with_the_modules() {
+    // Memory is passed between the executable and the libraries of the
+    // modules, so they must all allocate from the guarded allocator
+    let env = [("STEADFAST_GUARDED", "1")];
+    let output = run_game_in(
+        &build_game(),
+        &["--headless", "--frames", "5"],
+        "guarded",
+        &env,
+    );
+
+    assert!(output.status.success(), "{:?}", output);
+}
+
+#[test]
+fn tagged_runs_share_the_allocator_with_the_modules() {
+    // Tagged allocations are prefixed with their tag, which only the
+    // allocator that made them knows to skip when freeing them
+    let output = run_game_in(
+        &build_tagged_game(),
+        &["--headless", "--frames", "5"],
+        "tags",
+        &[],
+    );
+
+    assert!(output.status.success(), "{:?}", output);
+}
+
+#[test]
+fn windowed_runs_fail_without_a_windowed_platform() {
+    let output = run_game(&["--frames", "2"], "windowed");
+
+    assert_eq!(output.status.code(), Some(1));
+    assert!(String::from_utf8_lossy(&output.stderr).contains("run with `--headless`"));
+}
+
+#[test]
+fn invalid_options_exit_with_usage_status() {
+    let output = run_game(&["--headless", "--frames"], "usage");
+
+    assert_eq!(output.status.code(), Some(2));
+    assert!(String::from_utf8_lossy(&output.stderr).contains("`--frames` needs a value"));
+}
+
+#[test]
+fn archived_cvars_set_on_the_command_line_are_saved() {
+    let _ = std::fs::remove_dir_all(user_dir("cvars"));
+
+    let args = ["--headless", "--frames", "2", "+set", "r_vsync", "0"];
+    let output = run_game(&args, "cvars");
+    assert!(output.status.success(), "{:?}", output);
+
+    let saved = read_user_file("cvars", "config.cfg").expect("The config file wasn't saved");
+
+    assert!(saved.contains("set r_vsync 0"), "{}", saved);
+}
+
+#[test]
+fn modules_log_to_the_log_file() {
+    let _ = std::fs::remove_dir_all(user_dir("logs"));
+
+    let output = run_game(&["--headless", "--frames", "2"], "logs");
+    assert!(output.status.success(), "{:?}", output);
+
+    // Logged by the game's library, with the `log` crate
+    let log = read_user_file("logs", "logs/game.log").expect("The log file wasn't written");
+    assert!(log.contains("Game ran for"), "{}", log);
+
+    let args = [
+        "--headless",
+        "--frames",
+        "2",
+        "+set",
+        "log_filter",
+        "info,libgame=warn",
+    ];
+    let output = run_game(&args, "logs");
+    assert!(output.status.success(), "{:?}", output);
+
+    // The cvar is set once the modules are loaded, so before they shut down
+    let log = read_user_file("logs", "logs/game.log").unwrap();
+    assert!(!log.contains("Game ran for"), "{}", log);
+
+    // The previous run's log is kept
+    let previous = read_user_file("logs", "logs/game.1.log").unwrap();
+    assert!(previous.contains("Game ran for"), "{}", previous);
+
+    let config = read_user_file("logs", "config.cfg").unwrap();
+    assert!(
+        config.contains("set log_filter info,libgame=warn"),
+        "{}",
+        config
+    );
+}
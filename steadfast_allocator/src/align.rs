@@ -1 +1,16 @@
 pub(crate) const ALIGNMENT: usize = 8;
+
+/// Rounds [`value`] up to the next multiple of [`align`], which must be a
+/// power of two. Returns `None` on overflow.
+pub(crate) const fn align_up(value: usize, align: usize) -> Option<usize> {
+    match value.checked_add(align - 1) {
+        Some(value) => Some(value & !(align - 1)),
+        None => None,
+    }
+}
+
+/// Rounds [`value`] down to the previous multiple of [`align`], which must
+/// be a power of two.
+pub(crate) const fn align_down(value: usize, align: usize) -> usize {
+    value & !(align - 1)
+}
@@ -4,4 +4,284 @@ mod unix;
 #[cfg(target_family = "windows")]
 mod windows;
 
+#[cfg(feature = "tags")]
+use crate::tags as tagging;
+
+use crate::allocator::{self, SteadfastAlloc};
+#[cfg(any(
+    target_os = "linux",
+    target_vendor = "apple",
+    target_family = "windows"
+))]
+use crate::guarded::{self, GuardedAllocator};
+use core::alloc::{GlobalAlloc, Layout};
+use core::ptr::NonNull;
+
 pub struct SteadfastAllocator;
+
+/// Allocates with the platform's aligned allocation functions.
+pub(crate) struct Platform;
+
+/// Allocates with the [`GuardedAllocator`] when it is enabled, or the
+/// [`Platform`] otherwise.
+#[cfg(any(
+    target_os = "linux",
+    target_vendor = "apple",
+    target_family = "windows"
+))]
+struct Backend;
+
+// The guarded allocator isn't built for other targets
+#[cfg(not(any(
+    target_os = "linux",
+    target_vendor = "apple",
+    target_family = "windows"
+)))]
+use self::Platform as Backend;
+
+#[cfg(any(
+    target_os = "linux",
+    target_vendor = "apple",
+    target_family = "windows"
+))]
+unsafe impl GlobalAlloc for Backend {
+    #[inline(always)]
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        if guarded::enabled() {
+            GuardedAllocator.alloc(layout)
+        } else {
+            Platform.alloc(layout)
+        }
+    }
+
+    #[inline(always)]
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        if guarded::enabled() {
+            GuardedAllocator.dealloc(ptr, layout)
+        } else {
+            Platform.dealloc(ptr, layout)
+        }
+    }
+
+    #[inline(always)]
+    unsafe fn alloc_zeroed(&self, layout: Layout) -> *mut u8 {
+        if guarded::enabled() {
+            GuardedAllocator.alloc_zeroed(layout)
+        } else {
+            Platform.alloc_zeroed(layout)
+        }
+    }
+
+    #[inline(always)]
+    unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+        if guarded::enabled() {
+            GuardedAllocator.realloc(ptr, layout, new_size)
+        } else {
+            Platform.realloc(ptr, layout, new_size)
+        }
+    }
+}
+
+unsafe impl GlobalAlloc for SteadfastAllocator {
+    #[inline(always)]
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        let ptr = tagging::alloc(layout, |layout| Backend.alloc(layout));
+
+        #[cfg(feature = "tracking")]
+        if !ptr.is_null() {
+            crate::tracking::record_alloc(layout.size());
+        }
+
+        ptr
+    }
+
+    #[inline(always)]
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        #[cfg(feature = "tracking")]
+        crate::tracking::record_dealloc(layout.size());
+
+        tagging::dealloc(ptr, layout, |ptr, layout| Backend.dealloc(ptr, layout))
+    }
+
+    #[inline(always)]
+    unsafe fn alloc_zeroed(&self, layout: Layout) -> *mut u8 {
+        let ptr = tagging::alloc(layout, |layout| Backend.alloc_zeroed(layout));
+
+        #[cfg(feature = "tracking")]
+        if !ptr.is_null() {
+            crate::tracking::record_alloc(layout.size());
+        }
+
+        ptr
+    }
+
+    #[inline(always)]
+    unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+        let new_ptr = tagging::realloc(ptr, layout, new_size, |ptr, layout, new_size| {
+            Backend.realloc(ptr, layout, new_size)
+        });
+
+        #[cfg(feature = "tracking")]
+        if !new_ptr.is_null() {
+            crate::tracking::record_dealloc(layout.size());
+            crate::tracking::record_alloc(new_size);
+        }
+
+        new_ptr
+    }
+}
+
+impl SteadfastAllocator {
+    /// Allocates [`layout`] for the arena [`name`], which memory budget
+    /// reports name if the allocation exceeds a budget.
+    #[inline(always)]
+    pub(crate) unsafe fn alloc_for(&self, name: &'static str, layout: Layout) -> *mut u8 {
+        #[cfg(feature = "tags")]
+        return crate::tags::with_source(name, || self.alloc(layout));
+
+        #[cfg(not(feature = "tags"))]
+        {
+            let _ = name;
+            self.alloc(layout)
+        }
+    }
+}
+
+unsafe impl SteadfastAlloc for SteadfastAllocator {
+    #[inline(always)]
+    fn allocate(&self, layout: Layout) -> Option<NonNull<u8>> {
+        if layout.size() == 0 {
+            return Some(allocator::dangling(layout));
+        }
+
+        NonNull::new(unsafe { self.alloc(layout) })
+    }
+
+    #[inline(always)]
+    unsafe fn deallocate(&self, ptr: NonNull<u8>, layout: Layout) {
+        if layout.size() != 0 {
+            self.dealloc(ptr.as_ptr(), layout);
+        }
+    }
+
+    unsafe fn grow(&self, ptr: NonNull<u8>, old: Layout, new: Layout) -> Option<NonNull<u8>> {
+        // realloc keeps the alignment, but can't start from nothing
+        if old.size() == 0 || old.align() != new.align() {
+            return allocator::reallocate(self, ptr, old, new);
+        }
+
+        NonNull::new(self.realloc(ptr.as_ptr(), old, new.size()))
+    }
+
+    unsafe fn shrink(&self, ptr: NonNull<u8>, old: Layout, new: Layout) -> Option<NonNull<u8>> {
+        if new.size() == 0 || old.align() != new.align() {
+            return allocator::reallocate(self, ptr, old, new);
+        }
+
+        NonNull::new(self.realloc(ptr.as_ptr(), old, new.size()))
+    }
+}
+
+/// Passes allocations straight through when tags are disabled.
+#[cfg(not(feature = "tags"))]
+mod tagging {
+    use core::alloc::Layout;
+
+    #[inline(always)]
+    pub unsafe fn alloc(layout: Layout, alloc: impl FnOnce(Layout) -> *mut u8) -> *mut u8 {
+        alloc(layout)
+    }
+
+    #[inline(always)]
+    pub unsafe fn dealloc(ptr: *mut u8, layout: Layout, dealloc: impl FnOnce(*mut u8, Layout)) {
+        dealloc(ptr, layout)
+    }
+
+    #[inline(always)]
+    pub unsafe fn realloc(
+        ptr: *mut u8,
+        layout: Layout,
+        new_size: usize,
+        realloc: impl FnOnce(*mut u8, Layout, usize) -> *mut u8,
+    ) -> *mut u8 {
+        realloc(ptr, layout, new_size)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::SteadfastAllocator;
+    use core::alloc::{GlobalAlloc, Layout};
+
+    fn fill(ptr: *mut u8, size: usize) {
+        for i in 0..size {
+            unsafe { ptr.add(i).write(i as u8) };
+        }
+    }
+
+    fn check(ptr: *mut u8, size: usize) {
+        for i in 0..size {
+            assert_eq!(unsafe { ptr.add(i).read() }, i as u8);
+        }
+    }
+
+    #[test]
+    fn realloc_keeps_alignment() {
+        for align in [32, 64, 4096] {
+            let layout = Layout::from_size_align(100, align).unwrap();
+
+            unsafe {
+                let ptr = SteadfastAllocator.alloc(layout);
+                assert_eq!(ptr as usize % align, 0);
+                fill(ptr, 100);
+
+                // Grow well past the first block, then shrink
+                let ptr = SteadfastAllocator.realloc(ptr, layout, 1 << 20);
+                assert_eq!(ptr as usize % align, 0);
+                check(ptr, 100);
+
+                let grown = Layout::from_size_align(1 << 20, align).unwrap();
+                let ptr = SteadfastAllocator.realloc(ptr, grown, 10);
+                assert_eq!(ptr as usize % align, 0);
+                check(ptr, 10);
+
+                SteadfastAllocator.dealloc(ptr, Layout::from_size_align(10, align).unwrap());
+            }
+        }
+    }
+
+    #[test]
+    fn realloc_keeps_small_alignments() {
+        let layout = Layout::from_size_align(4, 8).unwrap();
+
+        unsafe {
+            let ptr = SteadfastAllocator.alloc(layout);
+            fill(ptr, 4);
+
+            let ptr = SteadfastAllocator.realloc(ptr, layout, 4096);
+            assert_eq!(ptr as usize % 8, 0);
+            check(ptr, 4);
+
+            SteadfastAllocator.dealloc(ptr, Layout::from_size_align(4096, 8).unwrap());
+        }
+    }
+
+    #[test]
+    fn large_zeroed_allocations_are_zeroed() {
+        for align in [8, 64, 4096] {
+            let layout = Layout::from_size_align(64 << 20, align).unwrap();
+
+            unsafe {
+                let ptr = SteadfastAllocator.alloc_zeroed(layout);
+                assert!(!ptr.is_null());
+                assert_eq!(ptr as usize % align, 0);
+
+                let bytes = core::slice::from_raw_parts(ptr, layout.size());
+                assert!(bytes.iter().step_by(4093).all(|byte| *byte == 0));
+                assert_eq!(bytes[layout.size() - 1], 0);
+
+                SteadfastAllocator.dealloc(ptr, layout);
+            }
+        }
+    }
+}
@@ -1,8 +1,12 @@
-use crate::SteadfastAllocator;
+use super::Platform;
 use core::alloc::{GlobalAlloc, Layout};
 use core::ffi::c_void;
 
-unsafe impl GlobalAlloc for SteadfastAllocator {
+/// The alignment `malloc`, `calloc` and `realloc` guarantee. Larger
+/// alignments need `posix_memalign`, which has no `realloc`.
+const MIN_ALIGN: usize = 2 * core::mem::size_of::<usize>();
+
+unsafe impl GlobalAlloc for Platform {
     #[inline(always)]
     unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
         let mut ptr = core::ptr::null_mut();
@@ -27,6 +31,12 @@ unsafe impl GlobalAlloc for SteadfastAllocator {
 
     #[inline(always)]
     unsafe fn alloc_zeroed(&self, layout: Layout) -> *mut u8 {
+        // calloc can hand out pages the OS already zeroed, rather than
+        // writing to every byte of a large allocation
+        if layout.align() <= MIN_ALIGN && layout.align() <= layout.size() {
+            return calloc(layout.size(), 1) as *mut u8;
+        }
+
         let ptr = self.alloc(layout);
 
         if !ptr.is_null() {
@@ -38,11 +48,25 @@ unsafe impl GlobalAlloc for SteadfastAllocator {
 
     #[inline(always)]
     unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
-        realloc(ptr as *mut c_void, new_size) as *mut u8
+        if layout.align() <= MIN_ALIGN && layout.align() <= new_size {
+            return realloc(ptr as *mut c_void, new_size) as *mut u8;
+        }
+
+        // realloc would lose the alignment, so move the allocation instead
+        let new_layout = Layout::from_size_align_unchecked(new_size, layout.align());
+        let new_ptr = self.alloc(new_layout);
+
+        if !new_ptr.is_null() {
+            core::ptr::copy_nonoverlapping(ptr, new_ptr, layout.size().min(new_size));
+            self.dealloc(ptr, layout);
+        }
+
+        new_ptr
     }
 }
 
 extern "C" {
+    pub fn calloc(count: usize, size: usize) -> *mut c_void;
     pub fn free(p: *mut c_void);
     pub fn posix_memalign(memptr: *mut *mut c_void, align: usize, size: usize) -> u32;
     pub fn realloc(p: *mut c_void, size: usize) -> *mut c_void;
@@ -1,8 +1,8 @@
-use crate::alloc::SteadfastAllocator;
+use super::Platform;
 use core::alloc::{GlobalAlloc, Layout};
 use core::ffi::c_void;
 
-unsafe impl GlobalAlloc for SteadfastAllocator {
+unsafe impl GlobalAlloc for Platform {
     #[inline(always)]
     unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
         _aligned_malloc(layout.size(), layout.align()) as *mut u8
@@ -15,6 +15,7 @@ unsafe impl GlobalAlloc for SteadfastAllocator {
 
     #[inline(always)]
     unsafe fn alloc_zeroed(&self, layout: Layout) -> *mut u8 {
+        // There is no aligned calloc, so large allocations are zeroed by hand
         let ptr = self.alloc(layout);
 
         if !ptr.is_null() {
@@ -26,6 +27,7 @@ unsafe impl GlobalAlloc for SteadfastAllocator {
 
     #[inline(always)]
     unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+        // Unlike realloc on Unix, this keeps the alignment of the block
         _aligned_realloc(ptr as *mut c_void, new_size, layout.align()) as *mut u8
     }
 }
@@ -0,0 +1,97 @@
+use core::alloc::Layout;
+use core::ptr::NonNull;
+
+/// An allocator collections can be built on, implemented by the global
+/// allocator and the arenas, pools and stacks in this crate.
+///
+/// Allocators which free memory all at once, like arenas, may only free
+/// the newest allocation on [`deallocate`], or nothing at all.
+///
+/// # Safety
+///
+/// Memory returned by [`allocate`], [`grow`] or [`shrink`] must fit the
+/// layout it was requested with, and stay valid until it is deallocated or
+/// the allocator is reset or dropped.
+pub unsafe trait SteadfastAlloc {
+    /// Allocates uninitialized memory for [`layout`], or returns `None` if
+    /// the allocator has no room for it.
+    fn allocate(&self, layout: Layout) -> Option<NonNull<u8>>;
+
+    /// Frees the memory at [`ptr`].
+    ///
+    /// # Safety
+    ///
+    /// [`ptr`] must have been allocated by this allocator for [`layout`].
+    unsafe fn deallocate(&self, ptr: NonNull<u8>, layout: Layout);
+
+    /// Grows the memory at [`ptr`] to [`new`], keeping its' contents. On
+    /// failure the memory is left untouched and `None` is returned.
+    ///
+    /// # Safety
+    ///
+    /// [`ptr`] must have been allocated by this allocator for [`old`], and
+    /// [`new`] must be at least as large as [`old`].
+    unsafe fn grow(&self, ptr: NonNull<u8>, old: Layout, new: Layout) -> Option<NonNull<u8>> {
+        debug_assert!(new.size() >= old.size());
+
+        reallocate(self, ptr, old, new)
+    }
+
+    /// Shrinks the memory at [`ptr`] to [`new`], keeping as much of its'
+    /// contents as fits. On failure the memory is left untouched and
+    /// `None` is returned.
+    ///
+    /// # Safety
+    ///
+    /// [`ptr`] must have been allocated by this allocator for [`old`], and
+    /// [`new`] must be no larger than [`old`].
+    unsafe fn shrink(&self, ptr: NonNull<u8>, old: Layout, new: Layout) -> Option<NonNull<u8>> {
+        debug_assert!(new.size() <= old.size());
+
+        reallocate(self, ptr, old, new)
+    }
+}
+
+unsafe impl<A: SteadfastAlloc + ?Sized> SteadfastAlloc for &A {
+    #[inline(always)]
+    fn allocate(&self, layout: Layout) -> Option<NonNull<u8>> {
+        (**self).allocate(layout)
+    }
+
+    #[inline(always)]
+    unsafe fn deallocate(&self, ptr: NonNull<u8>, layout: Layout) {
+        (**self).deallocate(ptr, layout)
+    }
+
+    #[inline(always)]
+    unsafe fn grow(&self, ptr: NonNull<u8>, old: Layout, new: Layout) -> Option<NonNull<u8>> {
+        (**self).grow(ptr, old, new)
+    }
+
+    #[inline(always)]
+    unsafe fn shrink(&self, ptr: NonNull<u8>, old: Layout, new: Layout) -> Option<NonNull<u8>> {
+        (**self).shrink(ptr, old, new)
+    }
+}
+
+/// Moves the memory at [`ptr`] to a new allocation for [`new`], for
+/// allocators which can't resize it in place.
+pub(crate) unsafe fn reallocate<A: SteadfastAlloc + ?Sized>(
+    alloc: &A,
+    ptr: NonNull<u8>,
+    old: Layout,
+    new: Layout,
+) -> Option<NonNull<u8>> {
+    let new_ptr = alloc.allocate(new)?;
+
+    core::ptr::copy_nonoverlapping(ptr.as_ptr(), new_ptr.as_ptr(), old.size().min(new.size()));
+    alloc.deallocate(ptr, old);
+
+    Some(new_ptr)
+}
+
+/// A dangling pointer aligned for [`layout`], handed out for zero sized
+/// allocations.
+pub(crate) fn dangling(layout: Layout) -> NonNull<u8> {
+    unsafe { NonNull::new_unchecked(layout.align() as *mut u8) }
+}
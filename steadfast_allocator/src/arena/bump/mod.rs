@@ -1,4 +1,5 @@
 mod raw;
 mod typed;
 
-pub use raw::RawBumpArena;
+pub use raw::{Checkpoint, Overflow, RawBumpArena};
+pub use typed::TypedBumpArena;
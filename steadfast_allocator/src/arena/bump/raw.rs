@@ -1,51 +1,528 @@
+use crate::align::{align_up, ALIGNMENT};
+use crate::allocator::{self, SteadfastAlloc};
 use crate::layout::get_alignment_layout;
 use crate::SteadfastAllocator;
 use core::alloc::{GlobalAlloc, Layout};
+use core::cell::Cell;
+use core::ptr::NonNull;
 
+/// What an arena does when it has no room left for an allocation.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Overflow {
+    /// Panics, naming the arena.
+    Panic,
+
+    /// Fails the allocation, returning `None`.
+    ReturnNone,
+
+    /// Allocates another chunk, at least double the size of the last.
+    Chain,
+}
+
+/// A block of memory the arena allocates from. The memory handed out
+/// follows the header.
+#[derive(Debug)]
+struct Chunk {
+    /// The chunk that was being allocated from before this one.
+    prev: Option<NonNull<Chunk>>,
+    layout: Layout,
+}
+
+impl Chunk {
+    const HEADER: usize = match align_up(core::mem::size_of::<Chunk>(), ALIGNMENT) {
+        Some(size) => size,
+        None => panic!("Chunk header overflows"),
+    };
+
+    fn new(
+        name: &'static str,
+        capacity: usize,
+        prev: Option<NonNull<Chunk>>,
+    ) -> Option<NonNull<Chunk>> {
+        let size = Self::HEADER.checked_add(capacity)?;
+
+        if size > isize::MAX as usize - ALIGNMENT {
+            return None;
+        }
+
+        unsafe {
+            let layout = get_alignment_layout(size);
+            let chunk = NonNull::new(SteadfastAllocator.alloc_for(name, layout) as *mut Chunk)?;
+
+            chunk.as_ptr().write(Chunk { prev, layout });
+
+            Some(chunk)
+        }
+    }
+
+    /// Frees [`chunk`], returning the chunk before it.
+    unsafe fn free(chunk: NonNull<Chunk>) -> Option<NonNull<Chunk>> {
+        let Chunk { prev, layout } = chunk.as_ptr().read();
+
+        SteadfastAllocator.dealloc(chunk.as_ptr() as *mut u8, layout);
+
+        prev
+    }
+
+    fn start(chunk: NonNull<Chunk>) -> *mut u8 {
+        unsafe { (chunk.as_ptr() as *mut u8).add(Self::HEADER) }
+    }
+
+    fn end(chunk: NonNull<Chunk>) -> *mut u8 {
+        unsafe { (chunk.as_ptr() as *mut u8).add(chunk.as_ref().layout.size()) }
+    }
+
+    fn capacity(chunk: NonNull<Chunk>) -> usize {
+        Self::end(chunk) as usize - Self::start(chunk) as usize
+    }
+}
+
+/// A position in a [`RawBumpArena`], which it can be rewound to.
+#[derive(Clone, Copy, Debug)]
+pub struct Checkpoint {
+    chunk: NonNull<Chunk>,
+    cursor: *mut u8,
+}
+
+/// Hands out memory by bumping a cursor through a block allocated up
+/// front, which is freed all at once by [`reset`] or [`rewind`].
+///
+/// Values allocated in the arena are never dropped, see
+/// [`TypedBumpArena`](super::TypedBumpArena) for an arena which runs
+/// destructors.
 #[derive(Debug)]
 pub struct RawBumpArena {
     name: &'static str,
+    overflow: Overflow,
 
-    ptr: *mut u8,
-    end: *mut u8,
-
-    layout: Layout,
+    /// The newest chunk, which allocations are made from.
+    chunk: Cell<NonNull<Chunk>>,
+    cursor: Cell<*mut u8>,
 }
 
+// The arena owns its' chunks, and can only be moved once nothing borrows
+// from it.
+unsafe impl Send for RawBumpArena {}
+
 impl RawBumpArena {
+    /// Creates an arena of [`capacity`] bytes, which panics when it runs
+    /// out of room.
     pub fn new(name: &'static str, capacity: usize) -> Self {
+        Self::with_overflow(name, capacity, Overflow::Panic)
+    }
+
+    pub fn with_overflow(name: &'static str, capacity: usize, overflow: Overflow) -> Self {
+        let chunk = match Chunk::new(name, capacity, None) {
+            Some(chunk) => chunk,
+            None => panic!("Allocation Failed"),
+        };
+
+        Self {
+            name,
+            overflow,
+            chunk: Cell::new(chunk),
+            cursor: Cell::new(Chunk::start(chunk)),
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        self.name
+    }
+
+    pub fn overflow(&self) -> Overflow {
+        self.overflow
+    }
+
+    /// The number of bytes the arena can hold without chaining another
+    /// chunk, including those already allocated.
+    pub fn capacity(&self) -> usize {
+        let mut capacity = 0;
+        let mut chunk = Some(self.chunk.get());
+
+        while let Some(current) = chunk {
+            capacity += Chunk::capacity(current);
+            chunk = unsafe { current.as_ref().prev };
+        }
+
+        capacity
+    }
+
+    /// Allocates uninitialized memory for [`layout`], handling a full
+    /// arena as set by its' [`Overflow`].
+    pub fn alloc_layout(&self, layout: Layout) -> Option<NonNull<u8>> {
+        match self.try_alloc_layout(layout) {
+            None if self.overflow == Overflow::Panic => self.out_of_memory(),
+            ptr => ptr,
+        }
+    }
+
+    /// Allocates uninitialized memory for [`layout`], chaining another
+    /// chunk if the arena is set to, or returns `None` if there is no room.
+    pub fn try_alloc_layout(&self, layout: Layout) -> Option<NonNull<u8>> {
+        if let Some(ptr) = self.bump(layout) {
+            return Some(ptr);
+        }
+
+        match self.overflow {
+            Overflow::Panic | Overflow::ReturnNone => None,
+            Overflow::Chain => {
+                self.chain(layout)?;
+                self.bump(layout)
+            }
+        }
+    }
+
+    /// Moves [`value`] into the arena, or returns `None` if there is no
+    /// room for it.
+    #[allow(clippy::mut_from_ref)]
+    pub fn try_alloc<T>(&self, value: T) -> Option<&mut T> {
+        let ptr = self.try_alloc_layout(Layout::new::<T>())?.cast::<T>();
+
+        unsafe {
+            ptr.as_ptr().write(value);
+            Some(&mut *ptr.as_ptr())
+        }
+    }
+
+    /// Moves [`value`] into the arena, panicking if there is no room.
+    #[allow(clippy::mut_from_ref)]
+    pub fn alloc<T>(&self, value: T) -> &mut T {
+        match self.try_alloc(value) {
+            Some(value) => value,
+            None => self.out_of_memory(),
+        }
+    }
+
+    #[allow(clippy::mut_from_ref)]
+    pub fn try_alloc_slice_copy<T: Copy>(&self, slice: &[T]) -> Option<&mut [T]> {
+        let ptr = self.try_alloc_layout(Layout::for_value(slice))?.cast::<T>();
+
+        unsafe {
+            core::ptr::copy_nonoverlapping(slice.as_ptr(), ptr.as_ptr(), slice.len());
+            Some(core::slice::from_raw_parts_mut(ptr.as_ptr(), slice.len()))
+        }
+    }
+
+    /// Copies [`slice`] into the arena, panicking if there is no room.
+    #[allow(clippy::mut_from_ref)]
+    pub fn alloc_slice_copy<T: Copy>(&self, slice: &[T]) -> &mut [T] {
+        match self.try_alloc_slice_copy(slice) {
+            Some(slice) => slice,
+            None => self.out_of_memory(),
+        }
+    }
+
+    #[allow(clippy::mut_from_ref)]
+    pub fn try_alloc_str(&self, string: &str) -> Option<&mut str> {
+        let bytes = self.try_alloc_slice_copy(string.as_bytes())?;
+
+        unsafe { Some(core::str::from_utf8_unchecked_mut(bytes)) }
+    }
+
+    /// Copies [`string`] into the arena, panicking if there is no room.
+    #[allow(clippy::mut_from_ref)]
+    pub fn alloc_str(&self, string: &str) -> &mut str {
+        match self.try_alloc_str(string) {
+            Some(string) => string,
+            None => self.out_of_memory(),
+        }
+    }
+
+    /// Overwrites everything allocated in the arena with [`byte`], so that
+    /// reading it after it is freed is easier to spot.
+    pub(crate) fn poison(&mut self, byte: u8) {
+        let mut chunk = Some(self.chunk.get());
+        let mut end = self.cursor.get();
+
+        while let Some(current) = chunk {
+            let start = Chunk::start(current);
+
+            unsafe {
+                core::ptr::write_bytes(start, byte, end as usize - start as usize);
+                chunk = current.as_ref().prev;
+            }
+
+            // Older chunks were filled before the next was chained
+            if let Some(prev) = chunk {
+                end = Chunk::end(prev);
+            }
+        }
+    }
+
+    /// Frees everything allocated in the arena.
+    ///
+    /// Only the newest chunk is kept, which is the largest when chunks
+    /// were chained, so an arena reset every frame settles on a single
+    /// chunk large enough for a frame.
+    pub fn reset(&mut self) {
+        let chunk = self.chunk.get();
+
         unsafe {
-            let layout = get_alignment_layout(capacity);
-            let ptr = SteadfastAllocator.alloc(layout);
+            let mut prev = chunk.as_ref().prev;
+
+            while let Some(older) = prev {
+                prev = Chunk::free(older);
+            }
+
+            (*chunk.as_ptr()).prev = None;
+        }
+
+        self.cursor.set(Chunk::start(chunk));
+    }
+
+    /// Marks the current position of the arena, so that everything
+    /// allocated after it can be freed with [`rewind`].
+    pub fn checkpoint(&self) -> Checkpoint {
+        Checkpoint {
+            chunk: self.chunk.get(),
+            cursor: self.cursor.get(),
+        }
+    }
+
+    /// Frees everything allocated since [`checkpoint`] was taken.
+    ///
+    /// Panics if the checkpoint is not from this arena, or the memory it
+    /// marks was freed by an earlier [`reset`] or [`rewind`].
+    pub fn rewind(&mut self, checkpoint: Checkpoint) {
+        if !self.contains(checkpoint) {
+            panic!("Checkpoint is not in arena {}", self.name);
+        }
+
+        let mut chunk = self.chunk.get();
+
+        while chunk != checkpoint.chunk {
+            chunk = unsafe { Chunk::free(chunk) }.unwrap();
+        }
+
+        self.chunk.set(chunk);
+        self.cursor.set(checkpoint.cursor);
+    }
 
-            if ptr.is_null() {
-                panic!("Allocation Failed");
+    fn contains(&self, checkpoint: Checkpoint) -> bool {
+        let mut chunk = Some(self.chunk.get());
+
+        while let Some(current) = chunk {
+            if current == checkpoint.chunk {
+                let cursor = checkpoint.cursor as usize;
+
+                return cursor >= Chunk::start(current) as usize
+                    && cursor <= Chunk::end(current) as usize;
             }
 
-            let end = ptr.offset(layout.size() as isize);
+            chunk = unsafe { current.as_ref().prev };
+        }
+
+        false
+    }
+
+    /// Allocates [`layout`] from the current chunk, if it has room.
+    fn bump(&self, layout: Layout) -> Option<NonNull<u8>> {
+        let cursor = self.cursor.get();
+        let end = Chunk::end(self.chunk.get()) as usize;
+
+        let start = align_up(cursor as usize, layout.align())?;
+        let next = start.checked_add(layout.size())?;
+
+        if next > end {
+            return None;
+        }
+
+        unsafe {
+            let ptr = cursor.add(start - cursor as usize);
+            self.cursor.set(ptr.add(layout.size()));
+
+            NonNull::new(ptr)
+        }
+    }
+
+    /// Starts allocating from a new chunk with room for [`layout`].
+    fn chain(&self, layout: Layout) -> Option<()> {
+        let chunk = self.chunk.get();
+
+        let needed = layout.size().checked_add(layout.align())?;
+        let capacity = Chunk::capacity(chunk).saturating_mul(2).max(needed);
 
-            Self {
-                name,
-                ptr,
-                end,
-                layout,
+        let chunk = Chunk::new(self.name, capacity, Some(chunk))?;
+
+        self.chunk.set(chunk);
+        self.cursor.set(Chunk::start(chunk));
+
+        Some(())
+    }
+
+    /// Whether [`ptr`] is the newest allocation, of [`layout`].
+    fn is_last(&self, ptr: NonNull<u8>, layout: Layout) -> bool {
+        ptr.as_ptr() as usize + layout.size() == self.cursor.get() as usize
+    }
+
+    /// Resizes the newest allocation in place, if it still fits in the
+    /// current chunk.
+    fn resize_last(&self, ptr: NonNull<u8>, old: Layout, new: Layout) -> bool {
+        let start = ptr.as_ptr() as usize;
+
+        if !self.is_last(ptr, old) || !start.is_multiple_of(new.align()) {
+            return false;
+        }
+
+        match start.checked_add(new.size()) {
+            Some(next) if next <= Chunk::end(self.chunk.get()) as usize => {
+                self.cursor.set(unsafe { ptr.as_ptr().add(new.size()) });
+                true
             }
+            _ => false,
+        }
+    }
+
+    fn out_of_memory(&self) -> ! {
+        panic!("Arena {} is out of memory", self.name)
+    }
+}
+
+// Memory is only freed when it was the newest allocation, and grows in
+// place when it still is. Allocations never panic, whatever the overflow,
+// so callers like `ArenaVec::try_reserve` can handle running out.
+unsafe impl SteadfastAlloc for RawBumpArena {
+    fn allocate(&self, layout: Layout) -> Option<NonNull<u8>> {
+        self.try_alloc_layout(layout)
+    }
+
+    unsafe fn deallocate(&self, ptr: NonNull<u8>, layout: Layout) {
+        if self.is_last(ptr, layout) {
+            self.cursor.set(ptr.as_ptr());
         }
     }
 
-    // pub fn alloc<T>(&self, item: T) -> &mut T {}
+    unsafe fn grow(&self, ptr: NonNull<u8>, old: Layout, new: Layout) -> Option<NonNull<u8>> {
+        if self.resize_last(ptr, old, new) {
+            return Some(ptr);
+        }
+
+        allocator::reallocate(self, ptr, old, new)
+    }
+
+    unsafe fn shrink(&self, ptr: NonNull<u8>, old: Layout, new: Layout) -> Option<NonNull<u8>> {
+        if self.resize_last(ptr, old, new) || (ptr.as_ptr() as usize).is_multiple_of(new.align()) {
+            return Some(ptr);
+        }
+
+        allocator::reallocate(self, ptr, old, new)
+    }
 }
 
 impl Drop for RawBumpArena {
     fn drop(&mut self) {
+        let mut chunk = Some(self.chunk.get());
+
+        while let Some(current) = chunk {
+            chunk = unsafe { Chunk::free(current) };
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Overflow, RawBumpArena};
+    use crate::SteadfastAlloc;
+    use core::alloc::Layout;
+
+    #[test]
+    fn allocations_are_aligned_and_distinct() {
+        let arena = RawBumpArena::new("test", 256);
+
+        let byte = arena.alloc(7u8);
+        let word = arena.alloc(0x1234_5678_9abc_def0u64);
+        let aligned = arena.alloc_layout(Layout::from_size_align(16, 64).unwrap());
+
+        assert_eq!(*byte, 7);
+        assert_eq!(*word, 0x1234_5678_9abc_def0);
+        assert_eq!(word as *mut u64 as usize % 8, 0);
+        assert_eq!(aligned.unwrap().as_ptr() as usize % 64, 0);
+
+        assert_eq!(arena.alloc_slice_copy(&[1, 2, 3]), &[1, 2, 3]);
+        assert_eq!(arena.alloc_str("steadfast"), "steadfast");
+    }
+
+    #[test]
+    fn overflow_can_fail_or_chain() {
+        let arena = RawBumpArena::with_overflow("none", 16, Overflow::ReturnNone);
+        assert!(arena.try_alloc([0u8; 16]).is_some());
+        assert!(arena.try_alloc(0u8).is_none());
+
+        let mut arena = RawBumpArena::with_overflow("chain", 16, Overflow::Chain);
+        let first = arena.alloc([1u8; 16]);
+        let second = arena.alloc([2u8; 24]);
+
+        assert_eq!(first, &[1; 16]);
+        assert_eq!(second, &[2; 24]);
+        assert!(arena.capacity() >= 16 + 32);
+
+        // Only the newest chunk is kept
+        arena.reset();
+        assert_eq!(arena.capacity(), 32);
+    }
+
+    #[test]
+    fn try_alloc_fails_instead_of_panicking() {
+        let arena = RawBumpArena::new("full", 8);
+        assert!(arena.try_alloc(0u64).is_some());
+        assert!(arena.try_alloc(0u8).is_none());
+        assert!(arena.try_alloc_str("full").is_none());
+        assert!(arena.try_alloc_layout(Layout::new::<u8>()).is_none());
+        assert!(arena.allocate(Layout::new::<u8>()).is_none());
+    }
+
+    #[test]
+    #[should_panic(expected = "Arena full is out of memory")]
+    fn overflow_panics_by_default() {
+        let arena = RawBumpArena::new("full", 8);
+        arena.alloc(0u64);
+        arena.alloc(0u8);
+    }
+
+    #[test]
+    fn reset_and_rewind_reuse_memory() {
+        let mut arena = RawBumpArena::with_overflow("rewind", 64, Overflow::Chain);
+
+        let first = arena.alloc(1u32) as *mut u32;
+        arena.reset();
+        assert_eq!(arena.alloc(2u32) as *mut u32, first);
+
+        let checkpoint = arena.checkpoint();
+        let marked = arena.alloc(3u32) as *mut u32;
+        arena.alloc([0u8; 128]);
+
+        arena.rewind(checkpoint);
+        assert_eq!(arena.capacity(), 64);
+        assert_eq!(arena.alloc(4u32) as *mut u32, marked);
+    }
+
+    #[test]
+    fn newest_allocations_resize_in_place() {
+        let arena = RawBumpArena::new("resize", 256);
+        let small = Layout::array::<u32>(4).unwrap();
+        let large = Layout::array::<u32>(16).unwrap();
+
         unsafe {
-            SteadfastAllocator.dealloc(
-                self.ptr,
-                Layout::from_size_align_unchecked(
-                    self.end as usize - self.ptr as usize,
-                    self.layout.align(),
-                ),
-            )
+            let ptr = arena.allocate(small).unwrap();
+            assert_eq!(arena.grow(ptr, small, large), Some(ptr));
+
+            // Older allocations move, and can't be freed
+            let other = arena.allocate(small).unwrap();
+            let moved = arena.grow(ptr, large, large.align_to(64).unwrap()).unwrap();
+            assert_ne!(moved, ptr);
+
+            arena.deallocate(moved, large);
+            assert_eq!(arena.allocate(small), Some(moved));
+            arena.deallocate(other, small);
         }
     }
+
+    #[test]
+    #[should_panic(expected = "Checkpoint is not in arena other")]
+    fn foreign_checkpoints_are_refused() {
+        let arena = RawBumpArena::new("arena", 64);
+        let mut other = RawBumpArena::new("other", 64);
+
+        other.rewind(arena.checkpoint());
+    }
 }
@@ -0,0 +1,161 @@
+use super::{Overflow, RawBumpArena};
+use core::cell::Cell;
+use core::marker::PhantomData;
+use core::ptr::NonNull;
+
+/// An allocation in a [`TypedBumpArena`], linked to the one before it so
+/// the arena can find every value to drop.
+struct Entry<T> {
+    value: T,
+    prev: Option<NonNull<Entry<T>>>,
+}
+
+/// A bump arena holding values of a single type, which are dropped when
+/// the arena is reset or dropped.
+pub struct TypedBumpArena<T> {
+    raw: RawBumpArena,
+
+    /// The newest value in the arena.
+    last: Cell<Option<NonNull<Entry<T>>>>,
+    len: Cell<usize>,
+
+    _marker: PhantomData<T>,
+}
+
+unsafe impl<T: Send> Send for TypedBumpArena<T> {}
+
+impl<T> TypedBumpArena<T> {
+    /// Creates an arena with room for [`capacity`] values, which panics
+    /// when it runs out of room.
+    pub fn new(name: &'static str, capacity: usize) -> Self {
+        Self::with_overflow(name, capacity, Overflow::Panic)
+    }
+
+    pub fn with_overflow(name: &'static str, capacity: usize, overflow: Overflow) -> Self {
+        let entry = core::mem::size_of::<Entry<T>>();
+        let align = core::mem::align_of::<Entry<T>>();
+
+        // Leave room to align the first value
+        let bytes = capacity
+            .checked_mul(entry)
+            .and_then(|bytes| bytes.checked_add(align))
+            .expect("Arena capacity overflows");
+
+        Self {
+            raw: RawBumpArena::with_overflow(name, bytes, overflow),
+            last: Cell::new(None),
+            len: Cell::new(0),
+            _marker: PhantomData,
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        self.raw.name()
+    }
+
+    /// The number of values in the arena.
+    pub fn len(&self) -> usize {
+        self.len.get()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Moves [`value`] into the arena, or returns `None` if there is no
+    /// room for it.
+    #[allow(clippy::mut_from_ref)]
+    pub fn try_alloc(&self, value: T) -> Option<&mut T> {
+        let entry = self.raw.try_alloc(Entry {
+            value,
+            prev: self.last.get(),
+        })?;
+
+        self.last.set(Some(NonNull::from(&mut *entry)));
+        self.len.set(self.len.get() + 1);
+
+        Some(&mut entry.value)
+    }
+
+    /// Moves [`value`] into the arena, panicking if there is no room.
+    #[allow(clippy::mut_from_ref)]
+    pub fn alloc(&self, value: T) -> &mut T {
+        match self.try_alloc(value) {
+            Some(value) => value,
+            None => panic!("Arena {} is out of memory", self.name()),
+        }
+    }
+
+    /// Drops every value in the arena, newest first, and frees their
+    /// memory.
+    pub fn reset(&mut self) {
+        self.drop_values();
+        self.raw.reset();
+    }
+
+    fn drop_values(&mut self) {
+        let mut entry = self.last.take();
+        self.len.set(0);
+
+        while let Some(current) = entry {
+            unsafe {
+                entry = current.as_ref().prev;
+                core::ptr::drop_in_place(&mut (*current.as_ptr()).value);
+            }
+        }
+    }
+}
+
+impl<T> Drop for TypedBumpArena<T> {
+    fn drop(&mut self) {
+        self.drop_values();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::TypedBumpArena;
+    use crate::arena::bump::Overflow;
+    use core::cell::Cell;
+
+    struct Counted<'a> {
+        drops: &'a Cell<usize>,
+    }
+
+    impl Drop for Counted<'_> {
+        fn drop(&mut self) {
+            self.drops.set(self.drops.get() + 1);
+        }
+    }
+
+    #[test]
+    fn reset_drops_values() {
+        let drops = Cell::new(0);
+        let mut arena = TypedBumpArena::with_overflow("counted", 2, Overflow::Chain);
+
+        for _ in 0..5 {
+            arena.alloc(Counted { drops: &drops });
+        }
+
+        assert_eq!(arena.len(), 5);
+
+        arena.reset();
+        assert_eq!(drops.get(), 5);
+        assert!(arena.is_empty());
+
+        arena.alloc(Counted { drops: &drops });
+        drop(arena);
+        assert_eq!(drops.get(), 6);
+    }
+
+    #[test]
+    fn capacity_is_counted_in_values() {
+        let arena = TypedBumpArena::with_overflow("values", 4, Overflow::ReturnNone);
+
+        for value in 0..4u64 {
+            assert_eq!(*arena.alloc(value), value);
+        }
+
+        assert!(arena.try_alloc(4).is_none());
+    }
+}
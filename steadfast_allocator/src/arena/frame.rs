@@ -0,0 +1,142 @@
+use super::bump::{Overflow, RawBumpArena};
+use crate::SteadfastAlloc;
+use core::alloc::Layout;
+use core::ptr::NonNull;
+
+/// Written over a frame's memory when it is freed, in debug builds.
+pub const POISON: u8 = 0xFD;
+
+/// Memory for a single frame, rotated through [`FRAMES`] bump arenas.
+///
+/// Everything allocated stays valid until [`next_frame`] was called
+/// `FRAMES - 1` times, so with the default of two buffers memory allocated
+/// during a frame can be used until the end of the next. The arenas chain
+/// chunks when a frame needs more than [`capacity`], and settle on a single
+/// chunk once reset.
+///
+/// In debug builds freed memory is overwritten with [`POISON`], so holding
+/// on to it for too long shows up as garbage rather than stale data.
+#[derive(Debug)]
+pub struct FrameArena<const FRAMES: usize = 2> {
+    buffers: [RawBumpArena; FRAMES],
+    current: usize,
+    frame: u64,
+}
+
+impl<const FRAMES: usize> FrameArena<FRAMES> {
+    /// Creates [`FRAMES`] arenas of [`capacity`] bytes each.
+    pub fn new(name: &'static str, capacity: usize) -> Self {
+        assert!(FRAMES > 0, "Frame arena {} must have a buffer", name);
+
+        Self {
+            buffers: core::array::from_fn(|_| {
+                RawBumpArena::with_overflow(name, capacity, Overflow::Chain)
+            }),
+            current: 0,
+            frame: 0,
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        self.buffers[0].name()
+    }
+
+    /// The number of times [`next_frame`] was called.
+    pub fn frame(&self) -> u64 {
+        self.frame
+    }
+
+    /// The arena allocations are made from this frame.
+    pub fn current(&self) -> &RawBumpArena {
+        &self.buffers[self.current]
+    }
+
+    pub fn alloc_layout(&self, layout: Layout) -> Option<NonNull<u8>> {
+        self.current().alloc_layout(layout)
+    }
+
+    #[allow(clippy::mut_from_ref)]
+    pub fn alloc<T>(&self, value: T) -> &mut T {
+        self.current().alloc(value)
+    }
+
+    #[allow(clippy::mut_from_ref)]
+    pub fn alloc_slice_copy<T: Copy>(&self, slice: &[T]) -> &mut [T] {
+        self.current().alloc_slice_copy(slice)
+    }
+
+    #[allow(clippy::mut_from_ref)]
+    pub fn alloc_str(&self, string: &str) -> &mut str {
+        self.current().alloc_str(string)
+    }
+
+    /// Starts a new frame, freeing the memory of the oldest.
+    pub fn next_frame(&mut self) {
+        self.current = (self.current + 1) % FRAMES;
+        self.frame += 1;
+
+        let buffer = &mut self.buffers[self.current];
+
+        #[cfg(debug_assertions)]
+        buffer.poison(POISON);
+
+        buffer.reset();
+    }
+}
+
+// Memory allocated in an earlier frame is never the newest allocation of
+// the current one, so is left to be freed with its' frame.
+unsafe impl<const FRAMES: usize> SteadfastAlloc for FrameArena<FRAMES> {
+    fn allocate(&self, layout: Layout) -> Option<NonNull<u8>> {
+        self.current().allocate(layout)
+    }
+
+    unsafe fn deallocate(&self, ptr: NonNull<u8>, layout: Layout) {
+        self.current().deallocate(ptr, layout)
+    }
+
+    unsafe fn grow(&self, ptr: NonNull<u8>, old: Layout, new: Layout) -> Option<NonNull<u8>> {
+        self.current().grow(ptr, old, new)
+    }
+
+    unsafe fn shrink(&self, ptr: NonNull<u8>, old: Layout, new: Layout) -> Option<NonNull<u8>> {
+        self.current().shrink(ptr, old, new)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{FrameArena, POISON};
+
+    #[test]
+    fn memory_lives_until_the_end_of_the_next_frame() {
+        let mut arena: FrameArena = FrameArena::new("frame", 256);
+
+        let command = arena.alloc(0x1234u32) as *mut u32;
+        arena.next_frame();
+        assert_eq!(unsafe { *command }, 0x1234);
+
+        let event = arena.alloc_str("next") as *mut str;
+        arena.next_frame();
+        assert_eq!(unsafe { &*event }, "next");
+        assert_eq!(arena.frame(), 2);
+
+        // Buffers are reused once their frame is over
+        assert_eq!(arena.alloc(0u32) as *mut u32, command);
+    }
+
+    #[test]
+    #[cfg(debug_assertions)]
+    fn freed_frames_are_poisoned() {
+        let mut arena = FrameArena::<3>::new("frame", 64);
+
+        let value = arena.alloc(0u64) as *mut u64;
+
+        arena.next_frame();
+        arena.next_frame();
+        assert_eq!(unsafe { *value }, 0);
+
+        arena.next_frame();
+        assert_eq!(unsafe { *value }, u64::from_ne_bytes([POISON; 8]));
+    }
+}
@@ -1 +1,10 @@
 pub mod bump;
+pub mod frame;
+// Only the targets whose virtual memory functions were checked, see
+// `vm::unix`
+#[cfg(any(
+    target_os = "linux",
+    target_vendor = "apple",
+    target_family = "windows"
+))]
+pub mod vm;
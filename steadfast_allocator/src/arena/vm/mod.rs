@@ -0,0 +1,318 @@
+#[cfg(target_family = "unix")]
+pub(crate) mod unix;
+#[cfg(target_family = "unix")]
+pub(crate) use unix as os;
+
+#[cfg(target_family = "windows")]
+pub(crate) mod windows;
+#[cfg(target_family = "windows")]
+pub(crate) use windows as os;
+
+use crate::align::align_up;
+use crate::allocator::{self, SteadfastAlloc};
+use core::alloc::Layout;
+use core::cell::Cell;
+use core::ptr::NonNull;
+
+/// Pages are committed at least this many bytes at a time, to avoid a
+/// system call for every page.
+const COMMIT_STEP: usize = 64 * 1024;
+
+/// A bump arena which reserves a range of address space up front, and
+/// commits pages of it as they are allocated from.
+///
+/// Unlike [`RawBumpArena`](super::bump::RawBumpArena) the arena never
+/// chains chunks, so everything allocated in it stays at the same address
+/// and is contiguous. Reserving address space is cheap, so the range can
+/// be much larger than the memory the arena is expected to use.
+#[derive(Debug)]
+pub struct VirtualArena {
+    name: &'static str,
+    base: NonNull<u8>,
+    reserved: usize,
+    page_size: usize,
+
+    cursor: Cell<usize>,
+    /// The number of bytes from [`base`] which are committed.
+    committed: Cell<usize>,
+}
+
+// The arena owns its' range, and can only be moved once nothing borrows
+// from it.
+unsafe impl Send for VirtualArena {}
+
+impl VirtualArena {
+    /// Reserves [`reserve`] bytes of address space, rounded up to whole
+    /// pages. No memory is committed until it is allocated.
+    pub fn new(name: &'static str, reserve: usize) -> Self {
+        let page_size = os::page_size();
+
+        let reserved = match align_up(reserve.max(1), page_size) {
+            Some(reserved) => reserved,
+            None => panic!("Allocation Failed"),
+        };
+
+        let base = match NonNull::new(unsafe { os::reserve(reserved) }) {
+            Some(base) => base,
+            None => panic!("Allocation Failed"),
+        };
+
+        Self {
+            name,
+            base,
+            reserved,
+            page_size,
+            cursor: Cell::new(0),
+            committed: Cell::new(0),
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        self.name
+    }
+
+    /// The number of bytes of address space reserved.
+    pub fn reserved(&self) -> usize {
+        self.reserved
+    }
+
+    /// The number of bytes backed by memory.
+    pub fn committed(&self) -> usize {
+        self.committed.get()
+    }
+
+    /// The number of bytes allocated, including padding.
+    pub fn used(&self) -> usize {
+        self.cursor.get()
+    }
+
+    /// Allocates uninitialized memory for [`layout`], committing pages as
+    /// needed. Returns `None` if the reserved range is full or pages
+    /// couldn't be committed.
+    pub fn alloc_layout(&self, layout: Layout) -> Option<NonNull<u8>> {
+        let base = self.base.as_ptr() as usize;
+
+        let start = align_up(base.checked_add(self.cursor.get())?, layout.align())? - base;
+        let end = start.checked_add(layout.size())?;
+
+        if end > self.reserved {
+            return None;
+        }
+
+        if end > self.committed.get() {
+            self.commit(end)?;
+        }
+
+        self.cursor.set(end);
+
+        unsafe { Some(NonNull::new_unchecked(self.base.as_ptr().add(start))) }
+    }
+
+    /// Moves [`value`] into the arena, or returns `None` if there is no
+    /// room for it.
+    #[allow(clippy::mut_from_ref)]
+    pub fn try_alloc<T>(&self, value: T) -> Option<&mut T> {
+        let ptr = self.alloc_layout(Layout::new::<T>())?.cast::<T>();
+
+        unsafe {
+            ptr.as_ptr().write(value);
+            Some(&mut *ptr.as_ptr())
+        }
+    }
+
+    /// Moves [`value`] into the arena, panicking if there is no room.
+    #[allow(clippy::mut_from_ref)]
+    pub fn alloc<T>(&self, value: T) -> &mut T {
+        match self.try_alloc(value) {
+            Some(value) => value,
+            None => self.out_of_memory(),
+        }
+    }
+
+    #[allow(clippy::mut_from_ref)]
+    pub fn try_alloc_slice_copy<T: Copy>(&self, slice: &[T]) -> Option<&mut [T]> {
+        let ptr = self.alloc_layout(Layout::for_value(slice))?.cast::<T>();
+
+        unsafe {
+            core::ptr::copy_nonoverlapping(slice.as_ptr(), ptr.as_ptr(), slice.len());
+            Some(core::slice::from_raw_parts_mut(ptr.as_ptr(), slice.len()))
+        }
+    }
+
+    /// Copies [`slice`] into the arena, panicking if there is no room.
+    #[allow(clippy::mut_from_ref)]
+    pub fn alloc_slice_copy<T: Copy>(&self, slice: &[T]) -> &mut [T] {
+        match self.try_alloc_slice_copy(slice) {
+            Some(slice) => slice,
+            None => self.out_of_memory(),
+        }
+    }
+
+    /// Copies [`string`] into the arena, panicking if there is no room.
+    #[allow(clippy::mut_from_ref)]
+    pub fn alloc_str(&self, string: &str) -> &mut str {
+        let bytes = self.alloc_slice_copy(string.as_bytes());
+
+        unsafe { core::str::from_utf8_unchecked_mut(bytes) }
+    }
+
+    /// Frees everything allocated in the arena, keeping its' pages
+    /// committed for reuse.
+    pub fn reset(&mut self) {
+        self.cursor.set(0);
+    }
+
+    /// Frees everything allocated in the arena and returns its' pages to
+    /// the OS, e.g. after unloading a level.
+    pub fn reset_and_decommit(&mut self) {
+        self.cursor.set(0);
+
+        if self.committed.get() > 0 {
+            unsafe { os::decommit(self.base.as_ptr(), self.committed.get()) };
+            self.committed.set(0);
+        }
+    }
+
+    /// Commits pages up to at least [`end`].
+    fn commit(&self, end: usize) -> Option<()> {
+        let committed = self.committed.get();
+
+        let step = align_up(COMMIT_STEP, self.page_size)?;
+        let target = align_up(end, self.page_size)?
+            .max(committed.saturating_add(step))
+            .min(self.reserved);
+
+        let committing =
+            unsafe { os::commit(self.base.as_ptr().add(committed), target - committed) };
+
+        if !committing {
+            return None;
+        }
+
+        self.committed.set(target);
+
+        Some(())
+    }
+
+    /// Resizes the newest allocation in place, committing pages as
+    /// needed.
+    fn resize_last(&self, ptr: NonNull<u8>, old: Layout, new: Layout) -> bool {
+        let start = ptr.as_ptr() as usize - self.base.as_ptr() as usize;
+
+        if start + old.size() != self.cursor.get()
+            || !(ptr.as_ptr() as usize).is_multiple_of(new.align())
+        {
+            return false;
+        }
+
+        let end = match start.checked_add(new.size()) {
+            Some(end) if end <= self.reserved => end,
+            _ => return false,
+        };
+
+        if end > self.committed.get() && self.commit(end).is_none() {
+            return false;
+        }
+
+        self.cursor.set(end);
+
+        true
+    }
+
+    fn out_of_memory(&self) -> ! {
+        panic!("Arena {} is out of memory", self.name)
+    }
+}
+
+// Memory is only freed when it was the newest allocation, and grows in
+// place when it still is.
+unsafe impl SteadfastAlloc for VirtualArena {
+    fn allocate(&self, layout: Layout) -> Option<NonNull<u8>> {
+        self.alloc_layout(layout)
+    }
+
+    unsafe fn deallocate(&self, ptr: NonNull<u8>, layout: Layout) {
+        let start = ptr.as_ptr() as usize - self.base.as_ptr() as usize;
+
+        if start + layout.size() == self.cursor.get() {
+            self.cursor.set(start);
+        }
+    }
+
+    unsafe fn grow(&self, ptr: NonNull<u8>, old: Layout, new: Layout) -> Option<NonNull<u8>> {
+        if self.resize_last(ptr, old, new) {
+            return Some(ptr);
+        }
+
+        allocator::reallocate(self, ptr, old, new)
+    }
+
+    unsafe fn shrink(&self, ptr: NonNull<u8>, old: Layout, new: Layout) -> Option<NonNull<u8>> {
+        if self.resize_last(ptr, old, new) || (ptr.as_ptr() as usize).is_multiple_of(new.align()) {
+            return Some(ptr);
+        }
+
+        allocator::reallocate(self, ptr, old, new)
+    }
+}
+
+impl Drop for VirtualArena {
+    fn drop(&mut self) {
+        unsafe { os::release(self.base.as_ptr(), self.reserved) }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::VirtualArena;
+    use core::alloc::Layout;
+
+    #[test]
+    #[cfg(target_pointer_width = "64")]
+    fn huge_ranges_commit_on_demand() {
+        let arena = VirtualArena::new("level", 64 << 30);
+
+        assert_eq!(arena.reserved(), 64 << 30);
+        assert_eq!(arena.committed(), 0);
+
+        let first = arena.alloc(1u64) as *mut u64;
+        let committed = arena.committed();
+        assert!(committed > 0);
+
+        // Addresses stay stable as more pages are committed
+        let large = arena.alloc_layout(Layout::from_size_align(8 << 20, 4096).unwrap());
+        assert_eq!(large.unwrap().as_ptr() as usize % 4096, 0);
+        assert!(arena.committed() > committed);
+        assert_eq!(unsafe { *first }, 1);
+        assert_eq!(arena.alloc_str("level"), "level");
+    }
+
+    #[test]
+    fn full_ranges_return_none() {
+        let arena = VirtualArena::new("small", 1);
+        let reserved = arena.reserved();
+
+        assert!(arena
+            .alloc_layout(Layout::array::<u8>(reserved).unwrap())
+            .is_some());
+        assert!(arena.try_alloc(0u8).is_none());
+    }
+
+    #[test]
+    fn reset_can_decommit() {
+        let mut arena = VirtualArena::new("reset", 16 << 20);
+
+        let first = arena.alloc([7u8; 4096]) as *mut [u8; 4096];
+        arena.reset();
+        assert_eq!(arena.used(), 0);
+        assert!(arena.committed() > 0);
+
+        arena.reset_and_decommit();
+        assert_eq!(arena.committed(), 0);
+
+        // Decommitted pages come back zeroed
+        let second = arena.alloc_layout(Layout::new::<[u8; 4096]>()).unwrap();
+        assert_eq!(second.as_ptr() as *mut [u8; 4096], first);
+        assert_eq!(unsafe { *first }, [0; 4096]);
+    }
+}
@@ -0,0 +1,94 @@
+// The constants and types differ between every libc, so the module is
+// only built for the targets they were checked against, see `arena::vm`.
+use core::ffi::{c_int, c_long, c_void};
+
+#[cfg(all(target_os = "linux", not(target_env = "musl")))]
+#[allow(non_camel_case_types)]
+type off_t = c_long;
+#[cfg(any(target_env = "musl", target_vendor = "apple"))]
+#[allow(non_camel_case_types)]
+type off_t = i64;
+
+const PROT_NONE: c_int = 0;
+const PROT_READ: c_int = 1;
+const PROT_WRITE: c_int = 2;
+
+const MAP_PRIVATE: c_int = 0x02;
+const MAP_FIXED: c_int = 0x10;
+const MAP_FAILED: *mut c_void = !0 as *mut c_void;
+
+#[cfg(target_os = "linux")]
+const MAP_ANONYMOUS: c_int = 0x20;
+#[cfg(target_vendor = "apple")]
+const MAP_ANONYMOUS: c_int = 0x1000;
+
+/// Reserved pages are never written until committed, so they don't need
+/// to count against the overcommit limit.
+#[cfg(target_os = "linux")]
+const MAP_NORESERVE: c_int = 0x4000;
+#[cfg(target_vendor = "apple")]
+const MAP_NORESERVE: c_int = 0;
+
+#[cfg(target_os = "linux")]
+const SC_PAGESIZE: c_int = 30;
+#[cfg(target_vendor = "apple")]
+const SC_PAGESIZE: c_int = 29;
+
+pub fn page_size() -> usize {
+    unsafe { sysconf(SC_PAGESIZE) as usize }
+}
+
+/// Reserves [`size`] bytes of address space, which can't be accessed
+/// until committed. Returns null on failure.
+pub unsafe fn reserve(size: usize) -> *mut u8 {
+    map_reserved(core::ptr::null_mut(), size, 0)
+}
+
+pub unsafe fn commit(ptr: *mut u8, size: usize) -> bool {
+    mprotect(ptr as *mut c_void, size, PROT_READ | PROT_WRITE) == 0
+}
+
+/// Returns the pages to the OS, so they are zeroed if committed again.
+///
+/// `madvise(MADV_DONTNEED)` only zeroes the pages on Linux, so they are
+/// replaced with freshly reserved ones instead.
+pub unsafe fn decommit(ptr: *mut u8, size: usize) {
+    map_reserved(ptr, size, MAP_FIXED);
+}
+
+pub unsafe fn release(ptr: *mut u8, size: usize) {
+    munmap(ptr as *mut c_void, size);
+}
+
+/// Maps [`size`] bytes of inaccessible pages at [`addr`], if [`flags`]
+/// has `MAP_FIXED`, or anywhere otherwise. Returns null on failure.
+unsafe fn map_reserved(addr: *mut u8, size: usize, flags: c_int) -> *mut u8 {
+    let ptr = mmap(
+        addr as *mut c_void,
+        size,
+        PROT_NONE,
+        MAP_PRIVATE | MAP_ANONYMOUS | MAP_NORESERVE | flags,
+        -1,
+        0,
+    );
+
+    if ptr == MAP_FAILED {
+        core::ptr::null_mut()
+    } else {
+        ptr as *mut u8
+    }
+}
+
+extern "C" {
+    fn mmap(
+        addr: *mut c_void,
+        len: usize,
+        prot: c_int,
+        flags: c_int,
+        fd: c_int,
+        offset: off_t,
+    ) -> *mut c_void;
+    fn munmap(addr: *mut c_void, len: usize) -> c_int;
+    fn mprotect(addr: *mut c_void, len: usize, prot: c_int) -> c_int;
+    fn sysconf(name: c_int) -> c_long;
+}
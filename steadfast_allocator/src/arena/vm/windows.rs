@@ -0,0 +1,64 @@
+use core::ffi::c_void;
+
+const MEM_COMMIT: u32 = 0x1000;
+const MEM_RESERVE: u32 = 0x2000;
+const MEM_DECOMMIT: u32 = 0x4000;
+const MEM_RELEASE: u32 = 0x8000;
+
+const PAGE_NOACCESS: u32 = 0x01;
+const PAGE_READWRITE: u32 = 0x04;
+
+#[repr(C)]
+struct SystemInfo {
+    processor_architecture: u16,
+    reserved: u16,
+    page_size: u32,
+    minimum_application_address: *mut c_void,
+    maximum_application_address: *mut c_void,
+    active_processor_mask: usize,
+    number_of_processors: u32,
+    processor_type: u32,
+    allocation_granularity: u32,
+    processor_level: u16,
+    processor_revision: u16,
+}
+
+pub fn page_size() -> usize {
+    unsafe {
+        let mut info = core::mem::MaybeUninit::<SystemInfo>::uninit();
+        GetSystemInfo(info.as_mut_ptr());
+
+        info.assume_init().page_size as usize
+    }
+}
+
+/// Reserves [`size`] bytes of address space, which can't be accessed
+/// until committed. Returns null on failure.
+pub unsafe fn reserve(size: usize) -> *mut u8 {
+    VirtualAlloc(core::ptr::null_mut(), size, MEM_RESERVE, PAGE_NOACCESS) as *mut u8
+}
+
+pub unsafe fn commit(ptr: *mut u8, size: usize) -> bool {
+    !VirtualAlloc(ptr as *mut c_void, size, MEM_COMMIT, PAGE_READWRITE).is_null()
+}
+
+/// Returns the pages to the OS, so they are zeroed if committed again.
+pub unsafe fn decommit(ptr: *mut u8, size: usize) {
+    VirtualFree(ptr as *mut c_void, size, MEM_DECOMMIT);
+}
+
+pub unsafe fn release(ptr: *mut u8, _size: usize) {
+    VirtualFree(ptr as *mut c_void, 0, MEM_RELEASE);
+}
+
+#[link(name = "kernel32")]
+extern "system" {
+    fn VirtualAlloc(
+        address: *mut c_void,
+        size: usize,
+        allocation_type: u32,
+        protect: u32,
+    ) -> *mut c_void;
+    fn VirtualFree(address: *mut c_void, size: usize, free_type: u32) -> i32;
+    fn GetSystemInfo(info: *mut SystemInfo);
+}
@@ -0,0 +1,388 @@
+use crate::SteadfastAlloc;
+use core::alloc::Layout;
+use core::borrow::Borrow;
+use core::fmt;
+use core::hash::{BuildHasher, BuildHasherDefault, Hash, Hasher};
+use core::marker::PhantomData;
+use core::ptr::NonNull;
+
+/// The 64 bit FNV-1a hash, which is fast for the short keys most engine
+/// maps have. It is not resistant to collision attacks, so shouldn't be
+/// used for keys from the network.
+#[derive(Clone, Copy, Debug)]
+pub struct FnvHasher(u64);
+
+impl Default for FnvHasher {
+    fn default() -> Self {
+        Self(0xcbf2_9ce4_8422_2325)
+    }
+}
+
+impl Hasher for FnvHasher {
+    fn write(&mut self, bytes: &[u8]) {
+        for byte in bytes {
+            self.0 ^= *byte as u64;
+            self.0 = self.0.wrapping_mul(0x0100_0000_01b3);
+        }
+    }
+
+    fn finish(&self) -> u64 {
+        self.0
+    }
+}
+
+pub type FnvBuildHasher = BuildHasherDefault<FnvHasher>;
+
+/// A hash map allocated from [`A`], using open addressing with linear
+/// probing.
+///
+/// The map keeps at most 7/8 of its' slots full, and doubles when it
+/// would be fuller. Removing entries shifts the entries after them back,
+/// so lookups never have to skip over removed entries.
+pub struct ArenaHashMap<K, V, A: SteadfastAlloc, S = FnvBuildHasher> {
+    slots: NonNull<Option<(K, V)>>,
+    /// Zero or a power of two.
+    capacity: usize,
+    len: usize,
+    hasher: S,
+    alloc: A,
+    _marker: PhantomData<(K, V)>,
+}
+
+unsafe impl<K: Send, V: Send, A: SteadfastAlloc + Send, S: Send> Send for ArenaHashMap<K, V, A, S> {}
+
+impl<K, V, A: SteadfastAlloc, S: Default> ArenaHashMap<K, V, A, S> {
+    /// Creates an empty map, which doesn't allocate until inserted to.
+    pub fn new_in(alloc: A) -> Self {
+        Self::with_hasher_in(S::default(), alloc)
+    }
+}
+
+impl<K, V, A: SteadfastAlloc, S> ArenaHashMap<K, V, A, S> {
+    pub fn with_hasher_in(hasher: S, alloc: A) -> Self {
+        Self {
+            slots: NonNull::dangling(),
+            capacity: 0,
+            len: 0,
+            hasher,
+            alloc,
+            _marker: PhantomData,
+        }
+    }
+
+    pub fn allocator(&self) -> &A {
+        &self.alloc
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// The number of slots, of which at most 7/8 are used.
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    pub fn iter(&self) -> Iter<'_, K, V> {
+        Iter {
+            slots: self.slots().iter(),
+        }
+    }
+
+    /// Removes every entry, keeping the slots for reuse.
+    pub fn clear(&mut self) {
+        for slot in self.slots_mut() {
+            *slot = None;
+        }
+
+        self.len = 0;
+    }
+
+    fn slots(&self) -> &[Option<(K, V)>] {
+        unsafe { core::slice::from_raw_parts(self.slots.as_ptr(), self.capacity) }
+    }
+
+    fn slots_mut(&mut self) -> &mut [Option<(K, V)>] {
+        unsafe { core::slice::from_raw_parts_mut(self.slots.as_ptr(), self.capacity) }
+    }
+
+    fn layout(capacity: usize) -> Option<Layout> {
+        Layout::array::<Option<(K, V)>>(capacity).ok()
+    }
+}
+
+impl<K: Hash + Eq, V, A: SteadfastAlloc, S: BuildHasher> ArenaHashMap<K, V, A, S> {
+    pub fn with_capacity_and_hasher_in(capacity: usize, hasher: S, alloc: A) -> Self {
+        let mut map = Self::with_hasher_in(hasher, alloc);
+        map.reserve(capacity);
+
+        map
+    }
+
+    /// Makes room for at least [`additional`] more entries, or returns
+    /// `None` if the allocator has no room for them.
+    pub fn try_reserve(&mut self, additional: usize) -> Option<()> {
+        let needed = self.len.checked_add(additional)?;
+
+        if needed.checked_mul(8)? <= self.capacity * 7 {
+            return Some(());
+        }
+
+        let capacity = (needed.checked_mul(8)? / 7 + 1).checked_next_power_of_two()?;
+
+        self.resize(capacity.max(8))
+    }
+
+    /// Makes room for at least [`additional`] more entries, panicking if
+    /// the allocator has no room for them.
+    pub fn reserve(&mut self, additional: usize) {
+        if self.try_reserve(additional).is_none() {
+            panic!("Allocation Failed");
+        }
+    }
+
+    /// Inserts [`value`] at [`key`], returning the value it replaced.
+    /// Panics if the allocator has no room for it.
+    pub fn insert(&mut self, key: K, value: V) -> Option<V> {
+        if let Some(index) = self.find(&key) {
+            let (_, old) = self.slots_mut()[index].as_mut().unwrap();
+
+            return Some(core::mem::replace(old, value));
+        }
+
+        self.reserve(1);
+
+        let index = self.empty_slot(&key);
+        self.slots_mut()[index] = Some((key, value));
+        self.len += 1;
+
+        None
+    }
+
+    pub fn get<Q>(&self, key: &Q) -> Option<&V>
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        let index = self.find(key)?;
+
+        self.slots()[index].as_ref().map(|(_, value)| value)
+    }
+
+    pub fn get_mut<Q>(&mut self, key: &Q) -> Option<&mut V>
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        let index = self.find(key)?;
+
+        self.slots_mut()[index].as_mut().map(|(_, value)| value)
+    }
+
+    pub fn contains_key<Q>(&self, key: &Q) -> bool
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        self.find(key).is_some()
+    }
+
+    pub fn remove<Q>(&mut self, key: &Q) -> Option<V>
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        let index = self.find(key)?;
+        let mask = self.capacity - 1;
+
+        let (_, value) = self.slots_mut()[index].take()?;
+        self.len -= 1;
+
+        // Shift back every entry after the hole which can't be found from
+        // its' ideal slot any more
+        let mut hole = index;
+        let mut next = (index + 1) & mask;
+
+        while let Some((key, _)) = &self.slots()[next] {
+            let ideal = self.ideal(key);
+
+            if hole.wrapping_sub(ideal) & mask < next.wrapping_sub(ideal) & mask {
+                let entry = self.slots_mut()[next].take();
+                self.slots_mut()[hole] = entry;
+
+                hole = next;
+            }
+
+            next = (next + 1) & mask;
+        }
+
+        Some(value)
+    }
+
+    /// The slot [`key`] would be in, if nothing collided with it.
+    fn ideal<Q: Hash + ?Sized>(&self, key: &Q) -> usize {
+        self.hasher.hash_one(key) as usize & (self.capacity - 1)
+    }
+
+    fn find<Q>(&self, key: &Q) -> Option<usize>
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        if self.len == 0 {
+            return None;
+        }
+
+        let mask = self.capacity - 1;
+        let mut index = self.ideal(key);
+
+        // Some slot is always empty, so this ends
+        loop {
+            match &self.slots()[index] {
+                Some((found, _)) if found.borrow() == key => return Some(index),
+                Some(_) => index = (index + 1) & mask,
+                None => return None,
+            }
+        }
+    }
+
+    fn empty_slot(&self, key: &K) -> usize {
+        let mask = self.capacity - 1;
+        let mut index = self.ideal(key);
+
+        while self.slots()[index].is_some() {
+            index = (index + 1) & mask;
+        }
+
+        index
+    }
+
+    /// Moves every entry into [`capacity`] new slots.
+    fn resize(&mut self, capacity: usize) -> Option<()> {
+        let slots: NonNull<Option<(K, V)>> = self.alloc.allocate(Self::layout(capacity)?)?.cast();
+
+        // The memory is uninitialized, so can't be borrowed as slots yet
+        for index in 0..capacity {
+            unsafe { slots.as_ptr().add(index).write(None) };
+        }
+
+        let old = core::mem::replace(&mut self.slots, slots);
+        let old_capacity = core::mem::replace(&mut self.capacity, capacity);
+
+        if old_capacity == 0 {
+            return Some(());
+        }
+
+        for index in 0..old_capacity {
+            if let Some((key, value)) = unsafe { old.as_ptr().add(index).read() } {
+                let index = self.empty_slot(&key);
+                self.slots_mut()[index] = Some((key, value));
+            }
+        }
+
+        unsafe {
+            self.alloc
+                .deallocate(old.cast(), Self::layout(old_capacity).unwrap())
+        };
+
+        Some(())
+    }
+}
+
+impl<K, V, A: SteadfastAlloc, S> Drop for ArenaHashMap<K, V, A, S> {
+    fn drop(&mut self) {
+        if self.capacity == 0 {
+            return;
+        }
+
+        unsafe {
+            core::ptr::drop_in_place(self.slots_mut());
+            self.alloc
+                .deallocate(self.slots.cast(), Self::layout(self.capacity).unwrap());
+        }
+    }
+}
+
+impl<K: fmt::Debug, V: fmt::Debug, A: SteadfastAlloc, S> fmt::Debug for ArenaHashMap<K, V, A, S> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_map().entries(self.iter()).finish()
+    }
+}
+
+impl<'a, K, V, A: SteadfastAlloc, S> IntoIterator for &'a ArenaHashMap<K, V, A, S> {
+    type Item = (&'a K, &'a V);
+    type IntoIter = Iter<'a, K, V>;
+
+    fn into_iter(self) -> Iter<'a, K, V> {
+        self.iter()
+    }
+}
+
+/// The entries of an [`ArenaHashMap`], in no particular order.
+pub struct Iter<'a, K, V> {
+    slots: core::slice::Iter<'a, Option<(K, V)>>,
+}
+
+impl<'a, K, V> Iterator for Iter<'a, K, V> {
+    type Item = (&'a K, &'a V);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.slots
+            .by_ref()
+            .find_map(|slot| slot.as_ref().map(|(key, value)| (key, value)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::ArenaHashMap;
+    use crate::arena::bump::{Overflow, RawBumpArena};
+    use crate::collections::ArenaString;
+    use crate::SteadfastAllocator;
+
+    #[test]
+    fn entries_can_be_found_and_removed() {
+        let mut map: ArenaHashMap<u32, u32, _> = ArenaHashMap::new_in(SteadfastAllocator);
+
+        for key in 0..1000 {
+            assert_eq!(map.insert(key, key * 2), None);
+        }
+
+        assert_eq!(map.len(), 1000);
+        assert_eq!(map.insert(7, 0), Some(14));
+
+        // Removing every other key shifts colliding entries back
+        for key in (0..1000).step_by(2) {
+            assert!(map.remove(&key).is_some());
+        }
+
+        for key in 0..1000 {
+            assert_eq!(map.get(&key).is_some(), key % 2 == 1, "{}", key);
+        }
+
+        *map.get_mut(&999).unwrap() += 1;
+        assert_eq!(map.get(&999), Some(&1999));
+        assert_eq!(map.iter().count(), 500);
+
+        map.clear();
+        assert!(map.is_empty() && !map.contains_key(&1));
+    }
+
+    #[test]
+    fn keys_can_be_borrowed_from_arena_strings() {
+        let arena = RawBumpArena::with_overflow("names", 1024, Overflow::Chain);
+        let name = ArenaString::from_str_in("player", &arena);
+
+        let mut map: ArenaHashMap<&str, usize, _> = ArenaHashMap::new_in(&arena);
+        map.insert(&name, 1);
+        map.insert("enemy", 2);
+
+        assert_eq!(map.get("player"), Some(&1));
+        assert_eq!(map.remove("enemy"), Some(2));
+        assert_eq!(map.get("enemy"), None);
+    }
+}
@@ -0,0 +1,7 @@
+mod hash_map;
+mod string;
+mod vec;
+
+pub use hash_map::{ArenaHashMap, FnvBuildHasher, FnvHasher, Iter};
+pub use string::ArenaString;
+pub use vec::ArenaVec;
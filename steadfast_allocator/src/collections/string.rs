@@ -0,0 +1,167 @@
+use super::ArenaVec;
+use crate::SteadfastAlloc;
+use core::fmt;
+use core::ops::Deref;
+
+/// A growable UTF-8 string allocated from [`A`].
+pub struct ArenaString<A: SteadfastAlloc> {
+    bytes: ArenaVec<u8, A>,
+}
+
+impl<A: SteadfastAlloc> ArenaString<A> {
+    /// Creates an empty string, which doesn't allocate until pushed to.
+    pub fn new_in(alloc: A) -> Self {
+        Self {
+            bytes: ArenaVec::new_in(alloc),
+        }
+    }
+
+    pub fn with_capacity_in(capacity: usize, alloc: A) -> Self {
+        Self {
+            bytes: ArenaVec::with_capacity_in(capacity, alloc),
+        }
+    }
+
+    /// Copies [`string`] into a new string.
+    pub fn from_str_in(string: &str, alloc: A) -> Self {
+        let mut arena_string = Self::with_capacity_in(string.len(), alloc);
+        arena_string.push_str(string);
+
+        arena_string
+    }
+
+    pub fn allocator(&self) -> &A {
+        self.bytes.allocator()
+    }
+
+    /// The length of the string in bytes.
+    pub fn len(&self) -> usize {
+        self.bytes.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.bytes.is_empty()
+    }
+
+    pub fn capacity(&self) -> usize {
+        self.bytes.capacity()
+    }
+
+    pub fn as_str(&self) -> &str {
+        // Only ever extended with whole strings
+        unsafe { core::str::from_utf8_unchecked(&self.bytes) }
+    }
+
+    pub fn as_mut_str(&mut self) -> &mut str {
+        unsafe { core::str::from_utf8_unchecked_mut(&mut self.bytes) }
+    }
+
+    pub fn reserve(&mut self, additional: usize) {
+        self.bytes.reserve(additional);
+    }
+
+    pub fn push_str(&mut self, string: &str) {
+        self.bytes.extend_from_slice(string.as_bytes());
+    }
+
+    pub fn push(&mut self, character: char) {
+        self.push_str(character.encode_utf8(&mut [0; 4]));
+    }
+
+    pub fn pop(&mut self) -> Option<char> {
+        let character = self.as_str().chars().next_back()?;
+        self.bytes.truncate(self.len() - character.len_utf8());
+
+        Some(character)
+    }
+
+    /// Shortens the string to [`len`] bytes, which must be on a character
+    /// boundary.
+    pub fn truncate(&mut self, len: usize) {
+        assert!(
+            self.as_str().is_char_boundary(len),
+            "Truncating in the middle of a character"
+        );
+
+        self.bytes.truncate(len);
+    }
+
+    pub fn clear(&mut self) {
+        self.bytes.clear();
+    }
+}
+
+impl<A: SteadfastAlloc> Deref for ArenaString<A> {
+    type Target = str;
+
+    fn deref(&self) -> &str {
+        self.as_str()
+    }
+}
+
+impl<A: SteadfastAlloc> fmt::Write for ArenaString<A> {
+    fn write_str(&mut self, string: &str) -> fmt::Result {
+        self.bytes.try_reserve(string.len()).ok_or(fmt::Error)?;
+        self.push_str(string);
+
+        Ok(())
+    }
+}
+
+impl<A: SteadfastAlloc> fmt::Display for ArenaString<A> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Display::fmt(self.as_str(), f)
+    }
+}
+
+impl<A: SteadfastAlloc> fmt::Debug for ArenaString<A> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Debug::fmt(self.as_str(), f)
+    }
+}
+
+impl<A: SteadfastAlloc> PartialEq<str> for ArenaString<A> {
+    fn eq(&self, other: &str) -> bool {
+        self.as_str() == other
+    }
+}
+
+impl<A: SteadfastAlloc> PartialEq<&str> for ArenaString<A> {
+    fn eq(&self, other: &&str) -> bool {
+        self.as_str() == *other
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::ArenaString;
+    use crate::arena::bump::{Overflow, RawBumpArena};
+    use crate::arena::frame::FrameArena;
+    use core::fmt::Write;
+
+    #[test]
+    fn strings_are_built_in_arenas() {
+        let mut frame: FrameArena = FrameArena::new("strings", 64);
+
+        {
+            let mut string = ArenaString::from_str_in("frame ", &frame);
+            write!(string, "{} took {:.1}ms", 12, 16.6).unwrap();
+            string.push('✓');
+
+            assert_eq!(string, "frame 12 took 16.6ms✓");
+            assert_eq!(string.pop(), Some('✓'));
+            assert!(string.ends_with("ms"));
+        }
+
+        frame.next_frame();
+    }
+
+    #[test]
+    fn full_arenas_fail_to_format() {
+        let arena = RawBumpArena::with_overflow("full", 8, Overflow::ReturnNone);
+        let mut string = ArenaString::new_in(&arena);
+
+        assert!(write!(string, "{}", u64::MAX).is_err());
+        assert!(string.is_empty());
+    }
+}
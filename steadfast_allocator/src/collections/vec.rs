@@ -0,0 +1,377 @@
+use crate::SteadfastAlloc;
+use core::alloc::Layout;
+use core::fmt;
+use core::marker::PhantomData;
+use core::mem::size_of;
+use core::ops::{Deref, DerefMut};
+use core::ptr::NonNull;
+
+/// A growable array allocated from [`A`], e.g. a `&FrameArena` for a
+/// vector which lives until the end of the frame.
+///
+/// Growing an arena vector which is not the newest allocation in its'
+/// arena leaves the old buffer behind until the arena is reset.
+pub struct ArenaVec<T, A: SteadfastAlloc> {
+    ptr: NonNull<T>,
+    len: usize,
+    capacity: usize,
+    alloc: A,
+    _marker: PhantomData<T>,
+}
+
+unsafe impl<T: Send, A: SteadfastAlloc + Send> Send for ArenaVec<T, A> {}
+unsafe impl<T: Sync, A: SteadfastAlloc + Sync> Sync for ArenaVec<T, A> {}
+
+impl<T, A: SteadfastAlloc> ArenaVec<T, A> {
+    /// Creates an empty vector, which doesn't allocate until pushed to.
+    pub fn new_in(alloc: A) -> Self {
+        Self {
+            ptr: NonNull::dangling(),
+            len: 0,
+            // Zero sized values never need memory
+            capacity: if size_of::<T>() == 0 { usize::MAX } else { 0 },
+            alloc,
+            _marker: PhantomData,
+        }
+    }
+
+    /// Creates a vector with room for [`capacity`] values, panicking if
+    /// the allocator has no room for it.
+    pub fn with_capacity_in(capacity: usize, alloc: A) -> Self {
+        let mut vec = Self::new_in(alloc);
+        vec.reserve(capacity);
+
+        vec
+    }
+
+    pub fn allocator(&self) -> &A {
+        &self.alloc
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    pub fn as_slice(&self) -> &[T] {
+        unsafe { core::slice::from_raw_parts(self.ptr.as_ptr(), self.len) }
+    }
+
+    pub fn as_mut_slice(&mut self) -> &mut [T] {
+        unsafe { core::slice::from_raw_parts_mut(self.ptr.as_ptr(), self.len) }
+    }
+
+    /// Makes room for at least [`additional`] more values, or returns
+    /// `None` if the allocator has no room for them.
+    pub fn try_reserve(&mut self, additional: usize) -> Option<()> {
+        let needed = self.len.checked_add(additional)?;
+
+        if needed <= self.capacity {
+            return Some(());
+        }
+
+        let capacity = needed.max(self.capacity * 2).max(4);
+        let layout = Layout::array::<T>(capacity).ok()?;
+
+        let ptr = if self.capacity == 0 {
+            self.alloc.allocate(layout)?
+        } else {
+            unsafe { self.alloc.grow(self.ptr.cast(), self.layout(), layout)? }
+        };
+
+        self.ptr = ptr.cast();
+        self.capacity = capacity;
+
+        Some(())
+    }
+
+    /// Makes room for at least [`additional`] more values, panicking if
+    /// the allocator has no room for them.
+    pub fn reserve(&mut self, additional: usize) {
+        if self.try_reserve(additional).is_none() {
+            panic!("Allocation Failed");
+        }
+    }
+
+    /// Shrinks the buffer to fit the values in the vector.
+    pub fn shrink_to_fit(&mut self) {
+        if size_of::<T>() == 0 || self.capacity == self.len {
+            return;
+        }
+
+        if self.len == 0 {
+            unsafe { self.alloc.deallocate(self.ptr.cast(), self.layout()) };
+
+            self.ptr = NonNull::dangling();
+            self.capacity = 0;
+
+            return;
+        }
+
+        let layout = Layout::array::<T>(self.len).unwrap();
+
+        if let Some(ptr) = unsafe { self.alloc.shrink(self.ptr.cast(), self.layout(), layout) } {
+            self.ptr = ptr.cast();
+            self.capacity = self.len;
+        }
+    }
+
+    /// Appends [`value`], or hands it back if the allocator has no room
+    /// for it.
+    pub fn try_push(&mut self, value: T) -> Result<(), T> {
+        if self.len == self.capacity && self.try_reserve(1).is_none() {
+            return Err(value);
+        }
+
+        unsafe { self.ptr.as_ptr().add(self.len).write(value) };
+        self.len += 1;
+
+        Ok(())
+    }
+
+    /// Appends [`value`], panicking if the allocator has no room for it.
+    pub fn push(&mut self, value: T) {
+        if self.try_push(value).is_err() {
+            panic!("Allocation Failed");
+        }
+    }
+
+    pub fn pop(&mut self) -> Option<T> {
+        if self.len == 0 {
+            return None;
+        }
+
+        self.len -= 1;
+
+        unsafe { Some(self.ptr.as_ptr().add(self.len).read()) }
+    }
+
+    /// Inserts [`value`] at [`index`], shifting everything after it.
+    pub fn insert(&mut self, index: usize, value: T) {
+        assert!(index <= self.len, "Insert index {} out of bounds", index);
+
+        self.reserve(1);
+
+        unsafe {
+            let ptr = self.ptr.as_ptr().add(index);
+
+            core::ptr::copy(ptr, ptr.add(1), self.len - index);
+            ptr.write(value);
+        }
+
+        self.len += 1;
+    }
+
+    /// Removes the value at [`index`], shifting everything after it.
+    pub fn remove(&mut self, index: usize) -> T {
+        assert!(index < self.len, "Remove index {} out of bounds", index);
+
+        self.len -= 1;
+
+        unsafe {
+            let ptr = self.ptr.as_ptr().add(index);
+            let value = ptr.read();
+
+            core::ptr::copy(ptr.add(1), ptr, self.len - index);
+
+            value
+        }
+    }
+
+    /// Removes the value at [`index`], replacing it with the last value.
+    pub fn swap_remove(&mut self, index: usize) -> T {
+        assert!(index < self.len, "Remove index {} out of bounds", index);
+
+        let last = self.len - 1;
+        self.as_mut_slice().swap(index, last);
+        self.pop().unwrap()
+    }
+
+    /// Drops every value after the first [`len`].
+    pub fn truncate(&mut self, len: usize) {
+        if len >= self.len {
+            return;
+        }
+
+        let tail = core::ptr::slice_from_raw_parts_mut(
+            unsafe { self.ptr.as_ptr().add(len) },
+            self.len - len,
+        );
+
+        // Shortened first, so a panicking destructor can't drop twice
+        self.len = len;
+
+        unsafe { core::ptr::drop_in_place(tail) };
+    }
+
+    pub fn clear(&mut self) {
+        self.truncate(0);
+    }
+
+    fn layout(&self) -> Layout {
+        // Checked when the buffer was allocated
+        Layout::array::<T>(self.capacity).unwrap()
+    }
+}
+
+impl<T: Clone, A: SteadfastAlloc> ArenaVec<T, A> {
+    /// Appends a clone of every value in [`slice`].
+    pub fn extend_from_slice(&mut self, slice: &[T]) {
+        self.reserve(slice.len());
+
+        for value in slice {
+            self.push(value.clone());
+        }
+    }
+}
+
+impl<T, A: SteadfastAlloc> Drop for ArenaVec<T, A> {
+    fn drop(&mut self) {
+        self.clear();
+
+        if size_of::<T>() != 0 && self.capacity != 0 {
+            unsafe { self.alloc.deallocate(self.ptr.cast(), self.layout()) };
+        }
+    }
+}
+
+impl<T, A: SteadfastAlloc> Deref for ArenaVec<T, A> {
+    type Target = [T];
+
+    fn deref(&self) -> &[T] {
+        self.as_slice()
+    }
+}
+
+impl<T, A: SteadfastAlloc> DerefMut for ArenaVec<T, A> {
+    fn deref_mut(&mut self) -> &mut [T] {
+        self.as_mut_slice()
+    }
+}
+
+impl<T, A: SteadfastAlloc> Extend<T> for ArenaVec<T, A> {
+    fn extend<I: IntoIterator<Item = T>>(&mut self, iter: I) {
+        let iter = iter.into_iter();
+        self.reserve(iter.size_hint().0);
+
+        for value in iter {
+            self.push(value);
+        }
+    }
+}
+
+impl<T: fmt::Debug, A: SteadfastAlloc> fmt::Debug for ArenaVec<T, A> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_list().entries(self.iter()).finish()
+    }
+}
+
+impl<T: PartialEq, A: SteadfastAlloc> PartialEq<[T]> for ArenaVec<T, A> {
+    fn eq(&self, other: &[T]) -> bool {
+        self.as_slice() == other
+    }
+}
+
+impl<T: PartialEq, A: SteadfastAlloc, const N: usize> PartialEq<[T; N]> for ArenaVec<T, A> {
+    fn eq(&self, other: &[T; N]) -> bool {
+        self.as_slice() == other
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::ArenaVec;
+    use crate::arena::bump::{Overflow, RawBumpArena};
+    use crate::pool::RawPool;
+    use crate::stack::StackAllocator;
+    use crate::SteadfastAllocator;
+    use core::alloc::Layout;
+    use core::cell::Cell;
+
+    #[test]
+    fn vectors_grow_in_any_allocator() {
+        let arena = RawBumpArena::new("vec", 1024);
+        let stack = StackAllocator::new("vec", 1024);
+
+        let mut global = ArenaVec::new_in(SteadfastAllocator);
+        let mut bump = ArenaVec::new_in(&arena);
+        let mut stacked = ArenaVec::with_capacity_in(2, &stack);
+
+        for value in 0..100u32 {
+            global.push(value);
+            bump.push(value);
+            stacked.push(value);
+        }
+
+        assert!(global.iter().copied().eq(0..100));
+        assert!(bump.iter().copied().eq(0..100));
+        assert!(stacked.iter().copied().eq(0..100));
+
+        // The newest allocation grows in place, so the arena isn't wasted
+        assert_eq!(arena.capacity(), 1024);
+        assert!(stack.used() < 1024);
+    }
+
+    #[test]
+    fn full_allocators_fail_gracefully() {
+        let arena = RawBumpArena::with_overflow("full", 16, Overflow::ReturnNone);
+        let mut vec = ArenaVec::new_in(&arena);
+
+        for value in 0..4u32 {
+            vec.push(value);
+        }
+
+        assert_eq!(vec.try_push(4), Err(4));
+        assert_eq!(vec, [0, 1, 2, 3]);
+
+        // Even when the arena would panic on overflow itself
+        let arena = RawBumpArena::new("full", 16);
+        let mut vec = ArenaVec::<u32, _>::new_in(&arena);
+
+        assert!(vec.try_reserve(4).is_some());
+        assert!(vec.try_reserve(5).is_none());
+
+        let pool = RawPool::new("slots", Layout::new::<[u64; 4]>(), 1);
+        let mut vec = ArenaVec::new_in(&pool);
+
+        vec.extend(0..4u64);
+        assert!(vec.try_reserve(1).is_none());
+    }
+
+    #[test]
+    fn values_are_moved_and_dropped() {
+        struct Counted<'a>(&'a Cell<usize>);
+
+        impl Drop for Counted<'_> {
+            fn drop(&mut self) {
+                self.0.set(self.0.get() + 1);
+            }
+        }
+
+        let drops = Cell::new(0);
+        let arena = RawBumpArena::with_overflow("drops", 64, Overflow::Chain);
+
+        let mut vec = ArenaVec::new_in(&arena);
+        vec.extend((0..10).map(|_| Counted(&drops)));
+        vec.insert(3, Counted(&drops));
+
+        drop(vec.remove(0));
+        drop(vec.swap_remove(0));
+        vec.truncate(4);
+        assert_eq!(drops.get(), 7);
+
+        drop(vec);
+        assert_eq!(drops.get(), 11);
+
+        let mut units = ArenaVec::new_in(&arena);
+        units.extend(core::iter::repeat_n((), 1000));
+        assert_eq!(units.len(), 1000);
+    }
+}
@@ -0,0 +1,246 @@
+use crate::align::{align_down, align_up};
+use crate::arena::vm::os;
+use core::alloc::{GlobalAlloc, Layout};
+use core::ffi::c_char;
+use core::sync::atomic::{AtomicBool, AtomicU8, AtomicUsize, Ordering};
+
+/// The number of freed allocations kept inaccessible before their
+/// address space is returned to the OS.
+pub const QUARANTINE: usize = 4096;
+
+/// Set to anything but `0` to use the [`GuardedAllocator`] without the
+/// `guarded` feature.
+pub const ENV_VAR: &str = "STEADFAST_GUARDED";
+
+/// Places every allocation at the end of its' own pages, followed by an
+/// inaccessible guard page, so that writing past the end of an
+/// allocation faults immediately. Freed pages stay inaccessible until
+/// [`QUARANTINE`] more allocations were freed, so using them faults too.
+///
+/// Every allocation takes at least two pages of address space, so this is
+/// only meant for debugging and soak tests. Allocations aligned to more
+/// than a page are not guarded.
+///
+/// [`SteadfastAllocator`](crate::SteadfastAllocator) uses this when the
+/// `guarded` feature is enabled, or [`ENV_VAR`] is set.
+pub struct GuardedAllocator;
+
+/// Whether [`SteadfastAllocator`](crate::SteadfastAllocator) uses the
+/// [`GuardedAllocator`]. Decided once, as memory must be freed by the
+/// allocator which allocated it.
+pub fn enabled() -> bool {
+    if cfg!(feature = "guarded") {
+        return true;
+    }
+
+    const UNKNOWN: u8 = 0;
+    const DISABLED: u8 = 1;
+    const ENABLED: u8 = 2;
+
+    static MODE: AtomicU8 = AtomicU8::new(UNKNOWN);
+
+    match MODE.load(Ordering::Relaxed) {
+        DISABLED => false,
+        ENABLED => true,
+        _ => {
+            let enabled = env_enabled();
+            let mode = if enabled { ENABLED } else { DISABLED };
+
+            // Another thread may have decided first
+            match MODE.compare_exchange(UNKNOWN, mode, Ordering::Relaxed, Ordering::Relaxed) {
+                Ok(_) => enabled,
+                Err(mode) => mode == ENABLED,
+            }
+        }
+    }
+}
+
+fn env_enabled() -> bool {
+    // getenv reads the environment in place, so doesn't allocate
+    let value = unsafe { getenv(b"STEADFAST_GUARDED\0".as_ptr() as *const c_char) };
+
+    if value.is_null() {
+        return false;
+    }
+
+    let first = unsafe { *value } as u8;
+
+    first != 0 && first != b'0'
+}
+
+fn page_size() -> usize {
+    static PAGE_SIZE: AtomicUsize = AtomicUsize::new(0);
+
+    match PAGE_SIZE.load(Ordering::Relaxed) {
+        0 => {
+            let page_size = os::page_size();
+            PAGE_SIZE.store(page_size, Ordering::Relaxed);
+
+            page_size
+        }
+        page_size => page_size,
+    }
+}
+
+/// The number of accessible bytes holding an allocation of [`layout`].
+fn data_len(layout: Layout, page_size: usize) -> Option<usize> {
+    align_up(layout.size().max(1), page_size)
+}
+
+/// Freed allocations, as the base and length of their reserved range.
+struct Quarantine {
+    lock: AtomicBool,
+    next: AtomicUsize,
+    ranges: [(AtomicUsize, AtomicUsize); QUARANTINE],
+}
+
+#[allow(clippy::declare_interior_mutable_const)]
+const EMPTY: (AtomicUsize, AtomicUsize) = (AtomicUsize::new(0), AtomicUsize::new(0));
+
+static QUARANTINED: Quarantine = Quarantine {
+    lock: AtomicBool::new(false),
+    next: AtomicUsize::new(0),
+    ranges: [EMPTY; QUARANTINE],
+};
+
+impl Quarantine {
+    /// Quarantines a range, returning the range it evicted, if any.
+    fn push(&self, base: usize, len: usize) -> Option<(usize, usize)> {
+        while self
+            .lock
+            .compare_exchange_weak(false, true, Ordering::Acquire, Ordering::Relaxed)
+            .is_err()
+        {
+            core::hint::spin_loop();
+        }
+
+        let next = self.next.load(Ordering::Relaxed);
+        let (slot_base, slot_len) = &self.ranges[next];
+
+        let evicted = (
+            slot_base.swap(base, Ordering::Relaxed),
+            slot_len.swap(len, Ordering::Relaxed),
+        );
+
+        self.next.store((next + 1) % QUARANTINE, Ordering::Relaxed);
+        self.lock.store(false, Ordering::Release);
+
+        match evicted {
+            (0, _) => None,
+            evicted => Some(evicted),
+        }
+    }
+}
+
+unsafe impl GlobalAlloc for GuardedAllocator {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        let page_size = page_size();
+
+        if layout.align() > page_size {
+            return crate::alloc::Platform.alloc(layout);
+        }
+
+        let data_len = match data_len(layout, page_size) {
+            Some(data_len) => data_len,
+            None => return core::ptr::null_mut(),
+        };
+
+        let total = match data_len.checked_add(page_size) {
+            Some(total) => total,
+            None => return core::ptr::null_mut(),
+        };
+
+        let base = os::reserve(total);
+
+        if base.is_null() {
+            return base;
+        }
+
+        // The guard page is left reserved, so it can't be accessed
+        if !os::commit(base, data_len) {
+            os::release(base, total);
+            return core::ptr::null_mut();
+        }
+
+        let end = base as usize + data_len;
+
+        align_down(end - layout.size(), layout.align()) as *mut u8
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        let page_size = page_size();
+
+        if layout.align() > page_size {
+            return crate::alloc::Platform.dealloc(ptr, layout);
+        }
+
+        // Checked when allocating
+        let data_len = data_len(layout, page_size).unwrap();
+
+        // Less than a page is left for alignment after the allocation
+        let end = align_up(ptr as usize + layout.size().max(1), page_size).unwrap();
+        let base = end - data_len;
+
+        os::decommit(base as *mut u8, data_len);
+
+        if let Some((base, len)) = QUARANTINED.push(base, data_len + page_size) {
+            os::release(base as *mut u8, len);
+        }
+    }
+
+    unsafe fn alloc_zeroed(&self, layout: Layout) -> *mut u8 {
+        if layout.align() > page_size() {
+            return crate::alloc::Platform.alloc_zeroed(layout);
+        }
+
+        // Freshly committed pages are always zeroed
+        self.alloc(layout)
+    }
+}
+
+extern "C" {
+    fn getenv(name: *const c_char) -> *const c_char;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{page_size, GuardedAllocator};
+    use crate::align::align_up;
+    use core::alloc::{GlobalAlloc, Layout};
+
+    #[test]
+    fn allocations_end_at_the_guard_page() {
+        for (size, align) in [(1, 1), (24, 8), (100, 32), (5000, 16), (4096, 4096)] {
+            let layout = Layout::from_size_align(size, align).unwrap();
+
+            unsafe {
+                let ptr = GuardedAllocator.alloc_zeroed(layout);
+
+                assert_eq!(ptr as usize % align, 0);
+                let end = ptr as usize + size;
+                assert!(align_up(end, page_size()).unwrap() - end < align);
+
+                // Every byte of the allocation is writable
+                let bytes = core::slice::from_raw_parts_mut(ptr, size);
+                assert!(bytes.iter().all(|byte| *byte == 0));
+                bytes.fill(0xAB);
+
+                GuardedAllocator.dealloc(ptr, layout);
+            }
+        }
+    }
+
+    #[test]
+    fn freed_pages_are_not_reused() {
+        let layout = Layout::new::<[u64; 4]>();
+
+        unsafe {
+            let first = GuardedAllocator.alloc(layout);
+            GuardedAllocator.dealloc(first, layout);
+
+            let second = GuardedAllocator.alloc(layout);
+            assert_ne!(first, second);
+            GuardedAllocator.dealloc(second, layout);
+        }
+    }
+}
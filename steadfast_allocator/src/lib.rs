@@ -1,13 +1,44 @@
 #![no_std]
 
+// Tags are tracked per thread, which needs thread locals from std
+#[cfg(feature = "tags")]
+extern crate std;
+
 pub mod arena;
+pub mod collections;
+pub mod pool;
+pub mod stack;
+
+// Built on the virtual memory functions of `arena::vm`
+#[cfg(any(
+    target_os = "linux",
+    target_vendor = "apple",
+    target_family = "windows"
+))]
+pub mod guarded;
+#[cfg(all(
+    feature = "guarded",
+    not(any(
+        target_os = "linux",
+        target_vendor = "apple",
+        target_family = "windows"
+    ))
+))]
+compile_error!("The `guarded` feature is only supported on Linux, Apple targets and Windows");
+
+#[cfg(feature = "tags")]
+pub mod tags;
+#[cfg(feature = "tracking")]
+pub mod tracking;
 
 pub(crate) mod align;
+pub(crate) mod allocator;
 pub(crate) mod layout;
 
 mod alloc;
 
 pub use crate::alloc::SteadfastAllocator;
+pub use crate::allocator::SteadfastAlloc;
 
 #[global_allocator]
 static ALLOCATOR: SteadfastAllocator = SteadfastAllocator;
@@ -0,0 +1,532 @@
+use crate::align::{align_up, ALIGNMENT};
+use crate::allocator::SteadfastAlloc;
+use crate::SteadfastAllocator;
+use core::alloc::{GlobalAlloc, Layout};
+use core::cell::Cell;
+use core::marker::PhantomData;
+use core::mem::{align_of, size_of};
+use core::ptr::NonNull;
+
+/// Written over freed slots in debug mode.
+const POISON: u8 = 0xDD;
+
+/// Marks a slot as free in debug mode, so a second free can be caught.
+const FREED: usize = usize::from_ne_bytes([0xF2; size_of::<usize>()]);
+
+/// What a pool does when every slot is in use.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Growth {
+    /// Fails the allocation, returning `None`.
+    Fixed,
+
+    /// Allocates another page with the same number of slots.
+    Pages,
+}
+
+/// The start of a free slot, linking it into the free list.
+struct FreeSlot {
+    next: Option<NonNull<FreeSlot>>,
+
+    /// [`FREED`] in debug mode.
+    marker: usize,
+}
+
+/// A block of slots. The slots follow the header.
+struct Page {
+    next: Option<NonNull<Page>>,
+    layout: Layout,
+}
+
+/// Hands out fixed-size slots of memory in O(1), keeping freed slots in a
+/// free list stored in the slots themselves.
+///
+/// In debug mode freed slots are poisoned, and freeing a slot twice,
+/// freeing memory from another pool or writing to a freed slot panics.
+/// Debug mode is on by default in debug builds.
+#[derive(Debug)]
+pub struct RawPool {
+    name: &'static str,
+    growth: Growth,
+    debug: bool,
+
+    slot: Layout,
+    page_slots: usize,
+    /// The offset of the first slot from the start of a page.
+    offset: usize,
+
+    pages: Cell<Option<NonNull<Page>>>,
+    free: Cell<Option<NonNull<FreeSlot>>>,
+    len: Cell<usize>,
+    capacity: Cell<usize>,
+}
+
+// The pool owns its' pages, and hands out raw pointers only.
+unsafe impl Send for RawPool {}
+
+impl RawPool {
+    /// Creates a pool of [`page_slots`] slots fitting [`layout`], which
+    /// grows by pages of the same size.
+    pub fn new(name: &'static str, layout: Layout, page_slots: usize) -> Self {
+        Self::with_growth(name, layout, page_slots, Growth::Pages)
+    }
+
+    pub fn with_growth(
+        name: &'static str,
+        layout: Layout,
+        page_slots: usize,
+        growth: Growth,
+    ) -> Self {
+        assert!(page_slots > 0, "Pool {} must have at least one slot", name);
+
+        // Every slot must be able to hold a free list entry
+        let align = layout.align().max(align_of::<FreeSlot>());
+        let size = layout.size().max(size_of::<FreeSlot>());
+
+        let slot = match align_up(size, align) {
+            Some(size) => Layout::from_size_align(size, align).expect("Invalid slot layout"),
+            None => panic!("Slot size of pool {} overflows", name),
+        };
+
+        let offset = align_up(size_of::<Page>(), align).expect("Page header overflows");
+
+        let pool = Self {
+            name,
+            growth,
+            debug: cfg!(debug_assertions),
+            slot,
+            page_slots,
+            offset,
+            pages: Cell::new(None),
+            free: Cell::new(None),
+            len: Cell::new(0),
+            capacity: Cell::new(0),
+        };
+
+        if !pool.grow() {
+            panic!("Allocation Failed");
+        }
+
+        pool
+    }
+
+    pub fn name(&self) -> &'static str {
+        self.name
+    }
+
+    /// The layout of every slot, which may be larger than the layout the
+    /// pool was created with.
+    pub fn slot_layout(&self) -> Layout {
+        self.slot
+    }
+
+    /// The number of slots in use.
+    pub fn len(&self) -> usize {
+        self.len.get()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len.get() == 0
+    }
+
+    /// The number of slots in every page allocated so far.
+    pub fn capacity(&self) -> usize {
+        self.capacity.get()
+    }
+
+    pub fn is_debug(&self) -> bool {
+        self.debug
+    }
+
+    /// Turns debug checks on or off. Slots freed while debug mode was off
+    /// are not checked.
+    pub fn set_debug(&mut self, debug: bool) {
+        self.debug = debug;
+    }
+
+    /// Takes a slot from the free list, growing the pool if it is empty
+    /// and the pool may grow.
+    pub fn alloc(&mut self) -> Option<NonNull<u8>> {
+        self.take_slot()
+    }
+
+    /// Returns the slot at [`ptr`] to the free list.
+    ///
+    /// # Safety
+    ///
+    /// [`ptr`] must have been returned by [`alloc`] on this pool, and not
+    /// be used after it is freed. In debug mode freeing a pointer twice or
+    /// from another pool panics, but this is not guaranteed.
+    pub unsafe fn free(&mut self, ptr: NonNull<u8>) {
+        self.return_slot(ptr)
+    }
+
+    /// [`alloc`] through a shared reference, for [`SteadfastAlloc`].
+    fn take_slot(&self) -> Option<NonNull<u8>> {
+        if self.free.get().is_none() && (self.growth == Growth::Fixed || !self.grow()) {
+            return None;
+        }
+
+        let slot = self.free.get()?;
+
+        unsafe {
+            if self.debug && slot.as_ref().marker == FREED {
+                self.check_poison(slot);
+            }
+
+            self.free.set(slot.as_ref().next);
+            (*slot.as_ptr()).marker = 0;
+        }
+
+        self.len.set(self.len.get() + 1);
+
+        Some(slot.cast())
+    }
+
+    /// [`free`] through a shared reference, for [`SteadfastAlloc`].
+    unsafe fn return_slot(&self, ptr: NonNull<u8>) {
+        let slot = ptr.cast::<FreeSlot>();
+
+        if self.debug {
+            self.check_free(ptr);
+
+            core::ptr::write_bytes(ptr.as_ptr(), POISON, self.slot.size());
+        }
+
+        slot.as_ptr().write(FreeSlot {
+            next: self.free.get(),
+            marker: if self.debug { FREED } else { 0 },
+        });
+
+        self.free.set(Some(slot));
+        self.len.set(self.len.get() - 1);
+    }
+
+    /// Whether [`ptr`] points at a slot in this pool.
+    pub fn owns(&self, ptr: NonNull<u8>) -> bool {
+        let ptr = ptr.as_ptr() as usize;
+        let mut page = self.pages.get();
+
+        while let Some(current) = page {
+            let start = Self::slots(current, self.offset) as usize;
+            let end = start + self.slot.size() * self.page_slots;
+
+            if ptr >= start && ptr < end {
+                return (ptr - start).is_multiple_of(self.slot.size());
+            }
+
+            page = unsafe { current.as_ref().next };
+        }
+
+        false
+    }
+
+    /// Panics if [`ptr`] is not from this pool or was already freed.
+    unsafe fn check_free(&self, ptr: NonNull<u8>) {
+        if !self.owns(ptr) {
+            panic!("Pointer {:p} is not from pool {}", ptr, self.name);
+        }
+
+        let slot = ptr.cast::<FreeSlot>();
+
+        if slot.as_ref().marker == FREED && self.is_free(slot) {
+            panic!("Double free of {:p} in pool {}", ptr, self.name);
+        }
+    }
+
+    fn is_free(&self, slot: NonNull<FreeSlot>) -> bool {
+        let mut free = self.free.get();
+
+        while let Some(current) = free {
+            if current == slot {
+                return true;
+            }
+
+            free = unsafe { current.as_ref().next };
+        }
+
+        false
+    }
+
+    /// Panics if a freed slot was written to.
+    unsafe fn check_poison(&self, slot: NonNull<FreeSlot>) {
+        let bytes = core::slice::from_raw_parts(
+            (slot.as_ptr() as *const u8).add(size_of::<FreeSlot>()),
+            self.slot.size() - size_of::<FreeSlot>(),
+        );
+
+        if bytes.iter().any(|byte| *byte != POISON) {
+            panic!(
+                "Slot {:p} in pool {} was written after being freed",
+                slot, self.name
+            );
+        }
+    }
+
+    /// Whether a slot can hold [`layout`].
+    fn fits(&self, layout: Layout) -> bool {
+        layout.size() <= self.slot.size() && layout.align() <= self.slot.align()
+    }
+
+    fn slots(page: NonNull<Page>, offset: usize) -> *mut u8 {
+        unsafe { (page.as_ptr() as *mut u8).add(offset) }
+    }
+
+    /// Allocates a page, adding its' slots to the free list.
+    fn grow(&self) -> bool {
+        let layout = self
+            .slot
+            .size()
+            .checked_mul(self.page_slots)
+            .and_then(|size| size.checked_add(self.offset))
+            .and_then(|size| Layout::from_size_align(size, self.slot.align().max(ALIGNMENT)).ok());
+
+        let layout = match layout {
+            Some(layout) => layout,
+            None => return false,
+        };
+
+        let page = match NonNull::new(unsafe { SteadfastAllocator.alloc_for(self.name, layout) }) {
+            Some(page) => page.cast::<Page>(),
+            None => return false,
+        };
+
+        unsafe {
+            page.as_ptr().write(Page {
+                next: self.pages.get(),
+                layout,
+            });
+
+            // Link the slots in reverse, so they are handed out in order
+            let slots = Self::slots(page, self.offset);
+
+            for index in (0..self.page_slots).rev() {
+                let slot = slots.add(index * self.slot.size()) as *mut FreeSlot;
+
+                slot.write(FreeSlot {
+                    next: self.free.get(),
+                    marker: 0,
+                });
+
+                self.free.set(NonNull::new(slot));
+            }
+        }
+
+        self.pages.set(Some(page));
+        self.capacity.set(self.capacity.get() + self.page_slots);
+
+        true
+    }
+}
+
+// Every allocation takes a whole slot, so resizing within a slot is free
+// and anything larger fails.
+unsafe impl SteadfastAlloc for RawPool {
+    fn allocate(&self, layout: Layout) -> Option<NonNull<u8>> {
+        if !self.fits(layout) {
+            return None;
+        }
+
+        self.take_slot()
+    }
+
+    unsafe fn deallocate(&self, ptr: NonNull<u8>, _layout: Layout) {
+        self.return_slot(ptr)
+    }
+
+    unsafe fn grow(&self, ptr: NonNull<u8>, _old: Layout, new: Layout) -> Option<NonNull<u8>> {
+        if self.fits(new) {
+            Some(ptr)
+        } else {
+            None
+        }
+    }
+
+    unsafe fn shrink(&self, ptr: NonNull<u8>, _old: Layout, new: Layout) -> Option<NonNull<u8>> {
+        if self.fits(new) {
+            Some(ptr)
+        } else {
+            None
+        }
+    }
+}
+
+impl Drop for RawPool {
+    fn drop(&mut self) {
+        let mut page = self.pages.take();
+
+        while let Some(current) = page {
+            unsafe {
+                let Page { next, layout } = current.as_ptr().read();
+                SteadfastAllocator.dealloc(current.as_ptr() as *mut u8, layout);
+
+                page = next;
+            }
+        }
+    }
+}
+
+/// A [`RawPool`] of values of a single type.
+///
+/// Values are dropped when they are freed. Values still in the pool when
+/// it is dropped are not dropped.
+#[derive(Debug)]
+pub struct Pool<T> {
+    raw: RawPool,
+    _marker: PhantomData<T>,
+}
+
+unsafe impl<T: Send> Send for Pool<T> {}
+
+impl<T> Pool<T> {
+    /// Creates a pool with pages of [`page_slots`] values, which grows by
+    /// pages of the same size.
+    pub fn new(name: &'static str, page_slots: usize) -> Self {
+        Self::with_growth(name, page_slots, Growth::Pages)
+    }
+
+    pub fn with_growth(name: &'static str, page_slots: usize, growth: Growth) -> Self {
+        Self {
+            raw: RawPool::with_growth(name, Layout::new::<T>(), page_slots, growth),
+            _marker: PhantomData,
+        }
+    }
+
+    pub fn raw(&self) -> &RawPool {
+        &self.raw
+    }
+
+    pub fn raw_mut(&mut self) -> &mut RawPool {
+        &mut self.raw
+    }
+
+    pub fn len(&self) -> usize {
+        self.raw.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.raw.is_empty()
+    }
+
+    pub fn capacity(&self) -> usize {
+        self.raw.capacity()
+    }
+
+    /// Moves [`value`] into the pool, or returns `None` if the pool is full
+    /// and can't grow.
+    pub fn alloc(&mut self, value: T) -> Option<NonNull<T>> {
+        let ptr = self.raw.alloc()?.cast::<T>();
+
+        unsafe { ptr.as_ptr().write(value) };
+
+        Some(ptr)
+    }
+
+    /// Drops the value at [`ptr`] and returns its' slot to the pool.
+    ///
+    /// # Safety
+    ///
+    /// Same as [`RawPool::free`].
+    pub unsafe fn free(&mut self, ptr: NonNull<T>) {
+        // Check before dropping, as a freed slot no longer holds a value
+        if self.raw.is_debug() {
+            self.raw.check_free(ptr.cast());
+        }
+
+        core::ptr::drop_in_place(ptr.as_ptr());
+        self.raw.free(ptr.cast());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Growth, Pool, RawPool};
+    use core::alloc::Layout;
+    use core::cell::Cell;
+
+    #[test]
+    fn slots_are_reused() {
+        let mut pool = RawPool::with_growth("reuse", Layout::new::<u64>(), 4, Growth::Fixed);
+
+        let slots = [(); 4].map(|_| pool.alloc().unwrap());
+        assert!(pool.alloc().is_none());
+        assert_eq!(pool.len(), 4);
+
+        unsafe { pool.free(slots[2]) };
+        assert_eq!(pool.alloc(), Some(slots[2]));
+    }
+
+    #[test]
+    fn pools_grow_by_pages() {
+        let mut pool = Pool::new("grow", 2);
+
+        let values = [1u32, 2, 3, 4, 5].map(|value| pool.alloc(value).unwrap());
+        assert_eq!(pool.capacity(), 6);
+
+        for (value, ptr) in values.iter().enumerate() {
+            assert_eq!(unsafe { *ptr.as_ref() }, value as u32 + 1);
+            assert_eq!(ptr.as_ptr() as usize % core::mem::align_of::<u32>(), 0);
+        }
+    }
+
+    #[test]
+    fn freeing_drops_values() {
+        struct Counted<'a>(&'a Cell<usize>);
+
+        impl Drop for Counted<'_> {
+            fn drop(&mut self) {
+                self.0.set(self.0.get() + 1);
+            }
+        }
+
+        let drops = Cell::new(0);
+        let mut pool = Pool::new("drops", 4);
+
+        let value = pool.alloc(Counted(&drops)).unwrap();
+        unsafe { pool.free(value) };
+
+        assert_eq!(drops.get(), 1);
+        assert!(pool.is_empty());
+    }
+
+    #[test]
+    #[should_panic(expected = "Double free")]
+    fn double_frees_are_caught() {
+        let mut pool = RawPool::new("double", Layout::new::<[u8; 32]>(), 4);
+        pool.set_debug(true);
+
+        let slot = pool.alloc().unwrap();
+        pool.alloc().unwrap();
+
+        unsafe {
+            pool.free(slot);
+            pool.free(slot);
+        }
+    }
+
+    #[test]
+    #[should_panic(expected = "was written after being freed")]
+    fn writes_to_freed_slots_are_caught() {
+        let mut pool = RawPool::new("poison", Layout::new::<[u8; 32]>(), 4);
+        pool.set_debug(true);
+
+        let slot = pool.alloc().unwrap();
+
+        unsafe {
+            pool.free(slot);
+            *slot.as_ptr().add(31) = 0;
+        }
+
+        pool.alloc();
+    }
+
+    #[test]
+    #[should_panic(expected = "is not from pool")]
+    fn foreign_pointers_are_refused() {
+        let mut pool = RawPool::new("pool", Layout::new::<u64>(), 4);
+        let mut other = RawPool::new("other", Layout::new::<u64>(), 4);
+        pool.set_debug(true);
+
+        let slot = other.alloc().unwrap();
+        unsafe { pool.free(slot) };
+    }
+}
@@ -0,0 +1,522 @@
+use crate::align::{align_down, align_up, ALIGNMENT};
+use crate::allocator::{self, SteadfastAlloc};
+use crate::layout::get_alignment_layout;
+use crate::SteadfastAllocator;
+use core::alloc::{GlobalAlloc, Layout};
+use core::cell::Cell;
+use core::mem::{align_of, size_of};
+use core::ptr::NonNull;
+
+/// Stored before every allocation, so that it can be popped.
+struct Header {
+    prev_end: usize,
+    prev_last: usize,
+}
+
+const HEADER: usize = size_of::<Header>();
+
+/// A position in one end of a stack, which it can be popped back to.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Marker {
+    end: usize,
+    last: usize,
+}
+
+/// The memory a stack allocates from.
+#[derive(Debug)]
+struct Block {
+    ptr: NonNull<u8>,
+    layout: Layout,
+}
+
+impl Block {
+    fn new(name: &'static str, capacity: usize) -> Self {
+        if capacity > isize::MAX as usize - ALIGNMENT {
+            panic!("Allocation Failed");
+        }
+
+        unsafe {
+            let layout = get_alignment_layout(capacity);
+
+            match NonNull::new(SteadfastAllocator.alloc_for(name, layout)) {
+                Some(ptr) => Self { ptr, layout },
+                None => panic!("Allocation Failed"),
+            }
+        }
+    }
+
+    fn start(&self) -> usize {
+        self.ptr.as_ptr() as usize
+    }
+
+    fn end(&self) -> usize {
+        self.start() + self.layout.size()
+    }
+}
+
+impl Drop for Block {
+    fn drop(&mut self) {
+        unsafe { SteadfastAllocator.dealloc(self.ptr.as_ptr(), self.layout) }
+    }
+}
+
+/// One end of a stack, as addresses in its' block.
+#[derive(Clone, Copy, Debug)]
+struct End {
+    /// Where the next allocation is made from.
+    end: usize,
+
+    /// The newest allocation, or 0 if there is none.
+    last: usize,
+}
+
+impl End {
+    fn new(end: usize) -> Self {
+        Self { end, last: 0 }
+    }
+
+    fn marker(&self) -> Marker {
+        Marker {
+            end: self.end,
+            last: self.last,
+        }
+    }
+
+    /// Every allocation is aligned to at least a header, so the header
+    /// before it is aligned too.
+    fn align(layout: Layout) -> usize {
+        layout.align().max(align_of::<Header>())
+    }
+
+    /// Allocates upwards, no further than [`limit`].
+    fn push_up(&mut self, layout: Layout, limit: usize) -> Option<NonNull<u8>> {
+        let ptr = align_up(self.end.checked_add(HEADER)?, Self::align(layout))?;
+        let next = ptr.checked_add(layout.size())?;
+
+        if next > limit {
+            return None;
+        }
+
+        Some(self.push(ptr, next))
+    }
+
+    /// Allocates downwards, no further than [`limit`].
+    fn push_down(&mut self, layout: Layout, limit: usize) -> Option<NonNull<u8>> {
+        let ptr = align_down(self.end.checked_sub(layout.size())?, Self::align(layout));
+        let next = ptr.checked_sub(HEADER)?;
+
+        if next < limit {
+            return None;
+        }
+
+        Some(self.push(ptr, next))
+    }
+
+    fn push(&mut self, ptr: usize, next: usize) -> NonNull<u8> {
+        unsafe {
+            ((ptr - HEADER) as *mut Header).write(Header {
+                prev_end: self.end,
+                prev_last: self.last,
+            });
+        }
+
+        self.end = next;
+        self.last = ptr;
+
+        unsafe { NonNull::new_unchecked(ptr as *mut u8) }
+    }
+
+    /// Pops the newest allocation, which must be [`ptr`]. Popping any
+    /// other would free the allocations after it while still in use.
+    fn pop(&mut self, ptr: NonNull<u8>, name: &str) {
+        assert!(
+            ptr.as_ptr() as usize == self.last,
+            "Out of order pop of {:p} in stack {}",
+            ptr,
+            name
+        );
+
+        let Header {
+            prev_end,
+            prev_last,
+        } = unsafe { ((self.last - HEADER) as *const Header).read() };
+
+        self.end = prev_end;
+        self.last = prev_last;
+    }
+
+    fn pop_to(&mut self, marker: Marker) {
+        self.end = marker.end;
+        self.last = marker.last;
+    }
+
+    /// Resizes the newest allocation of an upwards end in place, if
+    /// [`ptr`] is the newest and it still fits below [`limit`].
+    fn resize_up(&mut self, ptr: NonNull<u8>, layout: Layout, limit: usize) -> bool {
+        let ptr = ptr.as_ptr() as usize;
+
+        if ptr != self.last || !ptr.is_multiple_of(Self::align(layout)) {
+            return false;
+        }
+
+        match ptr.checked_add(layout.size()) {
+            Some(next) if next <= limit => {
+                self.end = next;
+                true
+            }
+            _ => false,
+        }
+    }
+
+    /// Runs [`f`] on the end in [`cell`], storing it back after.
+    fn update<R>(cell: &Cell<End>, f: impl FnOnce(&mut End) -> R) -> R {
+        let mut end = cell.get();
+        let result = f(&mut end);
+        cell.set(end);
+
+        result
+    }
+}
+
+/// Allocates from a block in LIFO order, freeing allocations by popping
+/// them or popping back to a [`Marker`].
+///
+/// Values pushed to the stack are never dropped.
+#[derive(Debug)]
+pub struct StackAllocator {
+    name: &'static str,
+    block: Block,
+    bottom: Cell<End>,
+}
+
+// The stack owns its' block, and hands out raw pointers only.
+unsafe impl Send for StackAllocator {}
+
+impl StackAllocator {
+    pub fn new(name: &'static str, capacity: usize) -> Self {
+        let block = Block::new(name, capacity);
+
+        Self {
+            name,
+            bottom: Cell::new(End::new(block.start())),
+            block,
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        self.name
+    }
+
+    pub fn capacity(&self) -> usize {
+        self.block.layout.size()
+    }
+
+    /// The number of bytes in use, including headers and padding.
+    pub fn used(&self) -> usize {
+        self.bottom.get().end - self.block.start()
+    }
+
+    /// Allocates uninitialized memory for [`layout`], or returns `None` if
+    /// there is no room for it.
+    pub fn push(&mut self, layout: Layout) -> Option<NonNull<u8>> {
+        self.bottom.get_mut().push_up(layout, self.block.end())
+    }
+
+    /// Moves [`value`] onto the stack, or returns `None` if there is no
+    /// room for it.
+    pub fn push_value<T>(&mut self, value: T) -> Option<NonNull<T>> {
+        let ptr = self.push(Layout::new::<T>())?.cast::<T>();
+
+        unsafe { ptr.as_ptr().write(value) };
+
+        Some(ptr)
+    }
+
+    /// Frees the newest allocation, panicking if it isn't [`ptr`].
+    pub fn pop(&mut self, ptr: NonNull<u8>) {
+        self.bottom.get_mut().pop(ptr, self.name);
+    }
+
+    pub fn marker(&self) -> Marker {
+        self.bottom.get().marker()
+    }
+
+    /// Frees everything allocated since [`marker`] was taken.
+    ///
+    /// Debug builds assert the marker was not already popped.
+    pub fn pop_to(&mut self, marker: Marker) {
+        assert!(
+            marker.end >= self.block.start() && marker.end <= self.block.end(),
+            "Marker is not in stack {}",
+            self.name
+        );
+        debug_assert!(
+            marker.end <= self.bottom.get().end,
+            "Marker in stack {} was already popped",
+            self.name
+        );
+
+        self.bottom.get_mut().pop_to(marker);
+    }
+
+    /// Frees everything on the stack.
+    pub fn reset(&mut self) {
+        self.bottom.set(End::new(self.block.start()));
+    }
+}
+
+// Only the newest allocation is popped when freed, or resized in place.
+// Anything older is freed by popping to a marker or resetting the stack.
+unsafe impl SteadfastAlloc for StackAllocator {
+    fn allocate(&self, layout: Layout) -> Option<NonNull<u8>> {
+        let limit = self.block.end();
+
+        End::update(&self.bottom, |bottom| bottom.push_up(layout, limit))
+    }
+
+    unsafe fn deallocate(&self, ptr: NonNull<u8>, _layout: Layout) {
+        if self.bottom.get().last == ptr.as_ptr() as usize {
+            End::update(&self.bottom, |bottom| bottom.pop(ptr, self.name));
+        }
+    }
+
+    unsafe fn grow(&self, ptr: NonNull<u8>, old: Layout, new: Layout) -> Option<NonNull<u8>> {
+        let limit = self.block.end();
+
+        if End::update(&self.bottom, |bottom| bottom.resize_up(ptr, new, limit)) {
+            return Some(ptr);
+        }
+
+        allocator::reallocate(self, ptr, old, new)
+    }
+
+    unsafe fn shrink(&self, ptr: NonNull<u8>, old: Layout, new: Layout) -> Option<NonNull<u8>> {
+        let limit = self.block.end();
+
+        if End::update(&self.bottom, |bottom| bottom.resize_up(ptr, new, limit))
+            || (ptr.as_ptr() as usize).is_multiple_of(new.align())
+        {
+            return Some(ptr);
+        }
+
+        allocator::reallocate(self, ptr, old, new)
+    }
+}
+
+/// Which end of a [`DoubleStackAllocator`] to allocate from.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Side {
+    /// Grows up from the start of the block, e.g. for data which lives as
+    /// long as a level.
+    Bottom,
+
+    /// Grows down from the end of the block, e.g. for temporary data.
+    Top,
+}
+
+/// A [`StackAllocator`] with a stack at either end of its' block, which
+/// share the memory between them.
+#[derive(Debug)]
+pub struct DoubleStackAllocator {
+    name: &'static str,
+    block: Block,
+    bottom: End,
+    top: End,
+}
+
+unsafe impl Send for DoubleStackAllocator {}
+
+impl DoubleStackAllocator {
+    pub fn new(name: &'static str, capacity: usize) -> Self {
+        let block = Block::new(name, capacity);
+
+        Self {
+            name,
+            bottom: End::new(block.start()),
+            top: End::new(block.end()),
+            block,
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        self.name
+    }
+
+    pub fn capacity(&self) -> usize {
+        self.block.layout.size()
+    }
+
+    /// The number of bytes in use on [`side`], including headers and
+    /// padding.
+    pub fn used(&self, side: Side) -> usize {
+        match side {
+            Side::Bottom => self.bottom.end - self.block.start(),
+            Side::Top => self.block.end() - self.top.end,
+        }
+    }
+
+    /// Allocates uninitialized memory for [`layout`] on [`side`], or
+    /// returns `None` if the stacks would overlap.
+    pub fn push(&mut self, side: Side, layout: Layout) -> Option<NonNull<u8>> {
+        match side {
+            Side::Bottom => self.bottom.push_up(layout, self.top.end),
+            Side::Top => self.top.push_down(layout, self.bottom.end),
+        }
+    }
+
+    pub fn push_value<T>(&mut self, side: Side, value: T) -> Option<NonNull<T>> {
+        let ptr = self.push(side, Layout::new::<T>())?.cast::<T>();
+
+        unsafe { ptr.as_ptr().write(value) };
+
+        Some(ptr)
+    }
+
+    /// Frees the newest allocation on [`side`], panicking if it isn't
+    /// [`ptr`].
+    pub fn pop(&mut self, side: Side, ptr: NonNull<u8>) {
+        match side {
+            Side::Bottom => self.bottom.pop(ptr, self.name),
+            Side::Top => self.top.pop(ptr, self.name),
+        }
+    }
+
+    pub fn marker(&self, side: Side) -> Marker {
+        match side {
+            Side::Bottom => self.bottom.marker(),
+            Side::Top => self.top.marker(),
+        }
+    }
+
+    /// Frees everything allocated on [`side`] since [`marker`] was taken.
+    ///
+    /// Debug builds assert the marker was not already popped.
+    pub fn pop_to(&mut self, side: Side, marker: Marker) {
+        match side {
+            Side::Bottom => {
+                assert!(
+                    marker.end >= self.block.start() && marker.end <= self.top.end,
+                    "Marker is not in the bottom of stack {}",
+                    self.name
+                );
+                debug_assert!(
+                    marker.end <= self.bottom.end,
+                    "Marker in stack {} was already popped",
+                    self.name
+                );
+
+                self.bottom.pop_to(marker);
+            }
+            Side::Top => {
+                assert!(
+                    marker.end >= self.bottom.end && marker.end <= self.block.end(),
+                    "Marker is not in the top of stack {}",
+                    self.name
+                );
+                debug_assert!(
+                    marker.end >= self.top.end,
+                    "Marker in stack {} was already popped",
+                    self.name
+                );
+
+                self.top.pop_to(marker);
+            }
+        }
+    }
+
+    /// Frees everything on [`side`].
+    pub fn reset(&mut self, side: Side) {
+        match side {
+            Side::Bottom => self.bottom = End::new(self.block.start()),
+            Side::Top => self.top = End::new(self.block.end()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{DoubleStackAllocator, Side, StackAllocator};
+    use core::alloc::Layout;
+
+    #[test]
+    fn allocations_respect_their_alignment() {
+        let mut stack = StackAllocator::new("align", 1024);
+
+        for align in [1, 2, 8, 16, 64, 256] {
+            let ptr = stack.push(Layout::from_size_align(3, align).unwrap());
+            assert_eq!(ptr.unwrap().as_ptr() as usize % align, 0);
+        }
+
+        let mut stack = DoubleStackAllocator::new("align", 1024);
+
+        for align in [1, 16, 128] {
+            let ptr = stack.push(Side::Top, Layout::from_size_align(3, align).unwrap());
+            assert_eq!(ptr.unwrap().as_ptr() as usize % align, 0);
+        }
+    }
+
+    #[test]
+    fn pops_free_memory_in_order() {
+        let mut stack = StackAllocator::new("pop", 256);
+
+        let marker = stack.marker();
+        let first = stack.push_value(1u64).unwrap();
+        let second = stack.push_value(2u32).unwrap();
+        let used = stack.used();
+
+        stack.pop(second.cast());
+        assert_eq!(stack.push_value(3u32), Some(second));
+        assert_eq!(stack.used(), used);
+        assert_eq!(unsafe { *first.as_ref() }, 1);
+
+        stack.pop_to(marker);
+        assert_eq!(stack.used(), 0);
+        assert!(stack.push(Layout::new::<[u8; 512]>()).is_none());
+    }
+
+    #[test]
+    fn double_stacks_share_their_block() {
+        let mut stack = DoubleStackAllocator::new("double", 128);
+
+        let level = stack.push_value(Side::Bottom, [1u8; 48]).unwrap();
+        let frame = stack.marker(Side::Top);
+        let temporary = stack.push_value(Side::Top, [2u8; 48]).unwrap();
+
+        assert!(stack
+            .push(Side::Bottom, Layout::new::<[u8; 32]>())
+            .is_none());
+        assert!(temporary.as_ptr() as usize > level.as_ptr() as usize);
+
+        stack.pop_to(Side::Top, frame);
+        assert_eq!(stack.used(Side::Top), 0);
+        assert!(stack
+            .push(Side::Bottom, Layout::new::<[u8; 32]>())
+            .is_some());
+        assert_eq!(unsafe { level.as_ref() }, &[1; 48]);
+    }
+
+    #[test]
+    #[should_panic(expected = "Out of order pop")]
+    fn out_of_order_pops_are_caught() {
+        let mut stack = StackAllocator::new("order", 256);
+
+        let first = stack.push_value(1u64).unwrap();
+        stack.push_value(2u64).unwrap();
+
+        stack.pop(first.cast());
+    }
+
+    #[test]
+    #[cfg(debug_assertions)]
+    #[should_panic(expected = "was already popped")]
+    fn popped_markers_are_caught() {
+        let mut stack = StackAllocator::new("marker", 256);
+
+        let start = stack.marker();
+        stack.push_value(1u64).unwrap();
+        let inner = stack.marker();
+
+        stack.pop_to(start);
+        stack.pop_to(inner);
+    }
+}
@@ -0,0 +1,521 @@
+use crate::align::ALIGNMENT;
+use core::alloc::Layout;
+use core::cell::Cell;
+use core::fmt::{self, Display, Formatter};
+use core::sync::atomic::{AtomicPtr, AtomicU8, AtomicUsize, Ordering};
+
+/// The subsystem an allocation is attributed to, see [`with_mem_tag`].
+#[repr(u8)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Tag {
+    Untagged,
+    Engine,
+    Modules,
+    Rendering,
+    Audio,
+    Physics,
+    Gameplay,
+    Assets,
+    Ui,
+    Network,
+}
+
+impl Tag {
+    pub const COUNT: usize = 10;
+
+    pub const ALL: [Tag; Tag::COUNT] = [
+        Tag::Untagged,
+        Tag::Engine,
+        Tag::Modules,
+        Tag::Rendering,
+        Tag::Audio,
+        Tag::Physics,
+        Tag::Gameplay,
+        Tag::Assets,
+        Tag::Ui,
+        Tag::Network,
+    ];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Tag::Untagged => "Untagged",
+            Tag::Engine => "Engine",
+            Tag::Modules => "Modules",
+            Tag::Rendering => "Rendering",
+            Tag::Audio => "Audio",
+            Tag::Physics => "Physics",
+            Tag::Gameplay => "Gameplay",
+            Tag::Assets => "Assets",
+            Tag::Ui => "Ui",
+            Tag::Network => "Network",
+        }
+    }
+
+    fn from_byte(byte: u8) -> Tag {
+        Tag::ALL
+            .get(byte as usize)
+            .copied()
+            .unwrap_or(Tag::Untagged)
+    }
+}
+
+impl Display for Tag {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+/// What happens when a tag goes over its' budget.
+///
+/// Allocators may not unwind, so budgets are only acted on by
+/// [`check_budgets`], which runs when a [`with_mem_tag`] scope ends.
+#[repr(u8)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum BudgetAction {
+    /// Prints a warning to stderr.
+    Warn,
+
+    /// Logs a warning with `tracing`, or prints it to stderr if the
+    /// `tracing` feature is disabled.
+    Log,
+
+    /// Panics with the report of the tag.
+    Panic,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Budget {
+    pub bytes: usize,
+    pub action: BudgetAction,
+}
+
+std::thread_local! {
+    static CURRENT: Cell<Tag> = const { Cell::new(Tag::Untagged) };
+
+    /// The name of the arena allocating, if any.
+    static SOURCE: Cell<Option<&'static str>> = const { Cell::new(None) };
+}
+
+/// The budget of a tag is within its' limit.
+const WITHIN: u8 = 0;
+/// A thread is recording what pushed the tag over budget.
+const EXCEEDING: u8 = 1;
+/// The tag is over budget, which wasn't acted on yet.
+const EXCEEDED: u8 = 2;
+/// The tag is over budget, which was acted on.
+const REPORTED: u8 = 3;
+
+const NO_BUDGET: usize = usize::MAX;
+
+/// The statistics and budget of a single tag.
+struct TagStats {
+    live_bytes: AtomicUsize,
+    peak_bytes: AtomicUsize,
+    live_allocations: AtomicUsize,
+
+    budget: AtomicUsize,
+    action: AtomicU8,
+    state: AtomicU8,
+
+    /// The name of the arena which pushed the tag over budget.
+    source: AtomicPtr<u8>,
+    source_len: AtomicUsize,
+}
+
+impl TagStats {
+    #[allow(clippy::declare_interior_mutable_const)]
+    const NEW: TagStats = TagStats {
+        live_bytes: AtomicUsize::new(0),
+        peak_bytes: AtomicUsize::new(0),
+        live_allocations: AtomicUsize::new(0),
+        budget: AtomicUsize::new(NO_BUDGET),
+        action: AtomicU8::new(BudgetAction::Warn as u8),
+        state: AtomicU8::new(WITHIN),
+        source: AtomicPtr::new(core::ptr::null_mut()),
+        source_len: AtomicUsize::new(0),
+    };
+
+    fn source(&self) -> Option<&'static str> {
+        let ptr = self.source.load(Ordering::Relaxed);
+
+        if ptr.is_null() {
+            return None;
+        }
+
+        unsafe {
+            let bytes = core::slice::from_raw_parts(ptr, self.source_len.load(Ordering::Relaxed));
+            Some(core::str::from_utf8_unchecked(bytes))
+        }
+    }
+}
+
+static STATS: [TagStats; Tag::COUNT] = [TagStats::NEW; Tag::COUNT];
+
+/// Attributes every allocation made by [`f`] on this thread to [`tag`],
+/// then acts on any budget which was exceeded, see [`check_budgets`].
+pub fn with_mem_tag<R>(tag: Tag, f: impl FnOnce() -> R) -> R {
+    struct Restore(Tag);
+
+    impl Drop for Restore {
+        fn drop(&mut self) {
+            set_current(self.0);
+        }
+    }
+
+    let restore = Restore(current_tag());
+    set_current(tag);
+
+    let result = f();
+
+    drop(restore);
+    check_budgets();
+
+    result
+}
+
+/// The tag allocations on this thread are attributed to.
+pub fn current_tag() -> Tag {
+    CURRENT.try_with(Cell::get).unwrap_or(Tag::Untagged)
+}
+
+fn set_current(tag: Tag) {
+    let _ = CURRENT.try_with(|current| current.set(tag));
+}
+
+/// Names the arena allocating in [`f`], so it can be reported if it
+/// pushes a tag over budget.
+pub(crate) fn with_source<R>(name: &'static str, f: impl FnOnce() -> R) -> R {
+    let prev = SOURCE.try_with(|source| source.replace(Some(name))).ok();
+    let result = f();
+
+    if let Some(prev) = prev {
+        let _ = SOURCE.try_with(|source| source.set(prev));
+    }
+
+    result
+}
+
+pub fn set_budget(tag: Tag, budget: Budget) {
+    let stats = &STATS[tag as usize];
+
+    stats.action.store(budget.action as u8, Ordering::Relaxed);
+    stats.budget.store(budget.bytes, Ordering::Relaxed);
+    stats.state.store(WITHIN, Ordering::Relaxed);
+}
+
+pub fn clear_budget(tag: Tag) {
+    let stats = &STATS[tag as usize];
+
+    stats.budget.store(NO_BUDGET, Ordering::Relaxed);
+    stats.state.store(WITHIN, Ordering::Relaxed);
+}
+
+/// Acts on every tag which went over budget since it was last checked.
+///
+/// Runs when a [`with_mem_tag`] scope ends, and should also be called
+/// e.g. once per frame to catch untagged allocations.
+pub fn check_budgets() {
+    for tag in Tag::ALL {
+        let stats = &STATS[tag as usize];
+
+        let exceeded = stats
+            .state
+            .compare_exchange(EXCEEDED, REPORTED, Ordering::Acquire, Ordering::Relaxed)
+            .is_ok();
+
+        if !exceeded {
+            continue;
+        }
+
+        let report = report(tag);
+
+        match report.action {
+            Some(BudgetAction::Panic) => panic!("Memory budget exceeded: {}", report),
+            #[cfg(feature = "tracing")]
+            Some(BudgetAction::Log) => tracing::warn!("Memory budget exceeded: {}", report),
+            _ => std::eprintln!("Memory budget exceeded: {}", report),
+        }
+    }
+}
+
+/// The memory use of a tag.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct TagReport {
+    pub tag: Tag,
+    pub live_bytes: usize,
+    pub peak_bytes: usize,
+    pub live_allocations: usize,
+    pub budget: Option<usize>,
+    pub action: Option<BudgetAction>,
+
+    /// The arena whose allocation pushed the tag over budget, if the tag
+    /// is over budget and it was an arena.
+    pub source: Option<&'static str>,
+}
+
+impl TagReport {
+    pub fn over_budget(&self) -> bool {
+        matches!(self.budget, Some(budget) if self.live_bytes > budget)
+    }
+}
+
+impl Display for TagReport {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} has {} bytes live in {} allocations (peak {} bytes",
+            self.tag, self.live_bytes, self.live_allocations, self.peak_bytes
+        )?;
+
+        if let Some(budget) = self.budget {
+            write!(f, ", budget {} bytes", budget)?;
+        }
+
+        write!(f, ")")?;
+
+        if let Some(source) = self.source {
+            write!(f, ", exceeded by arena {}", source)?;
+        }
+
+        Ok(())
+    }
+}
+
+pub fn report(tag: Tag) -> TagReport {
+    let stats = &STATS[tag as usize];
+
+    let budget = match stats.budget.load(Ordering::Relaxed) {
+        NO_BUDGET => None,
+        budget => Some(budget),
+    };
+
+    let exceeded = matches!(stats.state.load(Ordering::Acquire), EXCEEDED | REPORTED);
+
+    TagReport {
+        tag,
+        live_bytes: stats.live_bytes.load(Ordering::Relaxed),
+        peak_bytes: stats.peak_bytes.load(Ordering::Relaxed),
+        live_allocations: stats.live_allocations.load(Ordering::Relaxed),
+        budget,
+        action: budget.map(|_| match stats.action.load(Ordering::Relaxed) {
+            0 => BudgetAction::Warn,
+            1 => BudgetAction::Log,
+            _ => BudgetAction::Panic,
+        }),
+        source: if exceeded { stats.source() } else { None },
+    }
+}
+
+/// Reports the memory use of every tag.
+pub fn reports() -> impl Iterator<Item = TagReport> {
+    Tag::ALL.iter().copied().map(report)
+}
+
+/// Every allocation is prefixed by its' tag, so it can be attributed to
+/// the same tag when it is freed. The prefix keeps the alignment of the
+/// allocation.
+fn prefix(layout: Layout) -> usize {
+    layout.align().max(ALIGNMENT)
+}
+
+fn prefixed(layout: Layout, size: usize) -> Option<Layout> {
+    let size = size.checked_add(prefix(layout))?;
+
+    Layout::from_size_align(size, layout.align()).ok()
+}
+
+fn record_alloc(tag: Tag, size: usize) {
+    let stats = &STATS[tag as usize];
+
+    let live = stats.live_bytes.fetch_add(size, Ordering::Relaxed) + size;
+    stats.peak_bytes.fetch_max(live, Ordering::Relaxed);
+    stats.live_allocations.fetch_add(1, Ordering::Relaxed);
+
+    if live <= stats.budget.load(Ordering::Relaxed) {
+        return;
+    }
+
+    let exceeding = stats
+        .state
+        .compare_exchange(WITHIN, EXCEEDING, Ordering::Relaxed, Ordering::Relaxed)
+        .is_ok();
+
+    if exceeding {
+        let source = SOURCE.try_with(Cell::get).ok().flatten();
+        let (ptr, len) = source.map_or((core::ptr::null_mut(), 0), |source| {
+            (source.as_ptr() as *mut u8, source.len())
+        });
+
+        stats.source.store(ptr, Ordering::Relaxed);
+        stats.source_len.store(len, Ordering::Relaxed);
+        stats.state.store(EXCEEDED, Ordering::Release);
+    }
+}
+
+fn record_dealloc(tag: Tag, size: usize) {
+    let stats = &STATS[tag as usize];
+
+    let live = stats.live_bytes.fetch_sub(size, Ordering::Relaxed) - size;
+    stats.live_allocations.fetch_sub(1, Ordering::Relaxed);
+
+    if live <= stats.budget.load(Ordering::Relaxed) {
+        let _ =
+            stats
+                .state
+                .compare_exchange(REPORTED, WITHIN, Ordering::Relaxed, Ordering::Relaxed);
+    }
+}
+
+/// Allocates [`layout`] with [`alloc`], prefixed by the current tag.
+#[inline(always)]
+pub(crate) unsafe fn alloc(layout: Layout, alloc: impl FnOnce(Layout) -> *mut u8) -> *mut u8 {
+    let outer = match prefixed(layout, layout.size()) {
+        Some(outer) => outer,
+        None => return core::ptr::null_mut(),
+    };
+
+    let ptr = alloc(outer);
+
+    if ptr.is_null() {
+        return ptr;
+    }
+
+    let tag = current_tag();
+    let ptr = ptr.add(prefix(layout));
+
+    ptr.sub(1).write(tag as u8);
+    record_alloc(tag, layout.size());
+
+    ptr
+}
+
+/// Frees [`ptr`] with [`dealloc`], attributing it to the tag it was
+/// allocated with.
+#[inline(always)]
+pub(crate) unsafe fn dealloc(ptr: *mut u8, layout: Layout, dealloc: impl FnOnce(*mut u8, Layout)) {
+    let tag = Tag::from_byte(ptr.sub(1).read());
+    record_dealloc(tag, layout.size());
+
+    // The layout was checked when allocating
+    let outer = prefixed(layout, layout.size()).unwrap();
+
+    dealloc(ptr.sub(prefix(layout)), outer)
+}
+
+#[inline(always)]
+pub(crate) unsafe fn realloc(
+    ptr: *mut u8,
+    layout: Layout,
+    new_size: usize,
+    realloc: impl FnOnce(*mut u8, Layout, usize) -> *mut u8,
+) -> *mut u8 {
+    let new_outer = match prefixed(layout, new_size) {
+        Some(outer) => outer,
+        None => return core::ptr::null_mut(),
+    };
+
+    let tag = Tag::from_byte(ptr.sub(1).read());
+    let outer = prefixed(layout, layout.size()).unwrap();
+
+    let new_ptr = realloc(ptr.sub(prefix(layout)), outer, new_outer.size());
+
+    if new_ptr.is_null() {
+        return new_ptr;
+    }
+
+    record_dealloc(tag, layout.size());
+    record_alloc(tag, new_size);
+
+    new_ptr.add(prefix(layout))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        clear_budget, current_tag, report, set_budget, with_mem_tag, with_source, Budget,
+        BudgetAction, Tag,
+    };
+    use crate::SteadfastAllocator;
+    use core::alloc::{GlobalAlloc, Layout};
+
+    // Every test uses its' own tag, as tests run in parallel
+
+    #[test]
+    fn allocations_are_attributed_to_the_current_tag() {
+        let layout = Layout::from_size_align(4096, 64).unwrap();
+
+        let ptr = with_mem_tag(Tag::Audio, || {
+            assert_eq!(current_tag(), Tag::Audio);
+            unsafe { SteadfastAllocator.alloc(layout) }
+        });
+
+        assert_eq!(current_tag(), Tag::Untagged);
+        assert_eq!(ptr as usize % 64, 0);
+        assert_eq!(report(Tag::Audio).live_bytes, 4096);
+
+        // Freed under another tag, but attributed to the one it was
+        // allocated with
+        unsafe { SteadfastAllocator.dealloc(ptr, layout) };
+
+        let audio = report(Tag::Audio);
+        assert_eq!(audio.live_bytes, 0);
+        assert_eq!(audio.peak_bytes, 4096);
+    }
+
+    #[test]
+    fn reallocations_keep_their_tag() {
+        let layout = Layout::from_size_align(16, 16).unwrap();
+
+        unsafe {
+            let ptr = with_mem_tag(Tag::Network, || SteadfastAllocator.alloc(layout));
+            ptr.write(42);
+
+            let ptr = SteadfastAllocator.realloc(ptr, layout, 1024);
+            assert_eq!(ptr.read(), 42);
+            assert_eq!(report(Tag::Network).live_bytes, 1024);
+
+            SteadfastAllocator.dealloc(ptr, Layout::from_size_align(1024, 16).unwrap());
+        }
+
+        assert_eq!(report(Tag::Network).live_allocations, 0);
+    }
+
+    #[test]
+    #[should_panic(expected = "Physics has 2048 bytes live")]
+    fn budgets_can_panic() {
+        set_budget(
+            Tag::Physics,
+            Budget {
+                bytes: 1024,
+                action: BudgetAction::Panic,
+            },
+        );
+
+        let layout = Layout::from_size_align(2048, 8).unwrap();
+        with_mem_tag(Tag::Physics, || unsafe { SteadfastAllocator.alloc(layout) });
+    }
+
+    #[test]
+    fn reports_name_the_arena_over_budget() {
+        set_budget(
+            Tag::Ui,
+            Budget {
+                bytes: 1024,
+                action: BudgetAction::Warn,
+            },
+        );
+
+        let layout = Layout::from_size_align(2048, 8).unwrap();
+
+        let ptr = with_mem_tag(Tag::Ui, || {
+            with_source("widgets", || unsafe { SteadfastAllocator.alloc(layout) })
+        });
+
+        let ui = report(Tag::Ui);
+        assert!(ui.over_budget());
+        assert_eq!(ui.source, Some("widgets"));
+
+        unsafe { SteadfastAllocator.dealloc(ptr, layout) };
+        clear_budget(Tag::Ui);
+    }
+}
@@ -0,0 +1,207 @@
+use core::fmt::{self, Display, Formatter};
+use core::sync::atomic::{AtomicUsize, Ordering};
+
+/// The number of size classes in the histogram. Class `n` holds
+/// allocations of up to `2^n` bytes, and the last class holds everything
+/// larger.
+pub const SIZE_CLASSES: usize = 32;
+
+static LIVE_BYTES: AtomicUsize = AtomicUsize::new(0);
+static PEAK_BYTES: AtomicUsize = AtomicUsize::new(0);
+static LIVE_ALLOCATIONS: AtomicUsize = AtomicUsize::new(0);
+static TOTAL_ALLOCATIONS: AtomicUsize = AtomicUsize::new(0);
+
+#[allow(clippy::declare_interior_mutable_const)]
+const EMPTY: AtomicUsize = AtomicUsize::new(0);
+
+/// The number of live allocations in each size class.
+static HISTOGRAM: [AtomicUsize; SIZE_CLASSES] = [EMPTY; SIZE_CLASSES];
+
+/// The size class of an allocation of [`size`] bytes.
+pub const fn size_class(size: usize) -> usize {
+    let class = if size <= 1 {
+        0
+    } else {
+        (usize::BITS - (size - 1).leading_zeros()) as usize
+    };
+
+    if class < SIZE_CLASSES {
+        class
+    } else {
+        SIZE_CLASSES - 1
+    }
+}
+
+pub(crate) fn record_alloc(size: usize) {
+    let live = LIVE_BYTES.fetch_add(size, Ordering::Relaxed) + size;
+
+    PEAK_BYTES.fetch_max(live, Ordering::Relaxed);
+    LIVE_ALLOCATIONS.fetch_add(1, Ordering::Relaxed);
+    TOTAL_ALLOCATIONS.fetch_add(1, Ordering::Relaxed);
+    HISTOGRAM[size_class(size)].fetch_add(1, Ordering::Relaxed);
+}
+
+pub(crate) fn record_dealloc(size: usize) {
+    LIVE_BYTES.fetch_sub(size, Ordering::Relaxed);
+    LIVE_ALLOCATIONS.fetch_sub(1, Ordering::Relaxed);
+    HISTOGRAM[size_class(size)].fetch_sub(1, Ordering::Relaxed);
+}
+
+/// Sets the peak to the bytes currently allocated, e.g. to measure the
+/// peak of a single level.
+pub fn reset_peak() {
+    PEAK_BYTES.store(LIVE_BYTES.load(Ordering::Relaxed), Ordering::Relaxed);
+}
+
+/// The allocation statistics of [`SteadfastAllocator`](crate::SteadfastAllocator)
+/// at a point in time.
+///
+/// The counters are read one at a time, so a snapshot taken while other
+/// threads allocate may be slightly inconsistent.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Snapshot {
+    pub live_bytes: usize,
+    pub peak_bytes: usize,
+    pub live_allocations: usize,
+
+    /// Every allocation ever made, including those since freed.
+    pub total_allocations: usize,
+
+    /// The number of live allocations in each size class, see
+    /// [`size_class`].
+    pub histogram: [usize; SIZE_CLASSES],
+}
+
+impl Snapshot {
+    pub fn take() -> Self {
+        let mut histogram = [0; SIZE_CLASSES];
+
+        for (count, class) in histogram.iter_mut().zip(HISTOGRAM.iter()) {
+            *count = class.load(Ordering::Relaxed);
+        }
+
+        Self {
+            live_bytes: LIVE_BYTES.load(Ordering::Relaxed),
+            peak_bytes: PEAK_BYTES.load(Ordering::Relaxed),
+            live_allocations: LIVE_ALLOCATIONS.load(Ordering::Relaxed),
+            total_allocations: TOTAL_ALLOCATIONS.load(Ordering::Relaxed),
+            histogram,
+        }
+    }
+
+    /// What changed between [`earlier`] and this snapshot. Memory which
+    /// is still live after e.g. unloading a level shows up as a positive
+    /// difference.
+    pub fn diff(&self, earlier: &Snapshot) -> SnapshotDiff {
+        let mut histogram = [0; SIZE_CLASSES];
+
+        for (class, count) in histogram.iter_mut().enumerate() {
+            *count = difference(self.histogram[class], earlier.histogram[class]);
+        }
+
+        SnapshotDiff {
+            live_bytes: difference(self.live_bytes, earlier.live_bytes),
+            live_allocations: difference(self.live_allocations, earlier.live_allocations),
+            allocations: self
+                .total_allocations
+                .wrapping_sub(earlier.total_allocations),
+            histogram,
+        }
+    }
+}
+
+impl Display for Snapshot {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} bytes live in {} allocations (peak {} bytes, {} allocations made)",
+            self.live_bytes, self.live_allocations, self.peak_bytes, self.total_allocations
+        )
+    }
+}
+
+/// The difference between two [`Snapshot`]s.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct SnapshotDiff {
+    pub live_bytes: isize,
+    pub live_allocations: isize,
+
+    /// The number of allocations made between the snapshots.
+    pub allocations: usize,
+
+    /// The change in live allocations in each size class.
+    pub histogram: [isize; SIZE_CLASSES],
+}
+
+impl SnapshotDiff {
+    /// Whether more memory is live than when the earlier snapshot was
+    /// taken.
+    pub fn leaked(&self) -> bool {
+        self.live_bytes > 0 || self.live_allocations > 0
+    }
+}
+
+impl Display for SnapshotDiff {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{:+} bytes live in {:+} allocations ({} allocations made)",
+            self.live_bytes, self.live_allocations, self.allocations
+        )?;
+
+        for (class, count) in self.histogram.iter().enumerate() {
+            if *count != 0 {
+                write!(f, ", {:+} up to {} bytes", count, 1usize << class)?;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+fn difference(later: usize, earlier: usize) -> isize {
+    later.wrapping_sub(earlier) as isize
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{size_class, Snapshot, SIZE_CLASSES};
+    use crate::SteadfastAllocator;
+    use core::alloc::{GlobalAlloc, Layout};
+
+    #[test]
+    fn size_classes_are_powers_of_two() {
+        assert_eq!(size_class(0), 0);
+        assert_eq!(size_class(1), 0);
+        assert_eq!(size_class(2), 1);
+        assert_eq!(size_class(3), 2);
+        assert_eq!(size_class(4), 2);
+        assert_eq!(size_class(5), 3);
+        assert_eq!(size_class(1 << 20), 20);
+        assert_eq!(size_class(usize::MAX), SIZE_CLASSES - 1);
+    }
+
+    #[test]
+    fn allocations_are_tracked() {
+        // Other tests allocate concurrently, so only check the class of an
+        // allocation larger than any they make
+        let layout = Layout::from_size_align(1 << 30, 8).unwrap();
+        let before = Snapshot::take();
+
+        unsafe {
+            let ptr = SteadfastAllocator.alloc(layout);
+            assert!(!ptr.is_null());
+
+            let during = Snapshot::take();
+            let diff = during.diff(&before);
+
+            assert_eq!(diff.histogram[30], 1);
+            assert!(diff.allocations >= 1);
+            assert!(during.peak_bytes >= 1 << 30);
+
+            SteadfastAllocator.dealloc(ptr, layout);
+        }
+
+        assert_eq!(Snapshot::take().diff(&before).histogram[30], 0);
+    }
+}
@@ -0,0 +1,46 @@
+//! Runs itself with the guarded allocator enabled, checking that overruns
+//! fault rather than corrupt memory.
+#![cfg(any(target_os = "linux", target_vendor = "apple"))]
+
+use std::process::Command;
+
+const CHILD: &str = "STEADFAST_GUARDED_CHILD";
+
+/// Runs [`test`] in a child process using the guarded allocator.
+fn run_guarded(test: &str) -> std::process::ExitStatus {
+    Command::new(std::env::current_exe().unwrap())
+        .args([test, "--exact", "--nocapture"])
+        .env(steadfast_allocator::guarded::ENV_VAR, "1")
+        .env(CHILD, "1")
+        .status()
+        .unwrap()
+}
+
+#[test]
+fn overruns_fault() {
+    if std::env::var_os(CHILD).is_some() {
+        let mut bytes = vec![0u8; 100];
+        let ptr = bytes.as_mut_ptr();
+
+        // Writing past the end of the vector hits the guard page
+        unsafe { ptr.add(100).write_volatile(1) };
+
+        return;
+    }
+
+    assert!(!run_guarded("overruns_fault").success());
+}
+
+#[test]
+fn guarded_programs_run() {
+    if std::env::var_os(CHILD).is_some() {
+        assert!(steadfast_allocator::guarded::enabled());
+
+        let strings: Vec<String> = (0..1000).map(|i| i.to_string()).collect();
+        assert_eq!(strings[999], "999");
+
+        return;
+    }
+
+    assert!(run_guarded("guarded_programs_run").success());
+}
@@ -0,0 +1,111 @@
+use std::collections::VecDeque;
+use std::fmt;
+use std::sync::{Arc, Mutex, MutexGuard};
+
+/// A line logged while the game runs.
+#[derive(Clone, Debug, PartialEq)]
+pub struct LogLine {
+    pub level: log::Level,
+    /// The module path the line was logged from, e.g. `game::player`.
+    pub target: String,
+    pub message: String,
+}
+
+impl fmt::Display for LogLine {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:<5} {}: {}", self.level, self.target, self.message)
+    }
+}
+
+/// The most recent lines logged, for the console to show.
+///
+/// Clones share the same lines, so the runtime can keep adding to the
+/// buffer it provides to the modules as a host resource. Once it holds
+/// [`capacity`] lines, the oldest line is dropped for each line pushed.
+#[derive(Clone, Debug)]
+pub struct LogBuffer {
+    lines: Arc<Mutex<VecDeque<LogLine>>>,
+    capacity: usize,
+}
+
+impl LogBuffer {
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            lines: Arc::new(Mutex::new(VecDeque::with_capacity(capacity))),
+            capacity,
+        }
+    }
+
+    pub fn push(&self, line: LogLine) {
+        if self.capacity == 0 {
+            return;
+        }
+
+        let mut lines = self.lock();
+
+        if lines.len() == self.capacity {
+            lines.pop_front();
+        }
+
+        lines.push_back(line);
+    }
+
+    /// A copy of the lines, oldest first.
+    pub fn lines(&self) -> Vec<LogLine> {
+        self.lock().iter().cloned().collect()
+    }
+
+    pub fn len(&self) -> usize {
+        self.lock().len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.lock().is_empty()
+    }
+
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    pub fn clear(&self) {
+        self.lock().clear();
+    }
+
+    /// Logging shouldn't stop because a thread panicked while logging.
+    fn lock(&self) -> MutexGuard<'_, VecDeque<LogLine>> {
+        self.lines.lock().unwrap_or_else(|error| error.into_inner())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{LogBuffer, LogLine};
+
+    fn line(message: &str) -> LogLine {
+        LogLine {
+            level: log::Level::Info,
+            target: String::from("game"),
+            message: message.to_string(),
+        }
+    }
+
+    #[test]
+    fn oldest_lines_are_dropped() {
+        let buffer = LogBuffer::new(2);
+        let shared = buffer.clone();
+
+        for message in &["a", "b", "c"] {
+            shared.push(line(message));
+        }
+
+        assert_eq!(buffer.lines(), [line("b"), line("c")]);
+        assert_eq!(buffer.lines()[1].to_string(), "INFO  game: c");
+
+        buffer.clear();
+        assert!(shared.is_empty());
+
+        let disabled = LogBuffer::new(0);
+        disabled.push(line("a"));
+        assert!(disabled.is_empty());
+    }
+}
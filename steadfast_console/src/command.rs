@@ -0,0 +1,271 @@
+use crate::{Cvars, Error};
+use std::borrow::Cow;
+use std::collections::BTreeMap;
+use std::fmt::Write as _;
+use std::path::Path;
+
+/// How many config files deep `exec` may be nested, so a config file
+/// which execs itself stops.
+pub const MAX_EXEC_DEPTH: usize = 8;
+
+/// Splits a command line into arguments, separated by whitespace.
+///
+/// Arguments may be quoted to include whitespace, in which `\"` and `\\`
+/// are a quote and a backslash. `//` outside of quotes starts a comment.
+pub fn tokenize(line: &str) -> Vec<String> {
+    let mut args = vec![];
+    let mut rest = line;
+
+    loop {
+        rest = rest.trim_start();
+
+        if rest.is_empty() || rest.starts_with("//") {
+            return args;
+        }
+
+        let mut arg = String::new();
+
+        if let Some(quoted) = rest.strip_prefix('"') {
+            let mut chars = quoted.char_indices();
+            // An unterminated quote runs to the end of the line
+            let mut end = quoted.len();
+
+            while let Some((index, c)) = chars.next() {
+                match c {
+                    '"' => {
+                        end = index + 1;
+                        break;
+                    }
+                    '\\' if matches!(quoted[index + 1..].chars().next(), Some('"' | '\\')) => {
+                        arg.extend(chars.next().map(|(_, c)| c))
+                    }
+                    c => arg.push(c),
+                }
+            }
+
+            rest = &quoted[end..];
+        } else {
+            let end = rest.find(char::is_whitespace).unwrap_or(rest.len());
+
+            arg.push_str(&rest[..end]);
+            rest = &rest[end..];
+        }
+
+        args.push(arg);
+    }
+}
+
+/// Quotes [`arg`] if [`tokenize`] wouldn't read it back as one argument.
+pub fn quote(arg: &str) -> Cow<'_, str> {
+    let plain = !arg.is_empty()
+        && !arg.starts_with('"')
+        && !arg.starts_with("//")
+        && !arg.contains(char::is_whitespace);
+
+    if plain {
+        return Cow::Borrowed(arg);
+    }
+
+    let escaped = arg.replace('\\', "\\\\").replace('"', "\\\"");
+
+    Cow::Owned(format!("\"{}\"", escaped))
+}
+
+/// Runs a line typed in the console, returning the text to print.
+///
+/// ```text
+/// r_vsync              prints the cvar
+/// r_vsync 0            sets the cvar
+/// set r_vsync 0        sets the cvar, or sets it once it is registered
+/// reset r_vsync        sets the cvar back to its' default
+/// toggle r_vsync       flips a bool cvar
+/// cvarlist [prefix]    lists the cvars
+/// exec autoexec.cfg    runs every line of a config file
+/// ```
+pub fn execute(cvars: &mut Cvars, line: &str) -> Result<Option<String>, Error> {
+    execute_args(cvars, &tokenize(line))
+}
+
+/// Runs a command already split into arguments, like [`execute`].
+pub fn execute_args(cvars: &mut Cvars, args: &[String]) -> Result<Option<String>, Error> {
+    run(cvars, args, 0)
+}
+
+/// Runs every line of the config file at [`path`]. Lines which fail are
+/// logged and skipped.
+pub fn exec_file(cvars: &mut Cvars, path: impl AsRef<Path>) -> Result<(), Error> {
+    exec(cvars, path.as_ref(), 0)
+}
+
+/// Writes the cvars to archive to a config file at [`path`], which
+/// restores them when it is run by [`exec_file`].
+///
+/// Values kept for cvars which were never registered, e.g. by a module
+/// which wasn't loaded, are written as they were set.
+pub fn save_archive(cvars: &Cvars, path: impl AsRef<Path>) -> Result<(), Error> {
+    let path = path.as_ref();
+    let mut config = String::from("// Written on exit, changes are overwritten\n");
+
+    let mut values: BTreeMap<&str, Cow<str>> = cvars
+        .pending()
+        .map(|(name, text)| (name, Cow::Borrowed(text)))
+        .collect();
+
+    for cvar in cvars.archived() {
+        values.insert(cvar.name(), Cow::Owned(cvar.value().to_string()));
+    }
+
+    for (name, value) in values {
+        writeln!(config, "set {} {}", name, quote(&value)).unwrap();
+    }
+
+    let io_error = |source| Error::Io {
+        path: path.to_path_buf(),
+        source,
+    };
+
+    if let Some(dir) = path.parent() {
+        std::fs::create_dir_all(dir).map_err(io_error)?;
+    }
+
+    std::fs::write(path, config).map_err(io_error)
+}
+
+fn run(cvars: &mut Cvars, args: &[String], depth: usize) -> Result<Option<String>, Error> {
+    let (command, args) = match args.split_first() {
+        Some((command, args)) => (command.as_str(), args),
+        None => return Ok(None),
+    };
+
+    match (command, args) {
+        ("set", [name, value]) => {
+            if cvars.set_or_defer(name, value)? {
+                Ok(None)
+            } else {
+                Ok(Some(format!(
+                    "`{}` will be set when it is registered",
+                    name
+                )))
+            }
+        }
+        ("set", _) => Err(Error::Usage("set <cvar> <value>")),
+        ("reset", [name]) => cvars.reset(name).map(|_| None),
+        ("reset", _) => Err(Error::Usage("reset <cvar>")),
+        ("toggle", [name]) => {
+            let value = cvars.bool(name).ok_or(Error::Usage("toggle <bool cvar>"))?;
+
+            cvars
+                .set_str(name, if value { "0" } else { "1" })
+                .map(|_| None)
+        }
+        ("toggle", _) => Err(Error::Usage("toggle <bool cvar>")),
+        ("cvarlist", []) => Ok(Some(list(cvars, ""))),
+        ("cvarlist", [prefix]) => Ok(Some(list(cvars, prefix))),
+        ("cvarlist", _) => Err(Error::Usage("cvarlist [prefix]")),
+        ("exec", [path]) => exec(cvars, Path::new(path), depth + 1).map(|_| None),
+        ("exec", _) => Err(Error::Usage("exec <file>")),
+        (name, []) if cvars.contains(name) => Ok(Some(describe(cvars, name))),
+        (name, [value]) if cvars.contains(name) => cvars.set_str(name, value).map(|_| None),
+        (command, _) => Err(Error::UnknownCommand(command.to_string())),
+    }
+}
+
+fn exec(cvars: &mut Cvars, path: &Path, depth: usize) -> Result<(), Error> {
+    if depth > MAX_EXEC_DEPTH {
+        return Err(Error::ExecDepth(MAX_EXEC_DEPTH));
+    }
+
+    let config = std::fs::read_to_string(path).map_err(|source| Error::Io {
+        path: path.to_path_buf(),
+        source,
+    })?;
+
+    for (number, line) in config.lines().enumerate() {
+        match run(cvars, &tokenize(line), depth) {
+            Ok(Some(output)) => log::info!("{}", output),
+            Ok(None) => (),
+            Err(error) => log::warn!("{}:{}: {}", path.display(), number + 1, error),
+        }
+    }
+
+    Ok(())
+}
+
+fn describe(cvars: &Cvars, name: &str) -> String {
+    let cvar = cvars.get(name).unwrap();
+    let mut text = format!(
+        "{} is {}, default {}",
+        name,
+        quote(&cvar.value().to_string()),
+        quote(&cvar.default_value().to_string())
+    );
+
+    if let (Some(min), Some(max)) = cvar.range() {
+        write!(text, ", from {} to {}", min, max).unwrap();
+    }
+
+    if cvar.flags() != Default::default() {
+        write!(text, " ({})", cvar.flags()).unwrap();
+    }
+
+    if !cvar.description().is_empty() {
+        write!(text, "\n    {}", cvar.description()).unwrap();
+    }
+
+    text
+}
+
+fn list(cvars: &Cvars, prefix: &str) -> String {
+    let mut text = String::new();
+    let mut count = 0;
+
+    for cvar in cvars.iter().filter(|cvar| cvar.name().starts_with(prefix)) {
+        let value = cvar.value().to_string();
+        writeln!(
+            text,
+            "{:<24} {:<12} {}",
+            cvar.name(),
+            quote(&value),
+            cvar.description()
+        )
+        .unwrap();
+
+        count += 1;
+    }
+
+    write!(text, "{} cvars", count).unwrap();
+
+    text
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{quote, tokenize};
+
+    #[test]
+    fn lines_are_split_into_arguments() {
+        assert_eq!(tokenize("  set r_vsync   0 "), ["set", "r_vsync", "0"]);
+        assert_eq!(
+            tokenize(r#"set name "Big \"Bob\" \\o/" // the player"#),
+            ["set", "name", r#"Big "Bob" \o/"#]
+        );
+        assert_eq!(tokenize("set path a/b//c"), ["set", "path", "a/b//c"]);
+        assert_eq!(tokenize(r#"set name """#), ["set", "name", ""]);
+        assert_eq!(tokenize("name \"héllo wörld"), ["name", "héllo wörld"]);
+        assert!(tokenize("// a comment").is_empty());
+    }
+
+    #[test]
+    fn quoted_arguments_round_trip() {
+        for arg in &[
+            "plain",
+            "",
+            "two words",
+            r#"a "quote""#,
+            r"back\slash",
+            "//",
+        ] {
+            assert_eq!(tokenize(&quote(arg)), [*arg]);
+        }
+    }
+}
@@ -0,0 +1,380 @@
+use crate::Error;
+use std::fmt;
+use std::ops::{BitOr, BitOrAssign};
+
+/// Changes how a [`Cvar`] may be set, combined with `|`.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub struct Flags(u8);
+
+impl Flags {
+    pub const NONE: Self = Self(0);
+    /// May only be changed while `sv_cheats` is set, and is reset to its'
+    /// default when `sv_cheats` is cleared.
+    pub const CHEAT: Self = Self(1);
+    /// Saved to the user's config file, when it isn't the default.
+    pub const ARCHIVE: Self = Self(1 << 1);
+    /// May not be changed once registered, e.g. to report a version.
+    pub const READ_ONLY: Self = Self(1 << 2);
+
+    pub fn contains(self, flags: Self) -> bool {
+        self.0 & flags.0 == flags.0
+    }
+}
+
+impl BitOr for Flags {
+    type Output = Self;
+
+    fn bitor(self, other: Self) -> Self {
+        Self(self.0 | other.0)
+    }
+}
+
+impl BitOrAssign for Flags {
+    fn bitor_assign(&mut self, other: Self) {
+        self.0 |= other.0;
+    }
+}
+
+impl fmt::Display for Flags {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let names = [
+            (Self::CHEAT, "cheat"),
+            (Self::ARCHIVE, "archive"),
+            (Self::READ_ONLY, "read only"),
+        ];
+
+        let mut first = true;
+
+        for (flag, name) in &names {
+            if self.contains(*flag) {
+                if !first {
+                    f.write_str(", ")?;
+                }
+
+                f.write_str(name)?;
+                first = false;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum Value {
+    Bool(bool),
+    Int(i64),
+    Float(f64),
+    /// The value of a string or enum cvar.
+    String(String),
+}
+
+/// Formats the value as it is typed in the console, with bools as `0`
+/// or `1`.
+impl fmt::Display for Value {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Value::Bool(value) => write!(f, "{}", *value as u8),
+            Value::Int(value) => write!(f, "{}", value),
+            Value::Float(value) => write!(f, "{}", value),
+            Value::String(value) => f.write_str(value),
+        }
+    }
+}
+
+/// The type of a [`Cvar`].
+#[derive(Clone, Debug, PartialEq)]
+pub enum Kind {
+    Bool,
+    Int,
+    Float,
+    String,
+    /// A string, which must be one of the variants.
+    Enum(Vec<String>),
+}
+
+impl Kind {
+    /// Parses [`text`] as typed in the console, or returns `None` if it
+    /// isn't a value of this kind. Bools can be `0`/`1`, `true`/`false`,
+    /// `on`/`off` or `yes`/`no`.
+    pub fn parse(&self, text: &str) -> Option<Value> {
+        match self {
+            Kind::Bool => match text.to_ascii_lowercase().as_str() {
+                "1" | "true" | "on" | "yes" => Some(Value::Bool(true)),
+                "0" | "false" | "off" | "no" => Some(Value::Bool(false)),
+                _ => None,
+            },
+            Kind::Int => text.parse().ok().map(Value::Int),
+            Kind::Float => text
+                .parse()
+                .ok()
+                .filter(|value: &f64| value.is_finite())
+                .map(Value::Float),
+            Kind::String => Some(Value::String(text.to_string())),
+            Kind::Enum(variants) => variants
+                .iter()
+                .find(|variant| variant.eq_ignore_ascii_case(text))
+                .map(|variant| Value::String(variant.clone())),
+        }
+    }
+
+    /// Whether [`value`] is of this kind.
+    pub fn matches(&self, value: &Value) -> bool {
+        match (self, value) {
+            (Kind::Bool, Value::Bool(_))
+            | (Kind::Int, Value::Int(_))
+            | (Kind::Float, Value::Float(_))
+            | (Kind::String, Value::String(_)) => true,
+            (Kind::Enum(variants), Value::String(value)) => variants.contains(value),
+            _ => false,
+        }
+    }
+}
+
+impl fmt::Display for Kind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Kind::Bool => f.write_str("a bool"),
+            Kind::Int => f.write_str("an integer"),
+            Kind::Float => f.write_str("a number"),
+            Kind::String => f.write_str("a string"),
+            Kind::Enum(variants) => write!(f, "one of {}", variants.join(", ")),
+        }
+    }
+}
+
+/// A console variable, which tunes the engine or game while it runs.
+///
+/// ```
+/// use steadfast_console::{Cvar, Flags};
+///
+/// let cvar = Cvar::float("m_sensitivity", 1.0)
+///     .with_description("Mouse look speed")
+///     .with_range(0.1, 10.0)
+///     .with_flags(Flags::ARCHIVE);
+/// ```
+#[derive(Clone, Debug, PartialEq)]
+pub struct Cvar {
+    name: String,
+    description: String,
+    kind: Kind,
+    default: Value,
+    value: Value,
+    /// The bounds of numeric cvars, inclusive.
+    min: Option<f64>,
+    max: Option<f64>,
+    flags: Flags,
+}
+
+impl Cvar {
+    fn new(name: impl Into<String>, kind: Kind, default: Value) -> Self {
+        Self {
+            name: name.into(),
+            description: String::new(),
+            kind,
+            value: default.clone(),
+            default,
+            min: None,
+            max: None,
+            flags: Flags::NONE,
+        }
+    }
+
+    pub fn bool(name: impl Into<String>, default: bool) -> Self {
+        Self::new(name, Kind::Bool, Value::Bool(default))
+    }
+
+    pub fn int(name: impl Into<String>, default: i64) -> Self {
+        Self::new(name, Kind::Int, Value::Int(default))
+    }
+
+    pub fn float(name: impl Into<String>, default: f64) -> Self {
+        Self::new(name, Kind::Float, Value::Float(default))
+    }
+
+    pub fn string(name: impl Into<String>, default: impl Into<String>) -> Self {
+        Self::new(name, Kind::String, Value::String(default.into()))
+    }
+
+    /// A cvar which is one of [`variants`], like a string but checked
+    /// when it is set.
+    pub fn enumeration(name: impl Into<String>, variants: &[&str], default: &str) -> Self {
+        assert!(
+            variants.contains(&default),
+            "The default must be one of the variants"
+        );
+
+        let variants = variants.iter().map(|variant| variant.to_string()).collect();
+
+        Self::new(name, Kind::Enum(variants), Value::String(default.into()))
+    }
+
+    pub fn with_description(mut self, description: impl Into<String>) -> Self {
+        self.description = description.into();
+        self
+    }
+
+    /// Limits a numeric cvar to between [`min`] and [`max`], inclusive.
+    pub fn with_range(mut self, min: f64, max: f64) -> Self {
+        assert!(
+            matches!(self.kind, Kind::Int | Kind::Float),
+            "Only numeric cvars have a range"
+        );
+        assert!(min <= max, "The minimum must not be above the maximum");
+
+        self.min = Some(min);
+        self.max = Some(max);
+
+        assert!(
+            self.check(self.default.clone()).is_ok(),
+            "The default must be in range"
+        );
+
+        self
+    }
+
+    pub fn with_flags(mut self, flags: Flags) -> Self {
+        self.flags |= flags;
+        self
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn description(&self) -> &str {
+        &self.description
+    }
+
+    pub fn kind(&self) -> &Kind {
+        &self.kind
+    }
+
+    pub fn value(&self) -> &Value {
+        &self.value
+    }
+
+    pub fn default_value(&self) -> &Value {
+        &self.default
+    }
+
+    pub fn is_default(&self) -> bool {
+        self.value == self.default
+    }
+
+    /// The inclusive bounds of a numeric cvar.
+    pub fn range(&self) -> (Option<f64>, Option<f64>) {
+        (self.min, self.max)
+    }
+
+    pub fn flags(&self) -> Flags {
+        self.flags
+    }
+
+    /// Checks [`value`] is of the cvar's kind and in its' range, before
+    /// it is set. Integers are accepted by float cvars.
+    pub fn check(&self, value: Value) -> Result<Value, Error> {
+        let value = match (&self.kind, value) {
+            (Kind::Float, Value::Int(value)) => Value::Float(value as f64),
+            (_, value) => value,
+        };
+
+        if !self.kind.matches(&value) {
+            return Err(self.invalid(&value, self.kind.to_string()));
+        }
+
+        let number = match value {
+            Value::Int(value) => value as f64,
+            Value::Float(value) => value,
+            _ => return Ok(value),
+        };
+
+        let below = self.min.is_some_and(|min| number < min);
+        let above = self.max.is_some_and(|max| number > max);
+
+        if below || above {
+            let expected = format!(
+                "{} from {} to {}",
+                self.kind,
+                self.min.unwrap_or(f64::NEG_INFINITY),
+                self.max.unwrap_or(f64::INFINITY)
+            );
+
+            return Err(self.invalid(&value, expected));
+        }
+
+        Ok(value)
+    }
+
+    /// Parses and checks [`text`], as typed in the console.
+    pub fn parse(&self, text: &str) -> Result<Value, Error> {
+        let value = self.kind.parse(text).ok_or_else(|| Error::InvalidValue {
+            name: self.name.clone(),
+            value: text.to_string(),
+            expected: self.kind.to_string(),
+        })?;
+
+        self.check(value)
+    }
+
+    /// Sets the value without checking the flags, which the registry
+    /// does.
+    pub(crate) fn set_unchecked(&mut self, value: Value) {
+        self.value = value;
+    }
+
+    pub(crate) fn reset(&mut self) {
+        self.value = self.default.clone();
+    }
+
+    fn invalid(&self, value: &Value, expected: String) -> Error {
+        Error::InvalidValue {
+            name: self.name.clone(),
+            value: value.to_string(),
+            expected,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Cvar, Flags, Kind, Value};
+
+    #[test]
+    fn values_are_parsed_by_kind() {
+        assert_eq!(Kind::Bool.parse("On"), Some(Value::Bool(true)));
+        assert_eq!(Kind::Bool.parse("0"), Some(Value::Bool(false)));
+        assert_eq!(Kind::Bool.parse("2"), None);
+        assert_eq!(Kind::Int.parse("-3"), Some(Value::Int(-3)));
+        assert_eq!(Kind::Int.parse("1.5"), None);
+        assert_eq!(Kind::Float.parse("inf"), None);
+
+        let mode = Cvar::enumeration("r_mode", &["windowed", "fullscreen"], "windowed");
+        assert_eq!(
+            mode.parse("FullScreen").unwrap(),
+            Value::String(String::from("fullscreen"))
+        );
+        assert!(mode.parse("borderless").is_err());
+    }
+
+    #[test]
+    fn ranges_are_checked() {
+        let fov = Cvar::int("fov", 90).with_range(60.0, 120.0);
+
+        assert_eq!(fov.parse("120").unwrap(), Value::Int(120));
+        assert!(fov.parse("121").is_err());
+
+        let scale = Cvar::float("timescale", 1.0).with_range(0.0, 4.0);
+        assert_eq!(scale.check(Value::Int(2)).unwrap(), Value::Float(2.0));
+        assert!(scale.check(Value::Bool(true)).is_err());
+    }
+
+    #[test]
+    fn flags_combine() {
+        let flags = Flags::CHEAT | Flags::ARCHIVE;
+
+        assert!(flags.contains(Flags::CHEAT) && !flags.contains(Flags::READ_ONLY));
+        assert_eq!(flags.to_string(), "cheat, archive");
+        assert_eq!(Value::Bool(true).to_string(), "1");
+    }
+}
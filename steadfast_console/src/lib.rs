@@ -0,0 +1,49 @@
+mod buffer;
+mod command;
+mod cvar;
+mod registry;
+
+pub use crate::buffer::*;
+pub use crate::command::*;
+pub use crate::cvar::*;
+pub use crate::registry::*;
+
+use std::path::PathBuf;
+use thiserror::Error;
+
+#[derive(Debug, Error)]
+pub enum Error {
+    #[error("Unknown cvar `{0}`")]
+    UnknownCvar(String),
+
+    #[error("Unknown command `{0}`")]
+    UnknownCommand(String),
+
+    #[error("Usage: {0}")]
+    Usage(&'static str),
+
+    #[error("`{0}` is read only")]
+    ReadOnly(String),
+
+    #[error("`{0}` is a cheat, set `sv_cheats 1` to change it")]
+    Cheat(String),
+
+    #[error("`{value}` is not a valid value for `{name}`, which is {expected}")]
+    InvalidValue {
+        name: String,
+        value: String,
+        expected: String,
+    },
+
+    #[error("`{0}` is already registered as a different type")]
+    Redefined(String),
+
+    #[error("Config files may only exec {0} files deep")]
+    ExecDepth(usize),
+
+    #[error("Failed to access {path}: {source}")]
+    Io {
+        path: PathBuf,
+        source: std::io::Error,
+    },
+}
@@ -0,0 +1,306 @@
+use crate::{Cvar, Error, Flags, Value};
+use std::collections::{BTreeMap, HashMap};
+
+/// The cvar which allows changing [`Flags::CHEAT`] cvars.
+pub const CHEATS: &str = "sv_cheats";
+
+/// Every registered cvar, by name.
+///
+/// The runtime provides the registry to every module as a host resource,
+/// so the values outlive the modules which registered them, and are kept
+/// when those modules are reloaded and register them again.
+#[derive(Debug)]
+pub struct Cvars {
+    cvars: BTreeMap<String, Cvar>,
+    /// Values set before their cvar was registered, e.g. from the
+    /// command line, which are applied when it is.
+    pending: HashMap<String, String>,
+}
+
+impl Cvars {
+    pub fn new() -> Self {
+        let mut cvars = Self {
+            cvars: BTreeMap::new(),
+            pending: HashMap::new(),
+        };
+
+        let cheats = Cvar::bool(CHEATS, false).with_description("Allows changing cheat cvars");
+        cvars.register(cheats).unwrap();
+
+        cvars
+    }
+
+    /// Registers [`cvar`], or updates its' description, default, range and
+    /// flags if it's already registered, keeping its' value.
+    ///
+    /// A value set before the cvar was registered is applied, as long as
+    /// it could be set with [`set_str`].
+    pub fn register(&mut self, mut cvar: Cvar) -> Result<&Cvar, Error> {
+        let name = cvar.name().to_string();
+
+        if let Some(existing) = self.cvars.get(&name) {
+            let kind = std::mem::discriminant(cvar.kind());
+
+            if kind != std::mem::discriminant(existing.kind()) {
+                return Err(Error::Redefined(name));
+            }
+
+            match cvar.check(existing.value().clone()) {
+                Ok(value) => cvar.set_unchecked(value),
+                Err(error) => log::warn!("Resetting `{}`: {}", name, error),
+            }
+
+            self.cvars.insert(name.clone(), cvar);
+
+            return Ok(&self.cvars[&name]);
+        }
+
+        self.cvars.insert(name.clone(), cvar);
+
+        if let Some(text) = self.pending.remove(&name) {
+            if let Err(error) = self.set_str(&name, &text) {
+                log::warn!("{}", error);
+            }
+        }
+
+        Ok(&self.cvars[&name])
+    }
+
+    pub fn get(&self, name: &str) -> Option<&Cvar> {
+        self.cvars.get(name)
+    }
+
+    pub fn contains(&self, name: &str) -> bool {
+        self.cvars.contains_key(name)
+    }
+
+    pub fn value(&self, name: &str) -> Option<&Value> {
+        self.get(name).map(Cvar::value)
+    }
+
+    /// The value of the bool cvar [`name`], or `None` if there is no such
+    /// cvar of that type. As are the other typed getters.
+    pub fn bool(&self, name: &str) -> Option<bool> {
+        match self.value(name)? {
+            Value::Bool(value) => Some(*value),
+            _ => None,
+        }
+    }
+
+    pub fn int(&self, name: &str) -> Option<i64> {
+        match self.value(name)? {
+            Value::Int(value) => Some(*value),
+            _ => None,
+        }
+    }
+
+    pub fn float(&self, name: &str) -> Option<f64> {
+        match self.value(name)? {
+            Value::Float(value) => Some(*value),
+            _ => None,
+        }
+    }
+
+    /// The value of a string or enum cvar.
+    pub fn str(&self, name: &str) -> Option<&str> {
+        match self.value(name)? {
+            Value::String(value) => Some(value),
+            _ => None,
+        }
+    }
+
+    pub fn cheats_enabled(&self) -> bool {
+        self.bool(CHEATS).unwrap_or(false)
+    }
+
+    /// Sets the cvar [`name`] to [`value`], if its' flags allow it.
+    pub fn set(&mut self, name: &str, value: Value) -> Result<(), Error> {
+        let cvar = self.settable(name)?;
+        let value = cvar.check(value)?;
+
+        self.set_checked(name, value);
+
+        Ok(())
+    }
+
+    /// Sets the cvar [`name`] from [`text`], as typed in the console.
+    pub fn set_str(&mut self, name: &str, text: &str) -> Result<(), Error> {
+        let cvar = self.settable(name)?;
+        let value = cvar.parse(text)?;
+
+        self.set_checked(name, value);
+
+        Ok(())
+    }
+
+    /// Sets the cvar [`name`] from [`text`] like [`set_str`], or keeps it
+    /// to set when the cvar is registered, returning `false`.
+    pub fn set_or_defer(&mut self, name: &str, text: &str) -> Result<bool, Error> {
+        if !self.contains(name) {
+            self.pending.insert(name.to_string(), text.to_string());
+
+            return Ok(false);
+        }
+
+        self.set_str(name, text).map(|_| true)
+    }
+
+    /// Sets the cvar [`name`] back to its' default.
+    pub fn reset(&mut self, name: &str) -> Result<(), Error> {
+        let default = self.settable(name)?.default_value().clone();
+
+        self.set_checked(name, default);
+
+        Ok(())
+    }
+
+    /// Every cvar, sorted by name.
+    pub fn iter(&self) -> impl Iterator<Item = &Cvar> {
+        self.cvars.values()
+    }
+
+    /// The [`Flags::ARCHIVE`] cvars which have been changed from their
+    /// default, which are saved to the user's config file.
+    pub fn archived(&self) -> impl Iterator<Item = &Cvar> {
+        self.iter()
+            .filter(|cvar| cvar.flags().contains(Flags::ARCHIVE) && !cvar.is_default())
+    }
+
+    /// The values set for cvars which haven't been registered yet, as
+    /// names and the text they will be set from.
+    pub fn pending(&self) -> impl Iterator<Item = (&str, &str)> {
+        self.pending
+            .iter()
+            .map(|(name, text)| (name.as_str(), text.as_str()))
+    }
+
+    fn settable(&self, name: &str) -> Result<&Cvar, Error> {
+        let cvar = self
+            .get(name)
+            .ok_or_else(|| Error::UnknownCvar(name.to_string()))?;
+
+        if cvar.flags().contains(Flags::READ_ONLY) {
+            return Err(Error::ReadOnly(name.to_string()));
+        }
+
+        if cvar.flags().contains(Flags::CHEAT) && !self.cheats_enabled() {
+            return Err(Error::Cheat(name.to_string()));
+        }
+
+        Ok(cvar)
+    }
+
+    fn set_checked(&mut self, name: &str, value: Value) {
+        self.cvars.get_mut(name).unwrap().set_unchecked(value);
+
+        // Cheats only last while they are allowed
+        if name == CHEATS && !self.cheats_enabled() {
+            for cvar in self.cvars.values_mut() {
+                if cvar.flags().contains(Flags::CHEAT) {
+                    cvar.reset();
+                }
+            }
+        }
+    }
+}
+
+impl Default for Cvars {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Cvars, CHEATS};
+    use crate::{Cvar, Error, Flags, Value};
+
+    #[test]
+    fn registering_again_keeps_the_value() {
+        let mut cvars = Cvars::new();
+
+        cvars.register(Cvar::int("fov", 90)).unwrap();
+        cvars.set("fov", Value::Int(100)).unwrap();
+
+        // As happens when the module which registered it is reloaded
+        let fov = cvars
+            .register(Cvar::int("fov", 80).with_range(60.0, 120.0))
+            .unwrap();
+        assert_eq!(fov.value(), &Value::Int(100));
+        assert_eq!(fov.default_value(), &Value::Int(80));
+
+        // Unless it is out of the new range
+        cvars
+            .register(Cvar::int("fov", 70).with_range(60.0, 90.0))
+            .unwrap();
+        assert_eq!(cvars.int("fov"), Some(70));
+
+        assert!(matches!(
+            cvars.register(Cvar::float("fov", 90.0)),
+            Err(Error::Redefined(_))
+        ));
+    }
+
+    #[test]
+    fn values_can_be_set_before_registering() {
+        let mut cvars = Cvars::new();
+
+        assert!(!cvars.set_or_defer("r_vsync", "0").unwrap());
+        assert!(!cvars.set_or_defer("r_width", "wide").unwrap());
+
+        cvars.register(Cvar::bool("r_vsync", true)).unwrap();
+        cvars.register(Cvar::int("r_width", 1280)).unwrap();
+
+        assert_eq!(cvars.bool("r_vsync"), Some(false));
+        assert_eq!(cvars.int("r_width"), Some(1280));
+        assert!(cvars.set_or_defer("r_vsync", "1").unwrap());
+    }
+
+    #[test]
+    fn flags_limit_setting() {
+        let mut cvars = Cvars::new();
+
+        cvars
+            .register(Cvar::string("version", "0.1.0").with_flags(Flags::READ_ONLY))
+            .unwrap();
+        cvars
+            .register(Cvar::bool("noclip", false).with_flags(Flags::CHEAT))
+            .unwrap();
+
+        assert!(matches!(
+            cvars.set_str("version", "1.0.0"),
+            Err(Error::ReadOnly(_))
+        ));
+        assert!(matches!(cvars.set_str("noclip", "1"), Err(Error::Cheat(_))));
+
+        cvars.set_str(CHEATS, "1").unwrap();
+        cvars.set_str("noclip", "1").unwrap();
+        assert_eq!(cvars.bool("noclip"), Some(true));
+
+        // Disabling cheats resets them
+        cvars.set_str(CHEATS, "0").unwrap();
+        assert_eq!(cvars.bool("noclip"), Some(false));
+    }
+
+    #[test]
+    fn only_changed_archive_cvars_are_archived() {
+        let mut cvars = Cvars::new();
+
+        for name in &["a", "b", "c"] {
+            cvars
+                .register(Cvar::float(*name, 1.0).with_flags(Flags::ARCHIVE))
+                .unwrap();
+        }
+
+        cvars.register(Cvar::float("d", 1.0)).unwrap();
+
+        for name in &["b", "c", "d"] {
+            cvars.set(name, Value::Float(2.0)).unwrap();
+        }
+
+        cvars.reset("c").unwrap();
+
+        let archived: Vec<_> = cvars.archived().map(Cvar::name).collect();
+        assert_eq!(archived, ["b"]);
+    }
+}
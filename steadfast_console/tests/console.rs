@@ -0,0 +1,129 @@
+use std::path::Path;
+use steadfast_console::{exec_file, execute, save_archive, Cvar, Cvars, Error, Flags};
+
+fn registered() -> Cvars {
+    let mut cvars = Cvars::new();
+
+    cvars
+        .register(
+            Cvar::bool("r_vsync", true)
+                .with_description("Waits for the display before presenting")
+                .with_flags(Flags::ARCHIVE),
+        )
+        .unwrap();
+    cvars
+        .register(
+            Cvar::enumeration("r_mode", &["windowed", "fullscreen"], "windowed")
+                .with_flags(Flags::ARCHIVE),
+        )
+        .unwrap();
+    cvars
+        .register(Cvar::string("name", "player").with_flags(Flags::ARCHIVE))
+        .unwrap();
+    cvars
+        .register(Cvar::float("timescale", 1.0).with_range(0.0, 4.0))
+        .unwrap();
+
+    cvars
+}
+
+#[test]
+fn console_commands_set_and_print_cvars() {
+    let mut cvars = registered();
+
+    assert_eq!(execute(&mut cvars, "r_vsync off").unwrap(), None);
+    assert_eq!(cvars.bool("r_vsync"), Some(false));
+
+    execute(&mut cvars, "toggle r_vsync").unwrap();
+    assert_eq!(cvars.bool("r_vsync"), Some(true));
+
+    let printed = execute(&mut cvars, "r_vsync").unwrap().unwrap();
+    assert!(printed.starts_with("r_vsync is 1, default 1 (archive)"));
+    assert!(printed.contains("Waits for the display"));
+
+    execute(&mut cvars, "set timescale 0.5").unwrap();
+    assert_eq!(cvars.float("timescale"), Some(0.5));
+
+    execute(&mut cvars, "reset timescale").unwrap();
+    assert_eq!(cvars.float("timescale"), Some(1.0));
+
+    assert!(matches!(
+        execute(&mut cvars, "timescale 5"),
+        Err(Error::InvalidValue { .. })
+    ));
+    assert!(matches!(
+        execute(&mut cvars, "quit now"),
+        Err(Error::UnknownCommand(_))
+    ));
+    assert!(matches!(execute(&mut cvars, "set"), Err(Error::Usage(_))));
+
+    let list = execute(&mut cvars, "cvarlist r_").unwrap().unwrap();
+    assert!(list.ends_with("2 cvars"), "{}", list);
+
+    // Unknown cvars are set once they are registered
+    assert!(execute(&mut cvars, "set fov 100").unwrap().is_some());
+    cvars.register(Cvar::int("fov", 90)).unwrap();
+    assert_eq!(cvars.int("fov"), Some(100));
+}
+
+#[test]
+fn archived_cvars_are_restored_from_the_config() {
+    let dir = Path::new(env!("CARGO_TARGET_TMPDIR")).join("console");
+    let path = dir.join("user/config.cfg");
+    let _ = std::fs::remove_file(&path);
+
+    let mut cvars = registered();
+    execute(&mut cvars, "r_mode fullscreen").unwrap();
+    execute(&mut cvars, r#"name "Big \"Bob\"""#).unwrap();
+    execute(&mut cvars, "timescale 2").unwrap();
+
+    // As set from the config for a module which wasn't loaded
+    execute(&mut cvars, r#"set editor_layout "two panes""#).unwrap();
+
+    save_archive(&cvars, &path).unwrap();
+
+    let config = std::fs::read_to_string(&path).unwrap();
+    assert!(config.contains("set r_mode fullscreen\n"));
+    assert!(config.contains("set editor_layout \"two panes\"\n"));
+    assert!(!config.contains("r_vsync") && !config.contains("timescale"));
+
+    let mut restored = registered();
+    exec_file(&mut restored, &path).unwrap();
+
+    assert_eq!(restored.str("r_mode"), Some("fullscreen"));
+    assert_eq!(restored.str("name"), Some(r#"Big "Bob""#));
+    assert_eq!(restored.float("timescale"), Some(1.0));
+    assert_eq!(
+        restored.pending().collect::<Vec<_>>(),
+        [("editor_layout", "two panes")]
+    );
+}
+
+#[test]
+fn config_files_can_exec_each_other() {
+    let dir = Path::new(env!("CARGO_TARGET_TMPDIR")).join("console_exec");
+    std::fs::create_dir_all(&dir).unwrap();
+
+    let inner = dir.join("inner.cfg");
+    let outer = dir.join("outer.cfg");
+    let looping = dir.join("loop.cfg");
+
+    std::fs::write(&inner, "timescale 3 // faster\nnot_a_command\n").unwrap();
+    std::fs::write(&outer, format!("exec \"{}\"\nr_vsync 0\n", inner.display())).unwrap();
+    std::fs::write(&looping, format!("exec \"{}\"\n", looping.display())).unwrap();
+
+    let mut cvars = registered();
+    exec_file(&mut cvars, &outer).unwrap();
+
+    // The failing line is skipped
+    assert_eq!(cvars.float("timescale"), Some(3.0));
+    assert_eq!(cvars.bool("r_vsync"), Some(false));
+
+    // Nested too deep, which is logged rather than recursing forever
+    exec_file(&mut cvars, &looping).unwrap();
+
+    assert!(matches!(
+        exec_file(&mut cvars, dir.join("missing.cfg")),
+        Err(Error::Io { .. })
+    ));
+}
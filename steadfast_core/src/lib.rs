@@ -1,3 +1,4 @@
 pub extern crate log;
+pub extern crate steadfast_console as console;
 pub extern crate steadfast_defs as def;
 pub extern crate steadfast_modules as module;
@@ -1,3 +1,96 @@
-pub struct Application {
-    pub num: u32,
+/// The window an [`Application`] opens in.
+#[derive(Clone, Debug, PartialEq)]
+pub struct WindowConfig {
+    pub width: u32,
+    pub height: u32,
+    pub resizable: bool,
+    pub fullscreen: bool,
+    pub vsync: bool,
+}
+
+impl Default for WindowConfig {
+    fn default() -> Self {
+        Self {
+            width: 1280,
+            height: 720,
+            resizable: true,
+            fullscreen: false,
+            vsync: true,
+        }
+    }
+}
+
+/// How the engine runs an [`Application`].
+#[derive(Clone, Debug, PartialEq)]
+pub struct ApplicationConfig {
+    pub title: String,
+
+    /// Frames per second to render at most, or `None` for no limit.
+    pub target_frame_rate: Option<f64>,
+
+    /// Calls to [`Application::on_fixed_update`] per second.
+    pub update_rate: f64,
+
+    pub window: WindowConfig,
+}
+
+impl Default for ApplicationConfig {
+    fn default() -> Self {
+        Self {
+            title: String::from("Steadfast"),
+            target_frame_rate: Some(60.0),
+            update_rate: 60.0,
+            window: WindowConfig::default(),
+        }
+    }
+}
+
+/// Sent to the [`Application`] by the platform, before the frame's
+/// updates.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Event {
+    Resized { width: u32, height: u32 },
+    Focused(bool),
+    CloseRequested,
+}
+
+/// The game, created by the game module and driven by the engine every
+/// frame. Time is in seconds.
+///
+/// The application keeps running when the game module is reloaded, with
+/// the code of the new library, see [`resume`].
+pub trait Application {
+    /// Read once, when the application is first started.
+    fn config(&self) -> ApplicationConfig {
+        ApplicationConfig::default()
+    }
+
+    fn on_start(&mut self) {}
+
+    /// Advances the simulation by [`dt`], which is the same every call.
+    fn on_fixed_update(&mut self, _dt: f64) {}
+
+    /// Runs once a frame with the time since the last frame.
+    fn on_update(&mut self, _dt: f64) {}
+
+    /// Draws the frame, [`alpha`] of the way from the last fixed update
+    /// to the next.
+    fn on_render(&mut self, _alpha: f64) {}
+
+    fn on_event(&mut self, _event: &Event) {}
+
+    /// Called when the engine shuts down, but not when the game module is
+    /// reloaded.
+    fn on_shutdown(&mut self) {}
+}
+
+/// Boxes the data of an application created by the previous build of the
+/// game library as an `A`, with the vtable of this build.
+///
+/// # Safety
+///
+/// [`application`] must point at the data of a `Box<A>` created by the
+/// game library, and `A` must have the same layout in both builds.
+pub unsafe fn resume<A: Application + 'static>(application: *mut ()) -> Box<dyn Application> {
+    Box::from_raw(application as *mut A)
 }
@@ -1 +1,210 @@
+use steadfast_core::def::engine::{Application, ApplicationConfig};
+use steadfast_core::log;
+use steadfast_core::module::{catch_panic, Host};
 
+/// The running application, kept on the host so that it keeps running
+/// when the engine is reloaded.
+type Running = Box<dyn Application>;
+
+/// Creates and starts the application.
+pub fn start(host: &mut Host, create: fn() -> Box<dyn Application>) {
+    let started = catch_panic(|| {
+        let mut application = create();
+        application.on_start();
+
+        application
+    });
+
+    match started {
+        Ok(application) => host.insert_resource(application),
+        Err(panic) => log::error!(
+            "Starting the application panicked: {}\n{}",
+            panic,
+            panic.backtrace
+        ),
+    }
+}
+
+/// Gives the running application the code of the reloaded game library,
+/// or starts one if none is running.
+pub fn reload(
+    host: &mut Host,
+    create: fn() -> Box<dyn Application>,
+    resume: fn(*mut ()) -> Box<dyn Application>,
+) {
+    let previous = match host.remove_resource::<Running>() {
+        Some(previous) => previous,
+        None => return start(host, create),
+    };
+
+    // The vtable of the previous application points into the unloaded
+    // game library, so only its' data is kept
+    let data = Box::into_raw(previous) as *mut ();
+
+    match catch_panic(|| resume(data)) {
+        Ok(application) => host.insert_resource(application),
+        Err(panic) => log::error!(
+            "Resuming the application panicked: {}\n{}",
+            panic,
+            panic.backtrace
+        ),
+    }
+}
+
+/// Runs [`f`] on the running application, if there is one.
+///
+/// An application which panics is dropped, so stops running until the
+/// game module is reloaded.
+pub fn with<R>(
+    host: &mut Host,
+    callback: &str,
+    f: impl FnOnce(&mut dyn Application) -> R,
+) -> Option<R> {
+    let application = host.resource_mut::<Running>()?;
+
+    match catch_panic(|| f(application.as_mut())) {
+        Ok(result) => Some(result),
+        Err(panic) => {
+            log::error!(
+                "`{}` of the application panicked: {}\n{}",
+                callback,
+                panic,
+                panic.backtrace
+            );
+
+            host.remove_resource::<Running>();
+
+            None
+        }
+    }
+}
+
+/// Whether an application is running, which it stops doing if it
+/// panics.
+pub fn is_running(host: &Host) -> bool {
+    host.resource::<Running>().is_some()
+}
+
+pub fn config(host: &Host) -> ApplicationConfig {
+    host.resource::<Running>()
+        .and_then(|application| catch_panic(|| application.config()).ok())
+        .unwrap_or_default()
+}
+
+/// Shuts down and drops the running application.
+pub fn shutdown(host: &mut Host) {
+    with(host, "on_shutdown", |application| application.on_shutdown());
+
+    host.remove_resource::<Running>();
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{config, is_running, reload, shutdown, start, with};
+    use std::sync::atomic::{AtomicU32, Ordering};
+    use steadfast_core::def::engine::{self, Application, ApplicationConfig};
+    use steadfast_core::module::Host;
+
+    static STARTED: AtomicU32 = AtomicU32::new(0);
+    static SHUT_DOWN: AtomicU32 = AtomicU32::new(0);
+
+    struct Counter {
+        ticks: u32,
+    }
+
+    impl Application for Counter {
+        fn config(&self) -> ApplicationConfig {
+            ApplicationConfig {
+                update_rate: 30.0,
+                ..ApplicationConfig::default()
+            }
+        }
+
+        fn on_start(&mut self) {
+            STARTED.fetch_add(1, Ordering::Relaxed);
+        }
+
+        fn on_fixed_update(&mut self, _dt: f64) {
+            self.ticks += 1;
+
+            if self.ticks == 3 {
+                panic!("Third tick");
+            }
+        }
+
+        fn on_shutdown(&mut self) {
+            SHUT_DOWN.fetch_add(1, Ordering::Relaxed);
+        }
+    }
+
+    fn create() -> Box<dyn Application> {
+        Box::new(Counter { ticks: 0 })
+    }
+
+    #[test]
+    fn applications_run_until_they_panic() {
+        let mut host = Host::default();
+        assert_eq!(config(&host), ApplicationConfig::default());
+
+        start(&mut host, create);
+        assert_eq!(STARTED.load(Ordering::Relaxed), 1);
+        assert_eq!(config(&host).update_rate, 30.0);
+
+        for _ in 0..2 {
+            assert!(with(&mut host, "on_fixed_update", |app| app.on_fixed_update(0.1)).is_some());
+        }
+
+        // The panicking application is dropped without being shut down
+        assert!(with(&mut host, "on_fixed_update", |app| app.on_fixed_update(0.1)).is_none());
+        assert!(!is_running(&host));
+        assert!(with(&mut host, "on_update", |app| app.on_update(0.1)).is_none());
+
+        start(&mut host, create);
+        assert!(is_running(&host));
+
+        shutdown(&mut host);
+        assert_eq!(STARTED.load(Ordering::Relaxed), 2);
+        assert_eq!(SHUT_DOWN.load(Ordering::Relaxed), 1);
+    }
+
+    /// Reports its' ticks as its' title.
+    struct Ticks(u32);
+
+    impl Application for Ticks {
+        fn config(&self) -> ApplicationConfig {
+            ApplicationConfig {
+                title: self.0.to_string(),
+                ..ApplicationConfig::default()
+            }
+        }
+
+        fn on_update(&mut self, _dt: f64) {
+            self.0 += 1;
+        }
+    }
+
+    fn create_ticks() -> Box<dyn Application> {
+        Box::new(Ticks(0))
+    }
+
+    fn resume_ticks(application: *mut ()) -> Box<dyn Application> {
+        unsafe { engine::resume::<Ticks>(application) }
+    }
+
+    #[test]
+    fn applications_keep_running_across_reloads() {
+        let mut host = Host::default();
+
+        reload(&mut host, create_ticks, resume_ticks);
+
+        for _ in 0..2 {
+            with(&mut host, "on_update", |app| app.on_update(0.1));
+        }
+
+        reload(&mut host, create_ticks, resume_ticks);
+        assert_eq!(config(&host).title, "2");
+
+        with(&mut host, "on_update", |app| app.on_update(0.1));
+        assert_eq!(config(&host).title, "3");
+    }
+}
@@ -0,0 +1,79 @@
+use steadfast_core::console::{Cvar, Cvars, Flags};
+use steadfast_core::def::engine::ApplicationConfig;
+use steadfast_core::log;
+use steadfast_core::module::Host;
+
+/// Whether to wait for the display to refresh before presenting frames.
+pub const VSYNC: &str = "r_vsync";
+/// Frames per second to render at most, overriding the application.
+pub const MAX_FPS: &str = "com_maxfps";
+
+/// Registers the engine's cvars, which keep their values when the engine
+/// is reloaded.
+pub fn register(host: &mut Host) {
+    let cvars = match host.resource_mut::<Cvars>() {
+        Some(cvars) => cvars,
+        None => return,
+    };
+
+    let engine = vec![
+        Cvar::bool(VSYNC, true)
+            .with_description("Waits for the display to refresh before presenting frames")
+            .with_flags(Flags::ARCHIVE),
+        Cvar::int(MAX_FPS, 0)
+            .with_description("Frames per second to render at most, or 0 for the game's target")
+            .with_range(0.0, 1000.0)
+            .with_flags(Flags::ARCHIVE),
+    ];
+
+    for cvar in engine {
+        if let Err(error) = cvars.register(cvar) {
+            log::error!("{}", error);
+        }
+    }
+}
+
+/// Overrides the parts of [`config`] the engine's cvars set.
+pub fn apply(host: &Host, config: &mut ApplicationConfig) {
+    let cvars = match host.resource::<Cvars>() {
+        Some(cvars) => cvars,
+        None => return,
+    };
+
+    if let Some(vsync) = cvars.bool(VSYNC) {
+        config.window.vsync = vsync;
+    }
+
+    if let Some(max_fps) = cvars.int(MAX_FPS).filter(|max_fps| *max_fps > 0) {
+        config.target_frame_rate = Some(max_fps as f64);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{apply, register, MAX_FPS};
+    use steadfast_core::console::Cvars;
+    use steadfast_core::def::engine::ApplicationConfig;
+    use steadfast_core::module::Host;
+
+    #[test]
+    fn cvars_override_the_application_config() {
+        let mut host = Host::default();
+        let mut cvars = Cvars::new();
+        cvars.set_or_defer("r_vsync", "0").unwrap();
+
+        host.insert_resource(cvars);
+        register(&mut host);
+
+        let mut config = ApplicationConfig::default();
+        apply(&host, &mut config);
+        assert!(!config.window.vsync);
+        assert_eq!(config.target_frame_rate, Some(60.0));
+
+        let cvars = host.resource_mut::<Cvars>().unwrap();
+        cvars.set_str(MAX_FPS, "144").unwrap();
+
+        apply(&host, &mut config);
+        assert_eq!(config.target_frame_rate, Some(144.0));
+    }
+}
@@ -1,12 +1,13 @@
 mod application;
+mod cvars;
 
-use steadfast_core::def::engine::Application;
+use steadfast_core::def::engine::{ApplicationConfig, Event};
 use steadfast_core::module::engine::EngineExports;
-use steadfast_core::module::{init_module, Host};
+use steadfast_core::module::game::GameExports;
+use steadfast_core::module::{init_module, module_state, Host, ReloadEvent};
 
-struct State<'a> {
-    application: Option<Application>,
-    host: &'a Host,
+module_state! {
+    struct State {}
 }
 
 init_module! {
@@ -19,24 +20,79 @@ init_module! {
     deinit: deinit,
 }
 
-fn init(state: &mut State) {
-    state.application = None;
+#[no_mangle]
+fn engine_config(host: &Host) -> ApplicationConfig {
+    let mut config = application::config(host);
+    cvars::apply(host, &mut config);
+
+    config
+}
+
+#[no_mangle]
+fn engine_running(host: &Host) -> bool {
+    application::is_running(host)
+}
+
+#[no_mangle]
+fn engine_fixed_update(host: &mut Host, dt: f64) {
+    application::with(host, "on_fixed_update", |application| {
+        application.on_fixed_update(dt)
+    });
+}
+
+#[no_mangle]
+fn engine_update(host: &mut Host, dt: f64) {
+    application::with(host, "on_update", |application| application.on_update(dt));
+}
+
+#[no_mangle]
+fn engine_render(host: &mut Host, alpha: f64) {
+    application::with(host, "on_render", |application| {
+        application.on_render(alpha)
+    });
 }
 
-fn reload(state: &mut State) -> EngineExports {
-    EngineExports {}
+#[no_mangle]
+fn engine_event(host: &mut Host, event: &Event) {
+    application::with(host, "on_event", |application| application.on_event(event));
 }
 
-fn update(host: &'static mut Host, state: &mut State) {
-    state.host = host;
+#[no_mangle]
+fn engine_shutdown(host: &mut Host) {
+    application::shutdown(host);
+}
+
+fn init(_state: &mut State) {}
+
+fn reload(_state: &mut State) -> EngineExports {
+    EngineExports {
+        engine_config,
+        engine_running,
+        engine_fixed_update,
+        engine_update,
+        engine_render,
+        engine_event,
+        engine_shutdown,
+    }
+}
+
+/// Starts the application, and moves it to the code of the game whenever
+/// the game is reloaded.
+fn update(host: &mut Host, event: &ReloadEvent, _state: &mut State) {
+    if event.module != "libgame" {
+        return;
+    }
+
+    cvars::register(host);
+
+    if let Some(game) = host.get::<GameExports>() {
+        let create = game.create_application;
+        let resume = game.resume_application;
 
-    if let Some(game) = &host.libgame {
-        if state.application.is_none() {
-            state.application = Some(((*game).create_application)());
-        }
+        application::reload(host, create, resume);
     }
 }
 
-fn unload(state: &mut State) {}
+fn unload(_state: &mut State) {}
 
-fn deinit(state: &mut State) {}
+fn deinit(_state: &mut State) {}
@@ -0,0 +1,19 @@
+use std::env;
+use std::process::Command;
+
+/// Records the version of the compiler building this crate, which is
+/// stamped into every module so that the host can refuse modules built by
+/// a different compiler.
+fn main() {
+    let rustc = env::var("RUSTC").unwrap_or_else(|_| String::from("rustc"));
+
+    let version = Command::new(rustc)
+        .arg("--version")
+        .output()
+        .ok()
+        .and_then(|output| String::from_utf8(output.stdout).ok())
+        .unwrap_or_default();
+
+    println!("cargo:rustc-env=STEADFAST_RUSTC_VERSION={}", version.trim());
+    println!("cargo:rerun-if-env-changed=RUSTC");
+}
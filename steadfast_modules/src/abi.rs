@@ -0,0 +1,145 @@
+use crate::Exports;
+use std::fmt::{self, Display, Formatter};
+
+/// Describes what a module was compiled against.
+///
+/// Modules and the host exchange plain Rust types, whose layout is only
+/// stable when both are built by the same compiler, against the same
+/// version of this crate, with the same exports. [`init_module!`] exports
+/// this as `__MODULE_ABI`, which the host checks before touching anything
+/// else in the library.
+///
+/// The descriptor is `#[repr(C)]` and only holds plain data, so it can be
+/// read from any library regardless of how it was built.
+#[repr(C)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct AbiDescriptor {
+    /// Bumped whenever the layout of this struct changes.
+    pub revision: u32,
+    pub rustc: AbiString,
+    pub crate_version: AbiString,
+
+    /// A hash of the name and signature of every export.
+    pub exports: u64,
+}
+
+impl AbiDescriptor {
+    pub const REVISION: u32 = 1;
+
+    /// The descriptor of a module exporting [`VTable`], as built by the
+    /// current compiler.
+    pub const fn new<VTable: Exports>() -> Self {
+        Self {
+            revision: Self::REVISION,
+            rustc: AbiString::new(env!("STEADFAST_RUSTC_VERSION")),
+            crate_version: AbiString::new(env!("CARGO_PKG_VERSION")),
+            exports: VTable::SIGNATURE,
+        }
+    }
+}
+
+impl Display for AbiDescriptor {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        if self.revision != Self::REVISION {
+            return write!(f, "(descriptor revision {})", self.revision);
+        }
+
+        write!(
+            f,
+            "({}, steadfast_modules {}, exports {:016x})",
+            self.rustc, self.crate_version, self.exports
+        )
+    }
+}
+
+/// A string stored inline, truncated to [`AbiString::CAPACITY`] bytes.
+#[repr(C)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct AbiString {
+    len: u32,
+    bytes: [u8; AbiString::CAPACITY],
+}
+
+impl AbiString {
+    pub const CAPACITY: usize = 60;
+
+    pub const fn new(string: &str) -> Self {
+        let mut bytes = [0; Self::CAPACITY];
+        let mut len = 0;
+
+        while len < string.len() && len < Self::CAPACITY {
+            bytes[len] = string.as_bytes()[len];
+            len += 1;
+        }
+
+        Self {
+            len: len as u32,
+            bytes,
+        }
+    }
+
+    pub fn as_str(&self) -> &str {
+        let len = (self.len as usize).min(Self::CAPACITY);
+
+        // Truncation may have split a character
+        match std::str::from_utf8(&self.bytes[..len]) {
+            Ok(string) => string,
+            Err(error) => std::str::from_utf8(&self.bytes[..error.valid_up_to()]).unwrap(),
+        }
+    }
+}
+
+impl Display for AbiString {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// The 64-bit FNV-1a hash, usable in const contexts.
+///
+/// Only public for [`exports!`], which hashes the declaration of the
+/// exports with it.
+#[doc(hidden)]
+pub const fn fnv1a(bytes: &[u8]) -> u64 {
+    fnv1a_extend(0xcbf2_9ce4_8422_2325, bytes)
+}
+
+/// Continues a [`fnv1a`] [`hash`] with more [`bytes`].
+pub(crate) const fn fnv1a_extend(mut hash: u64, bytes: &[u8]) -> u64 {
+    let mut i = 0;
+
+    while i < bytes.len() {
+        hash ^= bytes[i] as u64;
+        hash = hash.wrapping_mul(0x0100_0000_01b3);
+        i += 1;
+    }
+
+    hash
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::engine::EngineExports;
+    use crate::game::GameExports;
+    use crate::{AbiDescriptor, AbiString};
+
+    #[test]
+    fn descriptors_differ_by_exports() {
+        assert_eq!(
+            AbiDescriptor::new::<GameExports>(),
+            AbiDescriptor::new::<GameExports>()
+        );
+        assert_ne!(
+            AbiDescriptor::new::<GameExports>(),
+            AbiDescriptor::new::<EngineExports>()
+        );
+    }
+
+    #[test]
+    fn strings_are_truncated_at_char_boundaries() {
+        let string = AbiString::new(&"é".repeat(AbiString::CAPACITY));
+
+        assert_eq!(string.as_str(), "é".repeat(AbiString::CAPACITY / 2));
+        assert_eq!(AbiString::new("1.0.0").as_str(), "1.0.0");
+    }
+}
@@ -0,0 +1,138 @@
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::sync::atomic::{AtomicPtr, Ordering};
+
+/// The host's global allocator, which every module loaded from a dynamic
+/// library allocates through.
+///
+/// Each library links its' own copy of the standard library, with its'
+/// own global allocator, but memory is freely passed between the host
+/// and its' modules, e.g. the boxes and strings modules return. Memory
+/// must be freed by the allocator which allocated it, so the host hands
+/// its' allocator to every library it loads, see [`ModuleAllocator`].
+#[derive(Debug)]
+pub struct HostAllocator {
+    pub alloc: unsafe fn(Layout) -> *mut u8,
+    pub dealloc: unsafe fn(*mut u8, Layout),
+    pub alloc_zeroed: unsafe fn(Layout) -> *mut u8,
+    pub realloc: unsafe fn(*mut u8, Layout, usize) -> *mut u8,
+}
+
+/// The global allocator of the process this copy of the crate is linked
+/// into.
+static CURRENT: HostAllocator = HostAllocator {
+    alloc: std::alloc::alloc,
+    dealloc: std::alloc::dealloc,
+    alloc_zeroed: std::alloc::alloc_zeroed,
+    realloc: std::alloc::realloc,
+};
+
+impl HostAllocator {
+    /// The global allocator of the host, when called by the host.
+    pub fn current() -> &'static HostAllocator {
+        &CURRENT
+    }
+}
+
+/// The global allocator of a module built by [`init_module!`], which
+/// allocates through the [`HostAllocator`] the host installs when it
+/// loads the library, before calling into it.
+///
+/// Until then, e.g. when the module is run by its' own tests, it
+/// allocates from the system.
+#[derive(Debug)]
+pub struct ModuleAllocator {
+    host: AtomicPtr<HostAllocator>,
+}
+
+impl ModuleAllocator {
+    #[allow(clippy::new_without_default)]
+    pub const fn new() -> Self {
+        Self {
+            host: AtomicPtr::new(std::ptr::null_mut()),
+        }
+    }
+
+    /// Has the module allocate through [`host`] from now on.
+    ///
+    /// # Safety
+    ///
+    /// Nothing the module allocated before may be freed after, so this
+    /// must be called before anything in the module is.
+    pub unsafe fn install(&self, host: &'static HostAllocator) {
+        self.host
+            .store(host as *const HostAllocator as *mut _, Ordering::Release);
+    }
+
+    fn host(&self) -> Option<&HostAllocator> {
+        unsafe { self.host.load(Ordering::Acquire).as_ref() }
+    }
+}
+
+unsafe impl GlobalAlloc for ModuleAllocator {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        match self.host() {
+            Some(host) => (host.alloc)(layout),
+            None => System.alloc(layout),
+        }
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        match self.host() {
+            Some(host) => (host.dealloc)(ptr, layout),
+            None => System.dealloc(ptr, layout),
+        }
+    }
+
+    unsafe fn alloc_zeroed(&self, layout: Layout) -> *mut u8 {
+        match self.host() {
+            Some(host) => (host.alloc_zeroed)(layout),
+            None => System.alloc_zeroed(layout),
+        }
+    }
+
+    unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+        match self.host() {
+            Some(host) => (host.realloc)(ptr, layout, new_size),
+            None => System.realloc(ptr, layout, new_size),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{HostAllocator, ModuleAllocator};
+    use std::alloc::{GlobalAlloc, Layout};
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    static ALLOCATED: AtomicUsize = AtomicUsize::new(0);
+
+    unsafe fn counted_alloc(layout: Layout) -> *mut u8 {
+        ALLOCATED.fetch_add(layout.size(), Ordering::Relaxed);
+        std::alloc::alloc(layout)
+    }
+
+    static COUNTED: HostAllocator = HostAllocator {
+        alloc: counted_alloc,
+        dealloc: std::alloc::dealloc,
+        alloc_zeroed: std::alloc::alloc_zeroed,
+        realloc: std::alloc::realloc,
+    };
+
+    #[test]
+    fn modules_allocate_through_the_installed_allocator() {
+        let allocator = ModuleAllocator::new();
+        let layout = Layout::new::<[u64; 4]>();
+
+        unsafe {
+            let ptr = allocator.alloc(layout);
+            allocator.dealloc(ptr, layout);
+            assert_eq!(ALLOCATED.load(Ordering::Relaxed), 0);
+
+            allocator.install(&COUNTED);
+
+            let ptr = allocator.alloc(layout);
+            assert_eq!(ALLOCATED.load(Ordering::Relaxed), 32);
+            allocator.dealloc(ptr, layout);
+        }
+    }
+}
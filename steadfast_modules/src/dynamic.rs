@@ -0,0 +1,258 @@
+use crate::{
+    AbiDescriptor, Error, Exports, HostAllocator, Module, ModuleAPI, ModuleAllocator, ModuleStatus,
+    NotifyTrigger, ReloadTrigger, SharedLogger,
+};
+use libloading::Library;
+use std::fmt::Debug;
+use std::mem::ManuallyDrop;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+#[cfg(windows)]
+type Symbol<T> = libloading::os::windows::Symbol<T>;
+#[cfg(not(windows))]
+type Symbol<T> = libloading::os::unix::Symbol<T>;
+
+/// The directory, relative to the system temp directory, that shadow
+/// copies of loaded libraries are placed in.
+const SHADOW_DIR: &str = "steadfast_modules";
+
+/// Incremented for every shadow copy so that each load within a
+/// process receives a unique path.
+static SHADOW_COUNTER: AtomicUsize = AtomicUsize::new(0);
+
+#[derive(Debug)]
+pub struct Symbols<VTable: Debug> {
+    pub lib: ManuallyDrop<Library>,
+    pub api: Symbol<*mut ModuleAPI<VTable>>,
+
+    /// The path from which the library was loaded. This is always a
+    /// shadow copy of the original, which is removed once the library
+    /// is closed.
+    loaded_path: PathBuf,
+
+    /// The exports returned by the module's last `reload`.
+    exports: Option<VTable>,
+}
+
+impl<VTable: Exports> Module<VTable> {
+    /// Creates a new library that can be reloaded at runtime
+    ///
+    /// [`path`] is the library name without any platform specific
+    /// prefix or extension (e.g. `target/debug/libgame`), which must
+    /// resolve to a dynamic library containing the `__MODULE` and
+    /// `__MODULE_ABI` symbols, created using the [`init_module!`] macro.
+    ///
+    /// The library is watched for changes with a [`NotifyTrigger`].
+    pub fn new(path: &Path) -> Result<Self, Error> {
+        Self::with_trigger(path, NotifyTrigger::default())
+    }
+
+    /// Creates a new library that is reloaded when [`trigger`] reports
+    /// a change, see [`new`].
+    pub fn with_trigger(path: &Path, trigger: impl ReloadTrigger + 'static) -> Result<Self, Error> {
+        Self::with_boxed_trigger(path, Box::new(trigger))
+    }
+
+    /// As [`with_trigger`], for a trigger which is already boxed.
+    pub fn with_boxed_trigger(
+        path: &Path,
+        mut trigger: Box<dyn ReloadTrigger>,
+    ) -> Result<Self, Error> {
+        let path = Self::library_path(path);
+
+        Self::remove_stale_copies(&path);
+
+        let symbols = Self::load(&path)?;
+        let api = symbols.api();
+
+        Self::check_layout(&api.layout)?;
+
+        trigger.watch(&path)?;
+
+        let mut module = Module {
+            path: path.into_boxed_path(),
+            state: Self::state_buffer(api.layout.size),
+            layout: api.layout,
+            version: api.version,
+            generation: 0,
+            symbols: None,
+            trigger,
+            logger: Some(SharedLogger::current()),
+            reload_requested: false,
+            status: ModuleStatus::Running,
+        };
+
+        // The library has its' own logging globals, which would otherwise
+        // drop anything it logs
+        if let Some(logger) = &module.logger {
+            (api.share_logger)(logger)?;
+        }
+
+        (api.init)(Self::get_state(&mut module.state))?;
+
+        Ok(module)
+    }
+
+    /// Whether the library changed since the last reload.
+    pub(crate) fn changed(&mut self) -> bool {
+        self.trigger.changed()
+    }
+
+    /// Loads the library to reload the module from.
+    pub(crate) fn load_symbols(&self) -> Result<Symbols<VTable>, Error> {
+        let symbols = Self::load(&self.path)?;
+        Self::check_layout(&symbols.api().layout)?;
+
+        Ok(symbols)
+    }
+
+    /// Loads the library from a shadow copy of [`path`].
+    ///
+    /// The library can't be loaded directly. Windows locks the file
+    /// while it is loaded, so the build would be unable to overwrite
+    /// it, and `dlopen` returns the cached handle when asked to open a
+    /// path that is already loaded, so we would never see the new code.
+    fn load(path: &Path) -> Result<Symbols<VTable>, Error> {
+        let shadow_path = Self::shadow_path(path);
+
+        std::fs::create_dir_all(shadow_path.parent().unwrap())?;
+        std::fs::copy(path, &shadow_path)?;
+
+        Symbols::new(&shadow_path).inspect_err(|_| {
+            let _ = std::fs::remove_file(&shadow_path);
+        })
+    }
+
+    /// Resolves a library name to the file the compiler outputs on the
+    /// current platform, e.g. `libgame` becomes `liblibgame.so` on linux
+    /// and `libgame.dll` on windows.
+    fn library_path(path: &Path) -> PathBuf {
+        match path.file_name() {
+            Some(name) => path.with_file_name(libloading::library_filename(name)),
+            None => path.to_path_buf(),
+        }
+    }
+
+    /// Returns a path, unique to this process and load, in the system
+    /// temp directory for a copy of [`path`].
+    fn shadow_path(path: &Path) -> PathBuf {
+        let stem = path.file_stem().unwrap_or_default().to_string_lossy();
+
+        std::env::temp_dir().join(SHADOW_DIR).join(format!(
+            "{}-{}-{}.{}",
+            stem,
+            std::process::id(),
+            SHADOW_COUNTER.fetch_add(1, Ordering::Relaxed),
+            std::env::consts::DLL_EXTENSION,
+        ))
+    }
+
+    /// Removes shadow copies of [`path`] that were left behind by
+    /// previous processes which did not shut down cleanly.
+    fn remove_stale_copies(path: &Path) {
+        let stem = path.file_stem().unwrap_or_default().to_string_lossy();
+        let prefix = format!("{}-", stem);
+        let current = format!("{}{}-", prefix, std::process::id());
+
+        let entries = match std::fs::read_dir(std::env::temp_dir().join(SHADOW_DIR)) {
+            Ok(entries) => entries,
+            Err(_) => return,
+        };
+
+        for entry in entries.flatten() {
+            let name = entry.file_name();
+            let name = name.to_string_lossy();
+
+            // Copies still loaded by another process can't be removed
+            // on windows, which is fine.
+            if name.starts_with(&prefix) && !name.starts_with(&current) {
+                let _ = std::fs::remove_file(entry.path());
+            }
+        }
+    }
+}
+
+impl<VTable: Debug> Symbols<VTable> {
+    pub fn api(&self) -> &ModuleAPI<VTable> {
+        unsafe { &**self.api }
+    }
+}
+
+impl<VTable: Exports> Symbols<VTable> {
+    /// Opens the library at [`path`], refusing it if its' ABI descriptor
+    /// does not match the host's or it is missing any required exports.
+    fn new(path: &Path) -> Result<Self, Error> {
+        unsafe {
+            let library = Library::new(path)?;
+
+            let expected = AbiDescriptor::new::<VTable>();
+            let found = **library.get::<*const AbiDescriptor>(b"__MODULE_ABI")?;
+
+            if found != expected {
+                return Err(Error::AbiMismatch {
+                    expected: Box::new(expected),
+                    found: Box::new(found),
+                });
+            }
+
+            // Before anything in the library runs, so all of its' memory
+            // comes from the host's allocator
+            let allocator = *library.get::<*const ModuleAllocator>(b"__MODULE_ALLOCATOR")?;
+            (*allocator).install(HostAllocator::current());
+
+            let api = library
+                .get::<*mut ModuleAPI<VTable>>(b"__MODULE")?
+                .into_raw();
+
+            let symbols = Symbols {
+                lib: ManuallyDrop::new(library),
+                api,
+                loaded_path: path.to_path_buf(),
+                exports: None,
+            };
+
+            VTable::new(&symbols)?;
+
+            Ok(symbols)
+        }
+    }
+
+    /// Looks up the symbol [`name`] in the library.
+    ///
+    /// # Safety
+    ///
+    /// [`T`] must be the type of the symbol.
+    pub unsafe fn get<T: Copy>(&self, name: &str) -> Option<T> {
+        self.lib
+            .get::<T>(name.as_bytes())
+            .map(|symbol| *symbol)
+            .ok()
+    }
+
+    /// The exports returned by the module's last `reload`, or those looked
+    /// up by name, see [`Exports::new`], before it has been reloaded.
+    pub fn exports(&self) -> Result<VTable, Error> {
+        match &self.exports {
+            Some(exports) => Ok(exports.clone()),
+            None => VTable::new(self),
+        }
+    }
+
+    pub(crate) fn set_exports(&mut self, exports: VTable) {
+        self.exports = Some(exports);
+    }
+
+    /// The shadow copy the library was loaded from.
+    pub fn loaded_path(&self) -> &Path {
+        &self.loaded_path
+    }
+}
+
+impl<VTable: Debug> Drop for Symbols<VTable> {
+    fn drop(&mut self) {
+        unsafe { ManuallyDrop::drop(&mut self.lib) };
+
+        let _ = std::fs::remove_file(&self.loaded_path);
+    }
+}
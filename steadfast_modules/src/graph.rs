@@ -0,0 +1,124 @@
+use crate::{Error, Manifest};
+
+/// The dependencies between the modules of a [`Manifest`].
+///
+/// Modules are stored in topological order, so every module comes after
+/// the modules it depends on. Cycles are rejected when the graph is built.
+#[derive(Debug)]
+pub struct ModuleGraph {
+    nodes: Vec<Node>,
+}
+
+#[derive(Debug)]
+struct Node {
+    name: String,
+
+    /// The indices of the modules this module directly depends on.
+    dependencies: Vec<usize>,
+}
+
+impl ModuleGraph {
+    pub fn new(manifest: &Manifest) -> Result<Self, Error> {
+        let order = manifest.load_order()?;
+        let index = |name: &str| order.iter().position(|module| module.name == name).unwrap();
+
+        let nodes = order
+            .iter()
+            .map(|module| Node {
+                name: module.name.clone(),
+                dependencies: module.dependencies.iter().map(|name| index(name)).collect(),
+            })
+            .collect();
+
+        Ok(Self { nodes })
+    }
+
+    pub fn len(&self) -> usize {
+        self.nodes.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.nodes.is_empty()
+    }
+
+    pub fn name(&self, module: usize) -> &str {
+        &self.nodes[module].name
+    }
+
+    /// Returns the index of the module with the given [`name`].
+    pub fn find(&self, name: &str) -> Option<usize> {
+        self.nodes.iter().position(|node| node.name == name)
+    }
+
+    /// Returns whether [`module`] directly depends on [`dependency`].
+    pub fn depends_on(&self, module: usize, dependency: usize) -> bool {
+        self.nodes[module].dependencies.contains(&dependency)
+    }
+
+    /// Returns whether [`module`] depends on [`dependency`], directly or
+    /// through the modules it depends on.
+    pub fn depends_on_transitively(&self, module: usize, dependency: usize) -> bool {
+        self.nodes[module]
+            .dependencies
+            .iter()
+            .any(|&direct| direct == dependency || self.depends_on_transitively(direct, dependency))
+    }
+
+    /// Returns the modules which directly depend on [`module`], in
+    /// topological order.
+    pub fn dependents(&self, module: usize) -> impl Iterator<Item = usize> + '_ {
+        (module + 1..self.nodes.len()).filter(move |&other| self.depends_on(other, module))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{Manifest, ModuleGraph};
+
+    #[test]
+    fn dependents_are_direct_and_ordered() {
+        let manifest: Manifest = r#"
+            [[module]]
+            name = "libtools"
+            path = {}
+            dependencies = ["libengine"]
+
+            [[module]]
+            name = "libengine"
+            path = {}
+            dependencies = ["libgame", "libaudio"]
+
+            [[module]]
+            name = "libaudio"
+            path = {}
+            dependencies = ["libgame"]
+
+            [[module]]
+            name = "libgame"
+            path = {}
+            "#
+        .parse()
+        .unwrap();
+
+        let graph = ModuleGraph::new(&manifest).unwrap();
+        let dependents = |name| -> Vec<&str> {
+            graph
+                .dependents(graph.find(name).unwrap())
+                .map(|module| graph.name(module))
+                .collect()
+        };
+
+        assert_eq!(dependents("libgame"), ["libaudio", "libengine"]);
+        assert_eq!(dependents("libaudio"), ["libengine"]);
+        assert_eq!(dependents("libengine"), ["libtools"]);
+        assert!(dependents("libtools").is_empty());
+
+        let (tools, game) = (
+            graph.find("libtools").unwrap(),
+            graph.find("libgame").unwrap(),
+        );
+        assert!(!graph.depends_on(tools, game));
+        assert!(graph.depends_on_transitively(tools, game));
+        assert!(!graph.depends_on_transitively(game, tools));
+    }
+}
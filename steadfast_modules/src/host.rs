@@ -1,17 +1,50 @@
-use crate::engine::EngineExports;
-use crate::game::GameExports;
+use crate::Exports;
+use std::any::{Any, TypeId};
+use std::collections::HashMap;
 
-#[derive(Debug)]
+/// Shared with every module, holding the exports of each loaded module
+/// and the resources the runtime provides to them.
+#[derive(Debug, Default)]
 pub struct Host {
-    pub libgame: Option<GameExports>,
-    pub libengine: Option<EngineExports>,
+    exports: HashMap<TypeId, Box<dyn Any>>,
+    resources: HashMap<TypeId, Box<dyn Any>>,
 }
 
-impl Default for Host {
-    fn default() -> Self {
-        Self {
-            libgame: None,
-            libengine: None,
-        }
+impl Host {
+    /// Returns the exports of the loaded module which exports [`T`].
+    pub fn get<T: Exports>(&self) -> Option<&T> {
+        self.exports
+            .get(&TypeId::of::<T>())
+            .and_then(|exports| exports.downcast_ref())
+    }
+
+    /// Publishes a module's exports, replacing those of its' previous load.
+    pub fn insert<T: Exports>(&mut self, exports: T) {
+        self.exports.insert(TypeId::of::<T>(), Box::new(exports));
+    }
+
+    /// Returns the resource of type [`T`], e.g. the runtime's frame arena.
+    pub fn resource<T: Any>(&self) -> Option<&T> {
+        self.resources
+            .get(&TypeId::of::<T>())
+            .and_then(|resource| resource.downcast_ref())
+    }
+
+    pub fn resource_mut<T: Any>(&mut self) -> Option<&mut T> {
+        self.resources
+            .get_mut(&TypeId::of::<T>())
+            .and_then(|resource| resource.downcast_mut())
+    }
+
+    /// Provides a resource to every module, replacing any of the same type.
+    pub fn insert_resource<T: Any>(&mut self, resource: T) {
+        self.resources.insert(TypeId::of::<T>(), Box::new(resource));
+    }
+
+    pub fn remove_resource<T: Any>(&mut self) -> Option<T> {
+        self.resources
+            .remove(&TypeId::of::<T>())
+            .and_then(|resource| resource.downcast().ok())
+            .map(|resource| *resource)
     }
 }
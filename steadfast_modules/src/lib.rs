@@ -1,22 +1,46 @@
+#[cfg(not(any(feature = "hot-reload", feature = "shipping")))]
+compile_error!("Either the `hot-reload` or `shipping` feature must be enabled");
+
+mod abi;
+#[cfg(not(feature = "shipping"))]
+mod allocator;
+#[cfg(not(feature = "shipping"))]
+mod dynamic;
+mod graph;
 mod host;
+#[cfg(feature = "shipping")]
+mod linked;
+mod logger;
+mod manifest;
 mod modules;
-
+mod panic;
+mod registry;
+mod state;
+#[cfg(not(feature = "shipping"))]
+mod trigger;
+
+pub use crate::abi::*;
+#[cfg(not(feature = "shipping"))]
+pub use crate::allocator::*;
+#[cfg(not(feature = "shipping"))]
+pub use crate::dynamic::*;
+pub use crate::graph::*;
 pub use crate::host::*;
+#[cfg(feature = "shipping")]
+pub use crate::linked::*;
+pub use crate::logger::*;
+pub use crate::manifest::*;
 pub use crate::modules::*;
+pub use crate::panic::*;
+pub use crate::registry::*;
+pub use crate::state::*;
+#[cfg(not(feature = "shipping"))]
+pub use crate::trigger::*;
 
-use libloading::Library;
-use notify::{watcher, RecommendedWatcher, Watcher};
 use std::fmt::Debug;
-use std::path::Path;
-use std::sync::mpsc::{channel, Receiver};
-use std::time::Duration;
+use std::path::{Path, PathBuf};
 use thiserror::Error;
 
-#[cfg(windows)]
-type Symbol<T> = libloading::os::windows::Symbol<T>;
-#[cfg(not(windows))]
-type Symbol<T> = libloading::unix::windows::Symbol<T>;
-
 /// An opaque pointer to a module's state.
 ///
 /// Model state is allocated on the [`Host`] so its'
@@ -25,175 +49,357 @@ pub struct State {
     _private: [u8; 0],
 }
 
-pub struct Module<VTable: Debug> {
+pub struct Module<VTable: Debug + 'static> {
+    /// The library output we watch for changes, e.g. `liblibgame.so`
+    /// or `libgame.dll`. Linked modules use their name instead.
     path: Box<Path>,
     pub symbols: Option<Symbols<VTable>>,
     pub state: Vec<u64>,
-    watcher: RecommendedWatcher,
-    rx: Receiver<notify::DebouncedEvent>,
+    /// The layout and version of the module that initialized [`state`].
+    layout: StateLayout,
+    version: u32,
+    /// The number of times the library has been successfully loaded.
+    generation: u32,
+    #[cfg(not(feature = "shipping"))]
+    trigger: Box<dyn ReloadTrigger>,
+    /// The module, when it is linked into the executable.
+    #[cfg(feature = "shipping")]
+    linked: &'static ModuleAPI<VTable>,
+    /// Installed in every library loaded, see [`share_logger`].
+    logger: Option<SharedLogger>,
+    /// Set by [`request_reload`] to reload on the next [`reload`].
+    reload_requested: bool,
+    status: ModuleStatus,
 }
 
-pub struct ModuleAPI<VTable: Debug> {
-    pub size: fn() -> usize,
-    pub init: fn(*mut ()),
-    pub reload: fn(*mut ()) -> VTable,
-    pub update: fn(&mut Host, *mut ()),
-    pub unload: fn(*mut ()),
-    pub deinit: fn(*mut ()),
+/// Whether a module's callbacks are being called.
+#[derive(Clone, Debug)]
+pub enum ModuleStatus {
+    Running,
+
+    /// A callback panicked. The module's `update` is skipped until a
+    /// rebuilt library is reloaded successfully.
+    Faulted {
+        callback: &'static str,
+        panic: Panic,
+    },
 }
 
-#[derive(Debug)]
-pub struct Symbols<VTable: Debug> {
-    pub lib: Library,
-    pub api: Symbol<*mut ModuleAPI<VTable>>,
-    // /// The path from which the library was loaded. This _will_ be a
-    // /// temporary file in windows.
-    // loaded_path: PathBuf,
-
-    // /// The original location of the file. This is the file we watch
-    // /// to know when to reload.
-    // original_path: PathBuf,
+/// Sent to the dependents of a module when it is reloaded.
+#[derive(Clone, Debug)]
+pub struct ReloadEvent {
+    /// The name of the reloaded module, as listed in the manifest.
+    pub module: String,
+
+    /// The number of times the module has been loaded, starting at 1.
+    pub generation: u32,
 }
 
-impl<VTable: Debug> Module<VTable> {
-    /// Creates a new library that can be reloaded at runtime
-    ///
-    /// [`path`] must be a dynamic library containing a `__MODULE`
-    /// symbol, created using the [`init_module!`] macro.
-    ///
-    pub fn new(path: &'static Path) -> Result<Self, Error> {
-        let symbols = Self::load(path)?;
-        let size = (unsafe { &**symbols.api }.size)();
-
-        let (tx, rx) = channel();
-        let mut watcher = watcher(tx, Duration::from_secs(1))?;
-        watcher.watch(path.parent().unwrap(), notify::RecursiveMode::NonRecursive)?;
-
-        let mut module = Module {
-            path: path.with_extension("dll").into_boxed_path(),
-            state: vec![],
-            symbols: None,
-            watcher,
-            rx,
-        };
+/// Builds a module's state from the bytes and version of the state left
+/// by the previous library.
+pub type MigrateFn = fn(&[u8], u32, *mut ()) -> Result<(), Panic>;
 
-        module.resize_state(size);
+/// The callbacks a module exports to the host.
+///
+/// Every callback catches panics on the module's side of the boundary and
+/// returns them as an error.
+pub struct ModuleAPI<VTable: Debug> {
+    pub layout: StateLayout,
+    pub version: u32,
+    /// Installs the host's logger in the module, see [`SharedLogger`].
+    pub share_logger: fn(&SharedLogger) -> Result<(), Panic>,
+    pub init: fn(*mut ()) -> Result<(), Panic>,
+    pub migrate: Option<MigrateFn>,
+    pub reload: fn(*mut ()) -> Result<VTable, Panic>,
+    pub update: fn(&mut Host, &ReloadEvent, *mut ()) -> Result<(), Panic>,
+    pub unload: fn(*mut ()) -> Result<(), Panic>,
+    pub deinit: fn(*mut ()) -> Result<(), Panic>,
+}
 
-        (unsafe { &**symbols.api }.init)(Self::get_state(&mut module.state));
+impl<VTable: Exports> Module<VTable> {
+    /// Reloads the library if its' trigger reported a change, a reload
+    /// was requested, or it has not been loaded yet.
+    pub fn reload(&mut self) -> Result<Option<&Symbols<VTable>>, ReloadError> {
+        let changed = self.changed();
+        let requested = std::mem::take(&mut self.reload_requested);
 
-        Ok(module)
+        if changed || requested || self.symbols.is_none() {
+            Ok(self.do_reload()?)
+        } else {
+            Ok(None)
+        }
     }
 
-    pub fn reload(&mut self) -> Result<Option<&Symbols<VTable>>, Error> {
-        let mut reload = false;
+    /// Reloads the library, keeping the previous one if this fails.
+    ///
+    /// The new library is loaded and validated before the previous one is
+    /// touched. If any of the callbacks involved in handing the state over
+    /// panics, the state is restored to how the previous library's
+    /// `unload` left it, and the previous library is kept and given the
+    /// state back with its' `reload`.
+    pub fn do_reload(&mut self) -> Result<Option<&Symbols<VTable>>, ReloadError> {
+        let mut symbols = self
+            .load_symbols()
+            .map_err(|error| self.reload_error(ReloadCause::Load(error)))?;
+
+        // Before any other callback, so the module can log from them
+        if let Some(logger) = &self.logger {
+            (symbols.api().share_logger)(logger)
+                .map_err(|panic| self.reload_error(ReloadCause::panicked("share_logger", panic)))?;
+        }
 
-        while let Ok(event) = self.rx.try_recv() {
-            use notify::DebouncedEvent::*;
+        let old_symbols = self.symbols.take();
+        let old_api = old_symbols.as_ref().map(Symbols::api);
 
-            match event {
-                NoticeWrite(ref path) | Write(ref path) | Create(ref path) => {
-                    if path.canonicalize()? == *self.path.canonicalize()? {
-                        reload = true;
-                    }
-                }
-                _ => (),
+        if let Some(old_api) = old_api {
+            if let Err(panic) = (old_api.unload)(Self::get_state(&mut self.state)) {
+                self.symbols = old_symbols;
+                self.reload_previous();
+
+                return Err(self.reload_error(ReloadCause::panicked("unload", panic)));
             }
         }
 
-        if reload || self.symbols.is_none() {
-            Ok(self.do_reload()?)
-        } else {
-            Ok(None)
+        // Taken after `unload`, so restoring it never brings back anything
+        // `unload` freed
+        let snapshot = self.state.clone();
+
+        match self.swap_state(old_api, symbols.api()) {
+            Ok(exports) => {
+                symbols.set_exports(exports);
+                self.symbols = Some(symbols);
+                self.generation += 1;
+                self.status = ModuleStatus::Running;
+
+                Ok(self.symbols.as_ref())
+            }
+            Err(cause) => {
+                // `clone_from` reuses the buffer, so the module's state
+                // stays at the same address.
+                self.state.clone_from(&snapshot);
+                self.symbols = old_symbols;
+                self.reload_previous();
+
+                Err(self.reload_error(cause))
+            }
         }
     }
 
-    pub fn do_reload(&mut self) -> Result<Option<&Symbols<VTable>>, Error> {
-        if let Some(Symbols { ref mut api, .. }) = self.symbols {
-            (unsafe { &***api }.unload)(Self::get_state(&mut self.state));
-        }
+    /// Reloads the library on the next call to [`reload`], whether or not
+    /// its' trigger reported a change.
+    pub fn request_reload(&mut self) {
+        self.reload_requested = true;
+    }
 
-        self.symbols = None;
+    /// Has the module log through [`logger`], now and whenever it is
+    /// reloaded.
+    pub fn share_logger(&mut self, logger: SharedLogger) {
+        if let Some(symbols) = &self.symbols {
+            if let Err(panic) = (symbols.api().share_logger)(&logger) {
+                log::error!(
+                    "`share_logger` of {} panicked: {}",
+                    self.path.display(),
+                    panic
+                );
+            }
+        }
 
-        let symbols = Self::load(&self.path)?;
+        self.logger = Some(logger);
+    }
 
-        self.resize_state((unsafe { &**symbols.api }.size)());
+    /// Notifies the module that a module it depends on was reloaded.
+    ///
+    /// If `update` panics the module is marked as faulted, and isn't
+    /// updated again until it has been reloaded.
+    pub fn update(&mut self, host: &mut Host, event: &ReloadEvent) -> Result<(), Panic> {
+        let symbols = match &self.symbols {
+            Some(symbols) if !self.is_faulted() => symbols,
+            _ => return Ok(()),
+        };
 
-        // TODO: Load module vtable
-        (unsafe { &**symbols.api }.reload)(Self::get_state(&mut self.state));
-        self.symbols = Some(symbols);
+        let result = (symbols.api().update)(host, event, Self::get_state(&mut self.state));
 
-        if let Some(symbols) = &self.symbols {
-            Ok(Some(symbols))
-        } else {
-            Ok(None)
+        if let Err(panic) = &result {
+            self.status = ModuleStatus::Faulted {
+                callback: "update",
+                panic: panic.clone(),
+            };
         }
+
+        result
     }
 
-    pub fn update(&mut self, host: &mut Host) -> () {
-        if let Some(Symbols { ref mut api, .. }) = self.symbols {
-            (unsafe { &***api }.update)(host, Self::get_state(&mut self.state));
-        }
+    pub fn status(&self) -> &ModuleStatus {
+        &self.status
     }
 
-    fn resize_state(&mut self, size: usize) {
-        self.state.resize((size + 7) / 8, 0);
+    pub fn is_faulted(&self) -> bool {
+        matches!(self.status, ModuleStatus::Faulted { .. })
     }
 
-    pub fn get_state(buffer: &mut Vec<u64>) -> *mut () {
-        buffer.as_mut_ptr() as *mut ()
+    /// The number of times the library has been successfully loaded,
+    /// starting at 1 for the first load.
+    pub fn generation(&self) -> u32 {
+        self.generation
     }
 
-    #[cfg(windows)]
-    fn load(path: &Path) -> Result<Symbols<VTable>, Error> {
-        let path_extension = path.with_extension("dll");
-        let path_copied = path_extension.with_extension("copy.dll");
+    /// Hands the state, once the previous library has unloaded it, over
+    /// to [`api`].
+    ///
+    /// If the state layout or version changed, the new module's `migrate`
+    /// hook is given the old state's bytes and version. If it has none,
+    /// the new module starts from `init` and the old state is torn down
+    /// with `deinit` once the new module has taken over.
+    ///
+    /// The state is only replaced once every callback has succeeded, but
+    /// the new `reload` may have modified it, so the caller must restore
+    /// it on error. Returns the exports from the new module's `reload`.
+    fn swap_state(
+        &mut self,
+        old_api: Option<&ModuleAPI<VTable>>,
+        api: &ModuleAPI<VTable>,
+    ) -> Result<VTable, ReloadCause> {
+        if api.layout == self.layout && api.version == self.version {
+            return (api.reload)(Self::get_state(&mut self.state))
+                .map_err(|panic| ReloadCause::panicked("reload", panic));
+        }
+
+        let mut state = Self::state_buffer(api.layout.size);
 
-        std::fs::copy(&path_extension, &path_copied)?;
+        match api.migrate {
+            Some(migrate) => {
+                let old_bytes = unsafe {
+                    std::slice::from_raw_parts(self.state.as_ptr() as *const u8, self.layout.size)
+                };
 
-        Symbols::new(&path_copied)
+                migrate(old_bytes, self.version, Self::get_state(&mut state))
+                    .map_err(|panic| ReloadCause::panicked("migrate", panic))?;
+            }
+            None => {
+                log::warn!(
+                    "State layout of {} changed (version {} -> {}) and it has no migrate hook, \
+                     reinitializing its' state",
+                    self.path.display(),
+                    self.version,
+                    api.version,
+                );
+
+                (api.init)(Self::get_state(&mut state))
+                    .map_err(|panic| ReloadCause::panicked("init", panic))?;
+            }
+        }
+
+        let exports = (api.reload)(Self::get_state(&mut state))
+            .map_err(|panic| ReloadCause::panicked("reload", panic))?;
+
+        let mut old_state = std::mem::replace(&mut self.state, state);
+
+        self.layout = api.layout;
+        self.version = api.version;
+
+        if let (Some(old_api), None) = (old_api, api.migrate) {
+            if let Err(panic) = (old_api.deinit)(Self::get_state(&mut old_state)) {
+                log::error!(
+                    "`deinit` of the previous {} panicked: {}",
+                    self.path.display(),
+                    panic
+                );
+            }
+        }
+
+        Ok(exports)
     }
 
-    #[cfg(not(windows))]
-    fn load(path: &'static Path) {
-        Symbols::new(path)
+    /// Gives the state back to the library kept after a failed reload, as
+    /// its' `unload` has already run. The module is only left faulted if
+    /// this panics too.
+    fn reload_previous(&mut self) {
+        let symbols = match &mut self.symbols {
+            Some(symbols) => symbols,
+            None => return,
+        };
+
+        match (symbols.api().reload)(Self::get_state(&mut self.state)) {
+            // The previous library is running as it was, so its' `update`
+            // is called again
+            Ok(exports) => {
+                symbols.set_exports(exports);
+                self.status = ModuleStatus::Running;
+            }
+            Err(panic) => {
+                log::error!(
+                    "`reload` of the previous {} panicked: {}",
+                    self.path.display(),
+                    panic
+                );
+
+                self.status = ModuleStatus::Faulted {
+                    callback: "reload",
+                    panic,
+                };
+            }
+        }
     }
-}
 
-impl<VTable: Debug> Symbols<VTable>
-where
-    VTable: Debug,
-{
-    fn new(path: &Path) -> Result<Self, Error> {
-        unsafe {
-            let library = Library::new(path)?;
-            let api = library
-                .get::<*mut ModuleAPI<VTable>>(b"__MODULE")?
-                .into_raw();
-
-            Ok(Symbols { lib: library, api })
+    fn reload_error(&self, cause: ReloadCause) -> ReloadError {
+        ReloadError {
+            path: self.path.to_path_buf(),
+            cause,
         }
     }
+
+    /// The state buffer is a `Vec<u64>`, so can't satisfy alignments
+    /// larger than that of `u64`.
+    fn check_layout(layout: &StateLayout) -> Result<(), Error> {
+        if layout.align > std::mem::align_of::<u64>() {
+            Err(Error::StateAlignment(layout.align))
+        } else {
+            Ok(())
+        }
+    }
+
+    /// Allocates a zeroed buffer large enough for a state of [`size`] bytes.
+    fn state_buffer(size: usize) -> Vec<u64> {
+        vec![0; size.div_ceil(8)]
+    }
+}
+
+impl<VTable: Debug + 'static> Module<VTable> {
+    pub fn get_state(buffer: &mut Vec<u64>) -> *mut () {
+        buffer.as_mut_ptr() as *mut ()
+    }
 }
 
-impl<VTable: Debug> Drop for Module<VTable> {
+impl<VTable: Debug + 'static> Drop for Module<VTable> {
     fn drop(&mut self) {
-        if let Some(Symbols { ref mut api, .. }) = self.symbols {
-            unsafe {
-                ((***api).deinit)(Self::get_state(&mut self.state));
+        if let Some(symbols) = &self.symbols {
+            if let Err(panic) = (symbols.api().deinit)(Self::get_state(&mut self.state)) {
+                log::error!("`deinit` of {} panicked: {}", self.path.display(), panic);
             }
         }
     }
 }
 
+///
+/// `state` must be declared with [`module_state!`], which records its'
+/// layout so the host can detect when it changes across a reload. The
+/// optional `version` (0 by default) should be bumped when the meaning of
+/// the state changes without its' layout changing.
 ///
 /// # Lifecycle
 ///
 ///   - `init` gets called on the initial application load. This is where
 ///     module state should be initialized.
+///   - `migrate` (optional) gets called on library load when the state
+///     layout or `version` differs from the module that created the state.
+///     It receives the old state's bytes and version, and returns the new
+///     state. Without it, the old state is deinitialized and `init` is
+///     called instead.
 ///   - `reload` gets called on library load, including the first. Module
 ///     specific reload functionality may reside here, but this function
 ///     MUST always return a vtable of its' exports
-///   - `update` is called when another library is reloaded
+///   - `update` is called when a library this module depends on is
+///     reloaded, with a [`ReloadEvent`] naming it
 ///   - `unload` gets called on library unload
 ///   - `deinit` gets called when the application is shutting down
 ///
@@ -201,8 +407,10 @@ impl<VTable: Debug> Drop for Module<VTable> {
 macro_rules! init_module {
     (
         state: $state:ty,
+        $(version: $version:expr,)?
         exports: $exports:ty,
         init: $init:ident,
+        $(migrate: $migrate:ident,)?
         reload: $reload:ident,
         update: $update:ident,
         unload: $unload:ident,
@@ -212,83 +420,118 @@ macro_rules! init_module {
             unsafe { &mut *(opaque_state as *mut $state) }
         }
 
-        fn __init_module(opaque_state: *mut ()) {
-            $init(cast(opaque_state))
+        fn __share_logger(logger: &$crate::SharedLogger) -> Result<(), $crate::Panic> {
+            $crate::catch_panic(|| logger.install())
         }
 
-        fn __reload_module(opaque_state: *mut ()) -> $exports {
-            $reload(cast(opaque_state))
+        fn __init_module(opaque_state: *mut ()) -> Result<(), $crate::Panic> {
+            $crate::catch_panic(|| $init(cast(opaque_state)))
         }
 
-        fn __update_module(host: &mut Host, opaque_state: *mut ()) {
-            $update(host, cast(opaque_state))
+        fn __reload_module(opaque_state: *mut ()) -> Result<$exports, $crate::Panic> {
+            $crate::catch_panic(|| $reload(cast(opaque_state)))
         }
 
-        fn __unload_module(opaque_state: *mut ()) {
-            $unload(cast(opaque_state))
+        fn __update_module(
+            host: &mut $crate::Host,
+            event: &$crate::ReloadEvent,
+            opaque_state: *mut (),
+        ) -> Result<(), $crate::Panic> {
+            $crate::catch_panic(|| $update(host, event, cast(opaque_state)))
         }
 
-        fn __deinit_module(opaque_state: *mut ()) {
-            $deinit(cast(opaque_state))
+        fn __unload_module(opaque_state: *mut ()) -> Result<(), $crate::Panic> {
+            $crate::catch_panic(|| $unload(cast(opaque_state)))
         }
 
-        #[no_mangle]
-        pub static __MODULE: steadfast_core::module::ModuleAPI<$exports> =
-            steadfast_core::module::ModuleAPI {
-                size: std::mem::size_of::<$state>,
+        fn __deinit_module(opaque_state: *mut ()) -> Result<(), $crate::Panic> {
+            $crate::catch_panic(|| $deinit(cast(opaque_state)))
+        }
+
+        $crate::__module_allocator!();
+
+        $crate::__module_static! {
+            pub static __MODULE_ABI: $crate::AbiDescriptor = $crate::AbiDescriptor::new::<$exports>();
+        }
+
+        $crate::__module_static! {
+            pub static __MODULE: $crate::ModuleAPI<$exports> = $crate::ModuleAPI {
+                layout: <$state as $crate::ModuleState>::LAYOUT,
+                version: $crate::init_module!(@version $($version)?),
+                share_logger: __share_logger,
                 init: __init_module,
+                migrate: $crate::init_module!(@migrate $state $(, $migrate)?),
                 reload: __reload_module,
                 update: __update_module,
                 unload: __unload_module,
                 deinit: __deinit_module,
             };
+        }
     };
+
+    (@version) => { 0 };
+    (@version $version:expr) => { $version };
+
+    (@migrate $state:ty) => { None };
+    (@migrate $state:ty, $migrate:ident) => {{
+        fn __migrate_module(
+            old_state: &[u8],
+            old_version: u32,
+            opaque_state: *mut (),
+        ) -> Result<(), $crate::Panic> {
+            let state = $crate::catch_panic(|| $migrate(old_state, old_version))?;
+
+            unsafe { std::ptr::write(opaque_state as *mut $state, state) };
+
+            Ok(())
+        }
+
+        Some(__migrate_module as $crate::MigrateFn)
+    }};
 }
 
+/// Exports a static from [`init_module!`] under its' own name, so the host
+/// can look it up in the library.
+#[cfg(not(feature = "shipping"))]
+#[doc(hidden)]
 #[macro_export]
-macro_rules! load_modules {
-    ($($libname:ident => $exports:ident,)*) => {
-        use steadfast_core::module::{Host, Module, Symbols};
-        use std::path::Path;
-
-        struct ModuleManager {
-            host: Host,
-            $(
-                $libname: Module<$exports>,
-            )*
-        }
+macro_rules! __module_static {
+    ($item:item) => {
+        #[no_mangle]
+        $item
+    };
+}
 
-        impl ModuleManager {
-            pub fn new() -> Self {
-                Self {
-                    host: Host::default(),
-                    $(
-                        $libname: Module::new(
-                            Path::new(concat!("../target/debug/", stringify!($libname)))
-                        ).expect(concat!("Failed to load library ", stringify!($libname))),
-                    )*
-                }
-            }
+/// Linked modules are referred to by path, and would clash if every
+/// module exported a static with the same name.
+#[cfg(feature = "shipping")]
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __module_static {
+    ($item:item) => {
+        $item
+    };
+}
 
-            pub fn reload(&mut self) -> () {
-                let mut reloaded = false;
-                $(
-                    if let Ok(vtable) = self.$libname.reload() {
-                        if let Some(symbols) = vtable {
-                            reloaded = true;
-                            self.host.$libname = Some($exports::new(symbols));
-                        }
-                    }
-                )*
-
-                if (reloaded) {
-                    $(
-                        self.$libname.update(&mut self.host);
-                    )*
-                }
-            }
-        }
-    }
+/// Has the library allocate through the host's allocator, which the host
+/// installs as `__MODULE_ALLOCATOR` when it loads the library.
+#[cfg(not(feature = "shipping"))]
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __module_allocator {
+    () => {
+        #[global_allocator]
+        #[no_mangle]
+        static __MODULE_ALLOCATOR: $crate::ModuleAllocator = $crate::ModuleAllocator::new();
+    };
+}
+
+/// Linked modules already share the executable's allocator.
+#[cfg(feature = "shipping")]
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __module_allocator {
+    () => {};
 }
 
 //  //
@@ -298,11 +541,76 @@ pub enum Error {
     #[error("An error occurred while trying to load")]
     Io(#[from] std::io::Error),
 
+    #[cfg(not(feature = "shipping"))]
     #[error("An error occurred while creating the filesystem watcher")]
     Watch(#[from] notify::Error),
 
+    #[cfg(not(feature = "shipping"))]
     #[error("An error occurred while attempting to load the library")]
     Library(#[from] libloading::Error),
+
+    #[error("Module state requires an alignment of {0}, which is not supported")]
+    StateAlignment(usize),
+
+    #[error("A module callback panicked: {0}")]
+    Panic(#[from] Panic),
+
+    #[error("Module was built with {found}, but the host expects {expected}")]
+    AbiMismatch {
+        expected: Box<AbiDescriptor>,
+        found: Box<AbiDescriptor>,
+    },
+
+    #[error("Module is missing the exports: {}", .0.join(", "))]
+    MissingExports(Vec<&'static str>),
+
+    #[error("An error occurred while reading the module manifest")]
+    Manifest(#[from] toml::de::Error),
+
+    #[error("Module {0} is listed more than once in the manifest")]
+    DuplicateModule(String),
+
+    #[error("Module {module} has no path for the {profile} profile")]
+    MissingProfile { module: String, profile: String },
+
+    #[error("Module {module} depends on {dependency}, which is not in the manifest")]
+    UnknownDependency { module: String, dependency: String },
+
+    #[error("Modules have a dependency cycle: {}", .0.join(" -> "))]
+    DependencyCycle(Vec<String>),
+
+    #[error("Module {0} has not been registered with its' exports")]
+    UnregisteredModule(String),
+
+    #[error("Module {0} is not loaded")]
+    UnknownModule(String),
+}
+
+/// The reason a module failed to reload. The previously loaded library,
+/// if any, remains loaded with its' state unchanged.
+#[derive(Debug, Error)]
+#[error("Failed to reload {}: {cause}", .path.display())]
+pub struct ReloadError {
+    pub path: PathBuf,
+    pub cause: ReloadCause,
+}
+
+#[derive(Debug, Error)]
+pub enum ReloadCause {
+    #[error("{0}")]
+    Load(Error),
+
+    #[error("`{callback}` panicked: {panic}")]
+    Panicked {
+        callback: &'static str,
+        panic: Panic,
+    },
+}
+
+impl ReloadCause {
+    fn panicked(callback: &'static str, panic: Panic) -> Self {
+        ReloadCause::Panicked { callback, panic }
+    }
 }
 
 #[cfg(test)]
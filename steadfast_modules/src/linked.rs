@@ -0,0 +1,92 @@
+use crate::{Error, Exports, Module, ModuleAPI, ModuleStatus};
+use std::fmt::{self, Debug, Formatter};
+use std::path::Path;
+
+/// A module linked into the executable.
+pub struct Symbols<VTable: Debug + 'static> {
+    pub api: &'static ModuleAPI<VTable>,
+
+    /// The exports returned by the module's `reload`.
+    exports: Option<VTable>,
+}
+
+impl<VTable: Exports> Module<VTable> {
+    /// Creates a module from the `__MODULE` static of a crate linked into
+    /// the executable, declared with [`init_module!`].
+    ///
+    /// [`name`] is only used to report errors. The module is never
+    /// reloaded unless it is asked to with [`request_reload`], which runs
+    /// the same `unload` and `reload` callbacks as a library reload.
+    pub fn linked(name: &str, api: &'static ModuleAPI<VTable>) -> Result<Self, Error> {
+        Self::check_layout(&api.layout)?;
+
+        // Shares the executable's logging globals, so it doesn't need the
+        // host's logger
+        let mut module = Module {
+            path: Path::new(name).into(),
+            state: Self::state_buffer(api.layout.size),
+            layout: api.layout,
+            version: api.version,
+            generation: 0,
+            symbols: None,
+            linked: api,
+            logger: None,
+            reload_requested: false,
+            status: ModuleStatus::Running,
+        };
+
+        (api.init)(Self::get_state(&mut module.state))?;
+
+        Ok(module)
+    }
+
+    /// Linked modules never change.
+    pub(crate) fn changed(&mut self) -> bool {
+        false
+    }
+
+    pub(crate) fn load_symbols(&self) -> Result<Symbols<VTable>, Error> {
+        Ok(Symbols {
+            api: self.linked,
+            exports: None,
+        })
+    }
+}
+
+impl<VTable: Debug> Debug for Symbols<VTable> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Symbols")
+            .field("exports", &self.exports)
+            .finish_non_exhaustive()
+    }
+}
+
+impl<VTable: Debug> Symbols<VTable> {
+    pub fn api(&self) -> &ModuleAPI<VTable> {
+        self.api
+    }
+}
+
+impl<VTable: Exports> Symbols<VTable> {
+    /// Linked modules have no symbol table, so this always returns `None`.
+    /// Their exports are those returned by the module's `reload`, see
+    /// [`exports`].
+    ///
+    /// # Safety
+    ///
+    /// Always safe, but kept `unsafe` to match dynamically loaded modules.
+    pub unsafe fn get<T: Copy>(&self, _name: &str) -> Option<T> {
+        None
+    }
+
+    /// The exports returned by the module's `reload`.
+    pub fn exports(&self) -> Result<VTable, Error> {
+        self.exports
+            .clone()
+            .ok_or_else(|| Error::MissingExports(VTable::members()))
+    }
+
+    pub(crate) fn set_exports(&mut self, exports: VTable) {
+        self.exports = Some(exports);
+    }
+}
@@ -0,0 +1,55 @@
+use std::fmt;
+use tracing_core::Dispatch;
+
+/// The host's `tracing` subscriber and `log` logger, shared with every
+/// module.
+///
+/// A module loaded from a dynamic library has its' own copy of the
+/// `tracing` and `log` globals, which start out without a subscriber or
+/// logger, so anything it logs would be lost. Installing the host's in
+/// the module's copy sends it to the host instead.
+#[derive(Clone)]
+pub struct SharedLogger {
+    pub dispatch: Dispatch,
+    pub logger: &'static dyn log::Log,
+    pub max_level: log::LevelFilter,
+}
+
+impl SharedLogger {
+    /// The subscriber and logger this copy of the globals uses, which is
+    /// the host's when called by the host.
+    pub fn current() -> Self {
+        Self {
+            dispatch: tracing_core::dispatcher::get_default(Dispatch::clone),
+            logger: log::logger(),
+            max_level: log::max_level(),
+        }
+    }
+
+    /// Installs the subscriber and logger in this copy of the globals,
+    /// which is the module's when called by a module.
+    ///
+    /// The subscriber and logger can only be set once per copy, so calling
+    /// this again doesn't replace them. It only picks up changes to what
+    /// they filter: the max level, and which callsites are enabled. A
+    /// library loaded by a reload has its' own copy, so gets them set
+    /// again.
+    pub fn install(&self) {
+        // These can only be set once, and fail when already set, which is
+        // always the case for modules linked into the host
+        let _ = tracing_core::dispatcher::set_global_default(self.dispatch.clone());
+        let _ = log::set_logger(self.logger);
+
+        log::set_max_level(self.max_level);
+        tracing_core::callsite::rebuild_interest_cache();
+    }
+}
+
+impl fmt::Debug for SharedLogger {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("SharedLogger")
+            .field("dispatch", &self.dispatch)
+            .field("max_level", &self.max_level)
+            .finish()
+    }
+}
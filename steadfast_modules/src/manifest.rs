@@ -0,0 +1,230 @@
+use crate::Error;
+use serde::Deserialize;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+
+/// Lists the modules an application is built from.
+///
+/// ```toml
+/// [[module]]
+/// name = "libgame"
+/// path = { debug = "../target/debug/libgame", release = "../target/release/libgame" }
+///
+/// [[module]]
+/// name = "libengine"
+/// path = { debug = "../target/debug/libengine", release = "../target/release/libengine" }
+/// dependencies = ["libgame"]
+/// ```
+///
+/// Paths are relative to the manifest, and name the library without any
+/// platform specific prefix or extension, as [`Module::new`] expects.
+#[derive(Debug, Deserialize)]
+pub struct Manifest {
+    #[serde(rename = "module", default)]
+    pub modules: Vec<ModuleManifest>,
+
+    /// The directory the manifest was read from.
+    #[serde(skip)]
+    root: PathBuf,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ModuleManifest {
+    pub name: String,
+
+    /// The path of the library for each build profile.
+    pub path: HashMap<String, PathBuf>,
+
+    /// The modules whose exports this module uses.
+    #[serde(default)]
+    pub dependencies: Vec<String>,
+}
+
+impl Manifest {
+    pub fn from_file(path: impl AsRef<Path>) -> Result<Self, Error> {
+        let path = path.as_ref();
+        let mut manifest: Self = std::fs::read_to_string(path)?.parse()?;
+
+        manifest.root = path.parent().unwrap_or_else(|| Path::new("")).to_path_buf();
+
+        Ok(manifest)
+    }
+
+    /// The build profile of the running executable, used to pick the
+    /// path each module is loaded from.
+    pub fn profile() -> &'static str {
+        if cfg!(debug_assertions) {
+            "debug"
+        } else {
+            "release"
+        }
+    }
+
+    /// The path of [`module`]'s library for [`profile`], relative to the
+    /// working directory.
+    pub fn path(&self, module: &ModuleManifest, profile: &str) -> Result<PathBuf, Error> {
+        module
+            .path
+            .get(profile)
+            .map(|path| self.root.join(path))
+            .ok_or_else(|| Error::MissingProfile {
+                module: module.name.clone(),
+                profile: profile.to_string(),
+            })
+    }
+
+    /// Orders the modules such that every module comes after its'
+    /// dependencies.
+    pub fn load_order(&self) -> Result<Vec<&ModuleManifest>, Error> {
+        let mut order = Vec::with_capacity(self.modules.len());
+        let mut visiting = vec![];
+
+        for module in &self.modules {
+            if self
+                .modules
+                .iter()
+                .filter(|other| other.name == module.name)
+                .count()
+                > 1
+            {
+                return Err(Error::DuplicateModule(module.name.clone()));
+            }
+
+            self.visit(module, &mut visiting, &mut order)?;
+        }
+
+        Ok(order)
+    }
+
+    fn visit<'a>(
+        &'a self,
+        module: &'a ModuleManifest,
+        visiting: &mut Vec<&'a str>,
+        order: &mut Vec<&'a ModuleManifest>,
+    ) -> Result<(), Error> {
+        if order.iter().any(|ordered| ordered.name == module.name) {
+            return Ok(());
+        }
+
+        if let Some(start) = visiting.iter().position(|name| *name == module.name) {
+            let mut cycle: Vec<String> = visiting[start..]
+                .iter()
+                .map(|name| name.to_string())
+                .collect();
+            cycle.push(module.name.clone());
+
+            return Err(Error::DependencyCycle(cycle));
+        }
+
+        visiting.push(&module.name);
+
+        for dependency in &module.dependencies {
+            let dependency = self
+                .modules
+                .iter()
+                .find(|other| other.name == *dependency)
+                .ok_or_else(|| Error::UnknownDependency {
+                    module: module.name.clone(),
+                    dependency: dependency.clone(),
+                })?;
+
+            self.visit(dependency, visiting, order)?;
+        }
+
+        visiting.pop();
+        order.push(module);
+
+        Ok(())
+    }
+}
+
+impl FromStr for Manifest {
+    type Err = Error;
+
+    fn from_str(manifest: &str) -> Result<Self, Error> {
+        Ok(toml::from_str(manifest)?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{Error, Manifest};
+    use std::str::FromStr;
+
+    fn names(manifest: &Manifest) -> Vec<&str> {
+        manifest
+            .load_order()
+            .unwrap()
+            .iter()
+            .map(|module| module.name.as_str())
+            .collect()
+    }
+
+    #[test]
+    fn dependencies_load_first() {
+        let manifest = Manifest::from_str(
+            r#"
+            [[module]]
+            name = "libengine"
+            path = { debug = "target/debug/libengine" }
+            dependencies = ["libgame", "libaudio"]
+
+            [[module]]
+            name = "libgame"
+            path = { debug = "target/debug/libgame" }
+            dependencies = ["libaudio"]
+
+            [[module]]
+            name = "libaudio"
+            path = { debug = "target/debug/libaudio" }
+            "#,
+        )
+        .unwrap();
+
+        assert_eq!(names(&manifest), ["libaudio", "libgame", "libengine"]);
+    }
+
+    #[test]
+    fn cycles_are_rejected() {
+        let manifest = Manifest::from_str(
+            r#"
+            [[module]]
+            name = "libgame"
+            path = {}
+            dependencies = ["libengine"]
+
+            [[module]]
+            name = "libengine"
+            path = {}
+            dependencies = ["libgame"]
+            "#,
+        )
+        .unwrap();
+
+        match manifest.load_order() {
+            Err(Error::DependencyCycle(cycle)) => {
+                assert_eq!(cycle, ["libgame", "libengine", "libgame"])
+            }
+            other => panic!("Expected a dependency cycle, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn unknown_dependencies_are_rejected() {
+        let manifest = Manifest::from_str(
+            r#"
+            [[module]]
+            name = "libgame"
+            path = {}
+            dependencies = ["libphysics"]
+            "#,
+        )
+        .unwrap();
+
+        assert!(matches!(
+            manifest.load_order(),
+            Err(Error::UnknownDependency { .. })
+        ));
+    }
+}
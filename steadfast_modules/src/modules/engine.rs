@@ -1,7 +1,14 @@
-use crate::exports;
+use crate::{exports, Host};
+use steadfast_defs::engine::{ApplicationConfig, Event};
 
 exports! {
     struct EngineExports {
-
+        engine_config: fn(host: &Host) -> ApplicationConfig,
+        engine_running: fn(host: &Host) -> bool,
+        engine_fixed_update: fn(host: &mut Host, dt: f64),
+        engine_update: fn(host: &mut Host, dt: f64),
+        engine_render: fn(host: &mut Host, alpha: f64),
+        engine_event: fn(host: &mut Host, event: &Event),
+        engine_shutdown: fn(host: &mut Host),
     }
 }
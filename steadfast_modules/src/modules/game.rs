@@ -3,6 +3,10 @@ use steadfast_defs::engine::Application;
 
 exports! {
     struct GameExports {
-        create_application: () -> Application,
+        create_application: fn() -> Box<dyn Application>,
+
+        /// Takes over the data of the application created by the previous
+        /// library, see [`resume`](steadfast_defs::engine::resume).
+        resume_application: fn(application: *mut ()) -> Box<dyn Application>,
     }
 }
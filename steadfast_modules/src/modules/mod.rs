@@ -1,32 +1,200 @@
 pub mod engine;
 pub mod game;
 
+use crate::{Error, Symbols};
+use std::fmt::Debug;
+
+/// A vtable of the functions a module exports, declared with [`exports!`].
+pub trait Exports: Clone + Debug + Sized + 'static {
+    /// A hash of the name and signature of every export, see
+    /// [`AbiDescriptor`](crate::AbiDescriptor).
+    const SIGNATURE: u64;
+
+    /// Looks up every export in [`symbols`], failing with
+    /// [`Error::MissingExports`] if any required export is not found.
+    fn new(symbols: &Symbols<Self>) -> Result<Self, Error>;
+
+    /// The name of every export, in declaration order.
+    fn members() -> Vec<&'static str>;
+}
+
+/// Declares the functions a module exports.
+///
+/// Every export is looked up by its' name, so must be `#[no_mangle]` in
+/// the module. Exports wrapped in an `Option` may be left out.
+///
+/// ```ignore
+/// exports! {
+///     struct GameExports {
+///         create_application: fn() -> Application,
+///         resize: extern "C" fn(width: u32, height: u32),
+///         debug_draw: Option<fn(frame: u64)>,
+///     }
+/// }
+/// ```
 #[macro_export]
 macro_rules! exports {
-    (struct $name:ident {
-        $($func_name:ident: ($($param_name:ident: $param_type:ty),*) -> $func_ret:ty,)*
-    }) => {
-        use crate::{Symbols};
+    (struct $name:ident { $($body:tt)* }) => {
+        $crate::exports!(@parse $name [] $($body)*);
+    };
+
+    (@parse $name:ident [$($exports:tt)*]
+        $(#[$meta:meta])*
+        $func_name:ident: Option<$(extern $abi:literal)? fn($($param_name:ident: $param_type:ty),* $(,)?) $(-> $func_ret:ty)?>
+        $(, $($rest:tt)*)?
+    ) => {
+        $crate::exports!(@parse $name [
+            $($exports)*
+            { [$(#[$meta])*] $func_name optional [$(extern $abi)? fn($($param_name: $param_type),*) $(-> $func_ret)?] }
+        ] $($($rest)*)?);
+    };
 
-        #[derive(Debug)]
+    (@parse $name:ident [$($exports:tt)*]
+        $(#[$meta:meta])*
+        $func_name:ident: $(extern $abi:literal)? fn($($param_name:ident: $param_type:ty),* $(,)?) $(-> $func_ret:ty)?
+        $(, $($rest:tt)*)?
+    ) => {
+        $crate::exports!(@parse $name [
+            $($exports)*
+            { [$(#[$meta])*] $func_name required [$(extern $abi)? fn($($param_name: $param_type),*) $(-> $func_ret)?] }
+        ] $($($rest)*)?);
+    };
+
+    (@parse $name:ident [$({ [$(#[$meta:meta])*] $func_name:ident $kind:ident [$($func_type:tt)*] })*]) => {
+        #[derive(Clone, Debug)]
         pub struct $name {
             $(
-                pub $func_name: fn($($param_name: $param_type)*) -> $func_ret,
+                $(#[$meta])*
+                pub $func_name: $crate::exports!(@field $kind $($func_type)*),
             )*
         }
 
         impl $name {
-            pub fn new(symbols: &Symbols<Self>) -> Self {
-                Self {
-                    $(
-                        $func_name: unsafe { std::mem::transmute(symbols.lib.get::<fn($($param_name: $param_type)*) -> $func_ret>(stringify!($func_name).as_bytes()).unwrap()) },
-                    )*
+            #[allow(unused_variables, unused_mut)]
+            pub fn new(symbols: &$crate::Symbols<Self>) -> Result<Self, $crate::Error> {
+                let mut missing = vec![];
+
+                $(
+                    let $func_name = unsafe {
+                        symbols.get::<$($func_type)*>(stringify!($func_name))
+                    };
+
+                    $crate::exports!(@check $kind $func_name missing);
+                )*
+
+                if !missing.is_empty() {
+                    return Err($crate::Error::MissingExports(missing));
                 }
+
+                Ok(Self {
+                    $($func_name: $crate::exports!(@unwrap $kind $func_name),)*
+                })
             }
 
             pub fn members() -> Vec<&'static str> {
                 vec![$(stringify!($func_name)),*]
             }
         }
+
+        impl $crate::Exports for $name {
+            const SIGNATURE: u64 = $crate::fnv1a(concat!(
+                stringify!($name), " {",
+                $(stringify!($func_name), ": ", stringify!($kind), " ", stringify!($($func_type)*), ";",)*
+                "}"
+            ).as_bytes());
+
+            fn new(symbols: &$crate::Symbols<Self>) -> Result<Self, $crate::Error> {
+                Self::new(symbols)
+            }
+
+            fn members() -> Vec<&'static str> {
+                Self::members()
+            }
+        }
+    };
+
+    (@field required $($func_type:tt)*) => { $($func_type)* };
+    (@field optional $($func_type:tt)*) => { Option<$($func_type)*> };
+
+    (@check required $func_name:ident $missing:ident) => {
+        if $func_name.is_none() {
+            $missing.push(stringify!($func_name));
+        }
+    };
+    (@check optional $func_name:ident $missing:ident) => {};
+
+    (@unwrap required $func_name:ident) => { $func_name.unwrap() };
+    (@unwrap optional $func_name:ident) => { $func_name };
+}
+
+#[cfg(test)]
+#[allow(dead_code)]
+mod tests {
+    use crate::Exports;
+
+    exports! {
+        struct TestExports {
+            /// The number of apples.
+            apples: fn() -> u32,
+            add: fn(a: u32, b: u32) -> u32,
+            clamp: extern "C" fn(value: f32, min: f32, max: f32,) -> f32,
+            log: Option<fn(message: &str)>,
+            reset: Option<extern "C" fn()>,
+        }
+    }
+
+    mod required {
+        exports! {
+            struct LogExports {
+                log: fn(message: &str),
+            }
+        }
+    }
+
+    mod optional {
+        exports! {
+            struct LogExports {
+                log: Option<fn(message: &str)>,
+            }
+        }
+    }
+
+    fn apples() -> u32 {
+        3
+    }
+
+    fn add(a: u32, b: u32) -> u32 {
+        a + b
+    }
+
+    extern "C" fn clamp(value: f32, min: f32, max: f32) -> f32 {
+        value.max(min).min(max)
+    }
+
+    #[test]
+    fn exports_take_any_number_of_parameters() {
+        let exports = TestExports {
+            apples,
+            add,
+            clamp,
+            log: None,
+            reset: None,
+        };
+
+        assert_eq!((exports.apples)(), 3);
+        assert_eq!((exports.add)(1, 2), 3);
+        assert_eq!((exports.clamp)(2.0, 0.0, 1.0), 1.0);
+        assert_eq!(
+            TestExports::members(),
+            ["apples", "add", "clamp", "log", "reset"]
+        );
+    }
+
+    #[test]
+    fn signature_includes_optionality() {
+        assert_ne!(
+            required::LogExports::SIGNATURE,
+            optional::LogExports::SIGNATURE
+        );
     }
 }
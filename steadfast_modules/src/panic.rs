@@ -0,0 +1,102 @@
+use std::any::Any;
+use std::backtrace::Backtrace;
+use std::cell::{Cell, RefCell};
+use std::panic::{catch_unwind, AssertUnwindSafe};
+use std::sync::Once;
+use thiserror::Error;
+
+/// A panic caught at a module boundary.
+#[derive(Clone, Debug, Error)]
+#[error("{message}")]
+pub struct Panic {
+    pub message: String,
+
+    /// The backtrace of the panicking thread, captured when it panicked.
+    pub backtrace: String,
+}
+
+thread_local! {
+    /// The backtrace of the last panic on this thread, set by the hook
+    /// installed by [`catch_panic`].
+    static BACKTRACE: RefCell<Option<String>> = const { RefCell::new(None) };
+
+    /// How many calls to [`catch_panic`] this thread is in, so the hook
+    /// leaves every other panic to the previous hook.
+    static CATCHING: Cell<usize> = const { Cell::new(0) };
+}
+
+static HOOK: Once = Once::new();
+
+/// Runs [`f`], catching any panic it raises.
+///
+/// This is called by the callbacks generated with [`init_module!`], so
+/// that the panic is caught by the same copy of `std` which raised it. A
+/// module linking its' own `std` can't unwind into the host.
+pub fn catch_panic<R>(f: impl FnOnce() -> R) -> Result<R, Panic> {
+    HOOK.call_once(install_hook);
+    BACKTRACE.with(|backtrace| backtrace.borrow_mut().take());
+
+    CATCHING.with(|catching| catching.set(catching.get() + 1));
+    let result = catch_unwind(AssertUnwindSafe(f));
+    CATCHING.with(|catching| catching.set(catching.get() - 1));
+
+    result.map_err(|payload| Panic {
+        message: panic_message(payload.as_ref()),
+        backtrace: BACKTRACE
+            .with(|backtrace| backtrace.borrow_mut().take())
+            .unwrap_or_default(),
+    })
+}
+
+/// The backtrace is only available while panicking, so it is captured
+/// by a hook before the previous hook reports the panic as usual. Panics
+/// outside of [`catch_panic`] are only passed to the previous hook.
+fn install_hook() {
+    let previous = std::panic::take_hook();
+
+    std::panic::set_hook(Box::new(move |info| {
+        if CATCHING.with(Cell::get) > 0 {
+            let backtrace = Backtrace::force_capture().to_string();
+            BACKTRACE.with(|last| *last.borrow_mut() = Some(backtrace));
+        }
+
+        previous(info);
+    }));
+}
+
+fn panic_message(payload: &(dyn Any + Send)) -> String {
+    if let Some(message) = payload.downcast_ref::<&str>() {
+        message.to_string()
+    } else if let Some(message) = payload.downcast_ref::<String>() {
+        message.clone()
+    } else {
+        String::from("Box<dyn Any>")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::BACKTRACE;
+    use crate::catch_panic;
+
+    #[test]
+    fn panics_are_caught_with_a_backtrace() {
+        let panic = catch_panic(|| panic!("Broken {}", 17)).unwrap_err();
+
+        assert_eq!(panic.message, "Broken 17");
+        assert!(panic
+            .backtrace
+            .contains("panics_are_caught_with_a_backtrace"));
+
+        assert_eq!(catch_panic(|| 17).unwrap(), 17);
+    }
+
+    #[test]
+    fn other_panics_are_left_to_the_previous_hook() {
+        catch_panic(|| ()).unwrap();
+
+        std::panic::catch_unwind(|| panic!("Not from a module")).unwrap_err();
+
+        assert!(BACKTRACE.with(|backtrace| backtrace.borrow().is_none()));
+    }
+}
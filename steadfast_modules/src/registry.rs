@@ -0,0 +1,281 @@
+use crate::{
+    Error, Exports, Host, Manifest, Module, ModuleGraph, Panic, ReloadError, ReloadEvent,
+    SharedLogger,
+};
+use std::collections::HashMap;
+use std::path::Path;
+
+#[cfg(not(feature = "shipping"))]
+use crate::{ManualTrigger, NotifyTrigger, ReloadTrigger};
+
+#[cfg(feature = "shipping")]
+use crate::ModuleAPI;
+
+/// A [`Module`] with its' exports type erased, so that modules exporting
+/// different types can be stored together.
+trait AnyModule {
+    /// Reloads the module if it changed, publishing its' exports to
+    /// [`host`]. Returns the new generation if the module was reloaded.
+    fn reload(&mut self, host: &mut Host) -> Result<Option<u32>, ReloadError>;
+
+    fn request_reload(&mut self);
+
+    fn share_logger(&mut self, logger: SharedLogger);
+
+    fn update(&mut self, host: &mut Host, event: &ReloadEvent) -> Result<(), Panic>;
+}
+
+impl<VTable: Exports> AnyModule for Module<VTable> {
+    fn reload(&mut self, host: &mut Host) -> Result<Option<u32>, ReloadError> {
+        match Module::reload(self)? {
+            Some(symbols) => {
+                // The exports were checked when the library was loaded.
+                host.insert(symbols.exports().expect("Module is missing exports"));
+
+                Ok(Some(self.generation()))
+            }
+            None => Ok(None),
+        }
+    }
+
+    fn request_reload(&mut self) {
+        Module::request_reload(self)
+    }
+
+    fn share_logger(&mut self, logger: SharedLogger) {
+        Module::share_logger(self, logger)
+    }
+
+    fn update(&mut self, host: &mut Host, event: &ReloadEvent) -> Result<(), Panic> {
+        Module::update(self, host, event)
+    }
+}
+
+#[cfg(not(feature = "shipping"))]
+type Loader = fn(&Path, Box<dyn ReloadTrigger>) -> Result<Box<dyn AnyModule>, Error>;
+
+/// Creates a linked module, given its' name.
+#[cfg(feature = "shipping")]
+type Loader = Box<dyn Fn(&str) -> Result<Box<dyn AnyModule>, Error>>;
+
+#[cfg(not(feature = "shipping"))]
+fn load<VTable: Exports>(
+    path: &Path,
+    trigger: Box<dyn ReloadTrigger>,
+) -> Result<Box<dyn AnyModule>, Error> {
+    Ok(Box::new(Module::<VTable>::with_boxed_trigger(
+        path, trigger,
+    )?))
+}
+
+/// Creates the trigger each module is reloaded by.
+#[cfg(not(feature = "shipping"))]
+pub type TriggerFactory = fn() -> Box<dyn ReloadTrigger>;
+
+/// Watches modules for changes in debug builds. Release builds only
+/// reload when asked to, without a watcher.
+#[cfg(not(feature = "shipping"))]
+fn default_trigger() -> Box<dyn ReloadTrigger> {
+    if cfg!(debug_assertions) {
+        Box::new(NotifyTrigger::default())
+    } else {
+        Box::new(ManualTrigger)
+    }
+}
+
+/// Loads the modules listed in a [`Manifest`] and shares their exports
+/// through a [`Host`].
+///
+/// The exports type of each module is not part of the manifest, so every
+/// module must be [`register`]ed before the registry is [`load`]ed. With
+/// the `shipping` feature, modules are linked into the executable and
+/// [`register_linked`] instead, and the paths in the manifest are unused.
+pub struct ModuleRegistry {
+    manifest: Manifest,
+    loaders: HashMap<String, Loader>,
+    #[cfg(not(feature = "shipping"))]
+    trigger: TriggerFactory,
+
+    /// The loaded modules, indexed the same as [`graph`].
+    modules: Vec<Box<dyn AnyModule>>,
+    graph: Option<ModuleGraph>,
+
+    host: Host,
+    /// Shared with every module, see [`share_logger`].
+    logger: Option<SharedLogger>,
+}
+
+impl ModuleRegistry {
+    pub fn new(manifest: Manifest) -> Self {
+        Self {
+            manifest,
+            loaders: HashMap::new(),
+            #[cfg(not(feature = "shipping"))]
+            trigger: default_trigger,
+            modules: vec![],
+            graph: None,
+            host: Host::default(),
+            logger: None,
+        }
+    }
+
+    pub fn from_file(path: impl AsRef<Path>) -> Result<Self, Error> {
+        Ok(Self::new(Manifest::from_file(path)?))
+    }
+
+    /// Associates the module [`name`]d in the manifest with the type of
+    /// its' exports.
+    #[cfg(not(feature = "shipping"))]
+    pub fn register<VTable: Exports>(&mut self, name: impl Into<String>) -> &mut Self {
+        self.loaders.insert(name.into(), load::<VTable>);
+        self
+    }
+
+    /// Associates the module [`name`]d in the manifest with the
+    /// `__MODULE` static of the crate it is linked from.
+    #[cfg(feature = "shipping")]
+    pub fn register_linked<VTable: Exports>(
+        &mut self,
+        name: impl Into<String>,
+        api: &'static ModuleAPI<VTable>,
+    ) -> &mut Self {
+        self.loaders.insert(
+            name.into(),
+            Box::new(move |name| Ok(Box::new(Module::linked(name, api)?))),
+        );
+        self
+    }
+
+    /// Sets the trigger that modules loaded after this are reloaded by.
+    #[cfg(not(feature = "shipping"))]
+    pub fn set_trigger(&mut self, trigger: TriggerFactory) -> &mut Self {
+        self.trigger = trigger;
+        self
+    }
+
+    /// Loads every module in the manifest, dependencies first, and
+    /// publishes their exports to the host.
+    pub fn load(&mut self) -> Result<(), Error> {
+        #[cfg(not(feature = "shipping"))]
+        let profile = Manifest::profile();
+        let graph = ModuleGraph::new(&self.manifest)?;
+
+        for module in self.manifest.load_order()? {
+            let loader = self
+                .loaders
+                .get(&module.name)
+                .ok_or_else(|| Error::UnregisteredModule(module.name.clone()))?;
+
+            #[cfg(not(feature = "shipping"))]
+            let mut module = loader(&self.manifest.path(module, profile)?, (self.trigger)())?;
+            #[cfg(feature = "shipping")]
+            let mut module = loader(&module.name)?;
+
+            if let Some(logger) = &self.logger {
+                module.share_logger(logger.clone());
+            }
+
+            self.modules.push(module);
+        }
+
+        self.graph = Some(graph);
+        self.reload();
+
+        Ok(())
+    }
+
+    /// Reloads every module which changed, then notifies every module
+    /// which depends on a reloaded module, directly or not, in topological
+    /// order.
+    ///
+    /// A module which panics is logged and skipped, without affecting the
+    /// other modules.
+    pub fn reload(&mut self) {
+        let graph = match &self.graph {
+            Some(graph) => graph,
+            None => return,
+        };
+
+        let mut events = vec![];
+
+        for (index, module) in self.modules.iter_mut().enumerate() {
+            match module.reload(&mut self.host) {
+                Ok(Some(generation)) => events.push((
+                    index,
+                    ReloadEvent {
+                        module: graph.name(index).to_string(),
+                        generation,
+                    },
+                )),
+                Ok(None) => (),
+                Err(error) => log::error!("{}", error),
+            }
+        }
+
+        for (index, module) in self.modules.iter_mut().enumerate() {
+            for (reloaded, event) in &events {
+                if graph.depends_on_transitively(index, *reloaded) {
+                    // The other modules keep running, the faulted module is
+                    // skipped until it is rebuilt.
+                    if let Err(panic) = module.update(&mut self.host, event) {
+                        log::error!(
+                            "`update` of {} panicked: {}\n{}",
+                            graph.name(index),
+                            panic,
+                            panic.backtrace
+                        );
+                    }
+                }
+            }
+        }
+    }
+
+    /// Reloads the module [`name`]d in the manifest on the next
+    /// [`reload`], whether or not it changed.
+    pub fn request_reload(&mut self, name: &str) -> Result<(), Error> {
+        let index = self
+            .graph
+            .as_ref()
+            .and_then(|graph| graph.find(name))
+            .ok_or_else(|| Error::UnknownModule(name.to_string()))?;
+
+        self.modules[index].request_reload();
+
+        Ok(())
+    }
+
+    /// Has every module log through [`logger`], which is usually the
+    /// host's [`SharedLogger::current`]. Modules are given it whenever
+    /// they are loaded, and those already loaded are given it now, so
+    /// this can be called again after the host changes what it filters,
+    /// see [`SharedLogger::install`].
+    pub fn share_logger(&mut self, logger: SharedLogger) {
+        for module in &mut self.modules {
+            module.share_logger(logger.clone());
+        }
+
+        self.logger = Some(logger);
+    }
+
+    pub fn host(&self) -> &Host {
+        &self.host
+    }
+
+    pub fn host_mut(&mut self) -> &mut Host {
+        &mut self.host
+    }
+
+    /// Returns the exports of the loaded module which exports [`T`].
+    pub fn get<T: Exports>(&self) -> Option<&T> {
+        self.host.get()
+    }
+}
+
+impl Drop for ModuleRegistry {
+    fn drop(&mut self) {
+        // Modules are deinitialized before the modules they depend on
+        while let Some(module) = self.modules.pop() {
+            drop(module);
+        }
+    }
+}
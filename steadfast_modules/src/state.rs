@@ -0,0 +1,281 @@
+/// Describes the memory layout of a module's state.
+///
+/// The layout is embedded in the module by [`init_module!`] and compared
+/// by the host on reload, so that state written by one build of a module
+/// is never handed to a build which expects a different layout.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct StateLayout {
+    pub size: usize,
+    pub align: usize,
+
+    /// A hash of the name, type, size, alignment and offset of every field,
+    /// in declaration order, including the layout of the field's type.
+    pub fields: u64,
+}
+
+impl StateLayout {
+    /// The layout of [`T`], before any of its' fields are added.
+    pub const fn new<T>() -> Self {
+        Self {
+            size: core::mem::size_of::<T>(),
+            align: core::mem::align_of::<T>(),
+            fields: crate::fnv1a(&[]),
+        }
+    }
+
+    /// Adds the field declared as [`declaration`], of type [`F`], at
+    /// [`offset`] bytes into the state.
+    pub const fn field<F: StateField>(self, declaration: &str, offset: usize) -> Self {
+        let fields = crate::fnv1a_extend(self.fields, declaration.as_bytes());
+        let fields = mix(fields, offset as u64);
+
+        Self {
+            fields: mix(fields, F::HASH),
+            ..self
+        }
+    }
+
+    /// A hash of the whole layout, for state nested in another's.
+    pub const fn hash(&self) -> u64 {
+        let hash = mix(self.fields, self.size as u64);
+        mix(hash, self.align as u64)
+    }
+}
+
+/// Implemented for module state declared with [`module_state!`].
+pub trait ModuleState {
+    const LAYOUT: StateLayout;
+}
+
+/// Implemented for every type a field of module state may have, so that
+/// changes to the layout of the type itself are detected too.
+///
+/// Implemented for primitives and the standard containers of them. Any
+/// other type must be declared with [`module_state!`] as well.
+pub trait StateField {
+    /// A hash of the type's name, size and alignment, and the layout of
+    /// the types it contains.
+    const HASH: u64;
+}
+
+/// Mixes [`value`] into [`hash`].
+const fn mix(hash: u64, value: u64) -> u64 {
+    crate::fnv1a_extend(hash, &value.to_le_bytes())
+}
+
+/// The [`StateField::HASH`] of [`T`], named [`name`] and containing a
+/// type whose hash is [`contains`].
+const fn field_hash<T>(name: &str, contains: u64) -> u64 {
+    let hash = crate::fnv1a(name.as_bytes());
+    let hash = mix(hash, core::mem::size_of::<T>() as u64);
+    let hash = mix(hash, core::mem::align_of::<T>() as u64);
+    mix(hash, contains)
+}
+
+macro_rules! primitive_fields {
+    ($($type:ty),* $(,)?) => {
+        $(
+            impl StateField for $type {
+                const HASH: u64 = field_hash::<$type>(stringify!($type), 0);
+            }
+        )*
+    };
+}
+
+primitive_fields! {
+    (), bool, char, f32, f64, String,
+    u8, u16, u32, u64, u128, usize,
+    i8, i16, i32, i64, i128, isize,
+}
+
+impl<T: StateField> StateField for Option<T> {
+    const HASH: u64 = field_hash::<Self>("Option", T::HASH);
+}
+
+impl<T: StateField> StateField for Box<T> {
+    const HASH: u64 = field_hash::<Self>("Box", T::HASH);
+}
+
+impl<T: StateField> StateField for Vec<T> {
+    const HASH: u64 = field_hash::<Self>("Vec", T::HASH);
+}
+
+impl<T: StateField, const N: usize> StateField for [T; N] {
+    const HASH: u64 = field_hash::<Self>("Array", T::HASH);
+}
+
+/// Declares a module's state struct, implementing [`ModuleState`] with a
+/// layout derived from its' fields, whose types must implement
+/// [`StateField`].
+///
+/// Types nested in the state are declared the same way, which implements
+/// [`StateField`] for them.
+///
+/// ```ignore
+/// module_state! {
+///     struct State {
+///         application: Option<Application>,
+///     }
+/// }
+/// ```
+#[macro_export]
+macro_rules! module_state {
+    (
+        $(#[$meta:meta])*
+        $vis:vis struct $name:ident {
+            $($(#[$field_meta:meta])* $field_vis:vis $field:ident: $field_type:ty),* $(,)?
+        }
+    ) => {
+        $(#[$meta])*
+        $vis struct $name {
+            $($(#[$field_meta])* $field_vis $field: $field_type,)*
+        }
+
+        impl $crate::ModuleState for $name {
+            const LAYOUT: $crate::StateLayout = $crate::StateLayout::new::<$name>()
+                $(.field::<$field_type>(
+                    concat!(stringify!($field), ": ", stringify!($field_type), ";"),
+                    ::core::mem::offset_of!($name, $field),
+                ))*;
+        }
+
+        impl $crate::StateField for $name {
+            const HASH: u64 = <$name as $crate::ModuleState>::LAYOUT.hash();
+        }
+    };
+}
+
+#[cfg(test)]
+#[allow(dead_code)]
+mod tests {
+    use crate::ModuleState;
+
+    mod original {
+        module_state! {
+            pub struct State {
+                pub count: u32,
+                pub total: u64,
+            }
+        }
+    }
+
+    mod reordered {
+        module_state! {
+            pub struct State {
+                pub total: u64,
+                pub count: u32,
+            }
+        }
+    }
+
+    mod renamed {
+        module_state! {
+            pub struct State {
+                pub count: u32,
+                pub sum: u64,
+            }
+        }
+    }
+
+    mod retyped {
+        module_state! {
+            pub struct State {
+                pub count: i32,
+                pub total: u64,
+            }
+        }
+    }
+
+    mod copied {
+        module_state! {
+            pub struct State {
+                pub count: u32,
+                pub total: u64,
+            }
+        }
+    }
+
+    mod moved {
+        module_state! {
+            #[repr(C)]
+            pub struct State {
+                pub count: u32,
+                pub total: u64,
+            }
+        }
+    }
+
+    mod nested {
+        module_state! {
+            pub struct Totals {
+                pub count: u32,
+                pub total: u64,
+            }
+        }
+
+        module_state! {
+            pub struct State {
+                pub totals: Option<Box<Totals>>,
+            }
+        }
+    }
+
+    mod nested_retyped {
+        module_state! {
+            pub struct Totals {
+                pub count: u64,
+                pub total: u32,
+            }
+        }
+
+        module_state! {
+            pub struct State {
+                pub totals: Option<Box<Totals>>,
+            }
+        }
+    }
+
+    #[test]
+    fn layout_matches_identical_declarations() {
+        assert_eq!(original::State::LAYOUT, copied::State::LAYOUT);
+    }
+
+    #[test]
+    fn layout_detects_field_changes() {
+        assert_ne!(original::State::LAYOUT, reordered::State::LAYOUT);
+        assert_ne!(original::State::LAYOUT, renamed::State::LAYOUT);
+        assert_ne!(original::State::LAYOUT, retyped::State::LAYOUT);
+
+        // None of these changes affect the size or alignment
+        assert_eq!(original::State::LAYOUT.size, renamed::State::LAYOUT.size);
+        assert_eq!(original::State::LAYOUT.align, retyped::State::LAYOUT.align);
+    }
+
+    #[test]
+    fn layout_detects_moved_fields() {
+        // `repr(C)` keeps the fields in declaration order, where Rust puts
+        // `total` first
+        assert_ne!(
+            std::mem::offset_of!(original::State, count),
+            std::mem::offset_of!(moved::State, count),
+        );
+
+        assert_ne!(original::State::LAYOUT, moved::State::LAYOUT);
+        assert_eq!(original::State::LAYOUT.size, moved::State::LAYOUT.size);
+    }
+
+    #[test]
+    fn layout_detects_changes_to_nested_types() {
+        assert_ne!(nested::State::LAYOUT, nested_retyped::State::LAYOUT);
+
+        // Both are declared the same way, and only hold a pointer
+        assert_eq!(
+            nested::State::LAYOUT.size,
+            nested_retyped::State::LAYOUT.size
+        );
+        assert_eq!(
+            std::mem::size_of::<nested::Totals>(),
+            std::mem::size_of::<nested_retyped::Totals>(),
+        );
+    }
+}
@@ -0,0 +1,296 @@
+use crate::Error;
+use notify::{watcher, DebouncedEvent, RecommendedWatcher, Watcher};
+use std::ffi::OsString;
+use std::path::{Path, PathBuf};
+use std::sync::mpsc::{channel, Receiver};
+use std::time::{Duration, SystemTime};
+
+/// Decides when a [`Module`](crate::Module) reloads its' library.
+///
+/// A module can always be reloaded with
+/// [`request_reload`](crate::Module::request_reload), whichever trigger it
+/// uses.
+pub trait ReloadTrigger {
+    /// Starts watching the library at [`path`]. Called once, after the
+    /// library was first loaded.
+    fn watch(&mut self, path: &Path) -> Result<(), Error>;
+
+    /// Whether the library changed since this was last called.
+    fn changed(&mut self) -> bool;
+}
+
+/// Reloads when the filesystem reports a write to the library.
+///
+/// Events are debounced by [`delay`], so a library is only reloaded once
+/// the build has finished writing it.
+pub struct NotifyTrigger {
+    delay: Duration,
+    file_name: Option<OsString>,
+    /// Held so that events keep being delivered to [`rx`].
+    watcher: Option<(RecommendedWatcher, Receiver<DebouncedEvent>)>,
+}
+
+impl NotifyTrigger {
+    pub fn new(delay: Duration) -> Self {
+        Self {
+            delay,
+            file_name: None,
+            watcher: None,
+        }
+    }
+
+    fn watch_dir(path: &Path) -> &Path {
+        match path.parent() {
+            Some(parent) if parent != Path::new("") => parent,
+            _ => Path::new("."),
+        }
+    }
+}
+
+impl Default for NotifyTrigger {
+    fn default() -> Self {
+        Self::new(Duration::from_secs(1))
+    }
+}
+
+impl ReloadTrigger for NotifyTrigger {
+    fn watch(&mut self, path: &Path) -> Result<(), Error> {
+        let (tx, rx) = channel();
+        let mut watcher = watcher(tx, self.delay)?;
+        watcher.watch(Self::watch_dir(path), notify::RecursiveMode::NonRecursive)?;
+
+        self.file_name = path.file_name().map(OsString::from);
+        self.watcher = Some((watcher, rx));
+
+        Ok(())
+    }
+
+    fn changed(&mut self) -> bool {
+        let rx = match &self.watcher {
+            Some((_, rx)) => rx,
+            None => return false,
+        };
+
+        let mut changed = false;
+
+        while let Ok(event) = rx.try_recv() {
+            use notify::DebouncedEvent::*;
+
+            match event {
+                NoticeWrite(ref path)
+                | Write(ref path)
+                | Create(ref path)
+                | Rename(_, ref path)
+                    if path.file_name() == self.file_name.as_deref() =>
+                {
+                    changed = true;
+                }
+                _ => (),
+            }
+        }
+
+        changed
+    }
+}
+
+/// Reloads when the modification time of the library changes, checking
+/// it every time the module is reloaded.
+#[derive(Default)]
+pub struct PollTrigger {
+    path: PathBuf,
+    modified: Option<SystemTime>,
+}
+
+impl PollTrigger {
+    fn modified(path: &Path) -> Option<SystemTime> {
+        std::fs::metadata(path)
+            .and_then(|metadata| metadata.modified())
+            .ok()
+    }
+}
+
+impl ReloadTrigger for PollTrigger {
+    fn watch(&mut self, path: &Path) -> Result<(), Error> {
+        self.path = path.to_path_buf();
+        self.modified = Self::modified(path);
+
+        Ok(())
+    }
+
+    fn changed(&mut self) -> bool {
+        let modified = Self::modified(&self.path);
+
+        // The library may be missing while it is being rebuilt
+        if modified.is_none() || modified == self.modified {
+            return false;
+        }
+
+        self.modified = modified;
+
+        true
+    }
+}
+
+/// Never reloads on its' own, only when a reload is requested.
+#[derive(Default)]
+pub struct ManualTrigger;
+
+impl ReloadTrigger for ManualTrigger {
+    fn watch(&mut self, _path: &Path) -> Result<(), Error> {
+        Ok(())
+    }
+
+    fn changed(&mut self) -> bool {
+        false
+    }
+}
+
+/// Filters another trigger, ignoring changes which leave the contents of
+/// the library unchanged, e.g. a rebuild which produced the same output.
+pub struct HashTrigger<T: ReloadTrigger> {
+    inner: T,
+    path: PathBuf,
+    hash: Option<u64>,
+    /// Set when [`inner`] reported a change which couldn't be hashed yet,
+    /// e.g. as the library was still being written.
+    pending: bool,
+}
+
+impl<T: ReloadTrigger> HashTrigger<T> {
+    pub fn new(inner: T) -> Self {
+        Self {
+            inner,
+            path: PathBuf::new(),
+            hash: None,
+            pending: false,
+        }
+    }
+
+    fn hash(path: &Path) -> Option<u64> {
+        std::fs::read(path).ok().map(|bytes| crate::fnv1a(&bytes))
+    }
+}
+
+impl<T: ReloadTrigger> ReloadTrigger for HashTrigger<T> {
+    fn watch(&mut self, path: &Path) -> Result<(), Error> {
+        self.path = path.to_path_buf();
+        self.hash = Self::hash(path);
+
+        self.inner.watch(path)
+    }
+
+    fn changed(&mut self) -> bool {
+        self.pending |= self.inner.changed();
+
+        if !self.pending {
+            return false;
+        }
+
+        // Checked again next time, rather than losing the change
+        let hash = match Self::hash(&self.path) {
+            Some(hash) => hash,
+            None => return false,
+        };
+
+        self.pending = false;
+
+        if Some(hash) == self.hash {
+            return false;
+        }
+
+        self.hash = Some(hash);
+
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{Error, HashTrigger, PollTrigger, ReloadTrigger};
+    use std::path::{Path, PathBuf};
+    use std::time::{Duration, SystemTime};
+
+    fn library(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join("steadfast_modules_trigger");
+        std::fs::create_dir_all(&dir).unwrap();
+
+        let path = dir.join(format!("{}-{}", name, std::process::id()));
+        std::fs::write(&path, b"first").unwrap();
+
+        path
+    }
+
+    /// Moves the modification time forward, as filesystems with a coarse
+    /// timestamp resolution may not see a change between quick writes.
+    fn touch(path: &PathBuf, contents: &[u8], seconds: u64) {
+        std::fs::write(path, contents).unwrap();
+
+        let file = std::fs::File::options().write(true).open(path).unwrap();
+        file.set_modified(SystemTime::now() + Duration::from_secs(seconds))
+            .unwrap();
+    }
+
+    #[test]
+    fn poll_trigger_sees_modifications() {
+        let path = library("poll");
+        let mut trigger = PollTrigger::default();
+        trigger.watch(&path).unwrap();
+
+        assert!(!trigger.changed());
+
+        touch(&path, b"second", 10);
+        assert!(trigger.changed());
+        assert!(!trigger.changed());
+
+        std::fs::remove_file(&path).unwrap();
+        assert!(!trigger.changed());
+    }
+
+    #[test]
+    fn hash_trigger_ignores_identical_writes() {
+        let path = library("hash");
+        let mut trigger = HashTrigger::new(PollTrigger::default());
+        trigger.watch(&path).unwrap();
+
+        touch(&path, b"first", 10);
+        assert!(!trigger.changed());
+
+        touch(&path, b"second", 20);
+        assert!(trigger.changed());
+
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    /// Reports a change when the test sets it.
+    #[derive(Default)]
+    struct Flag(bool);
+
+    impl ReloadTrigger for Flag {
+        fn watch(&mut self, _path: &Path) -> Result<(), Error> {
+            Ok(())
+        }
+
+        fn changed(&mut self) -> bool {
+            std::mem::take(&mut self.0)
+        }
+    }
+
+    #[test]
+    fn hash_trigger_retries_unreadable_changes() {
+        let path = library("retry");
+        let mut trigger = HashTrigger::new(Flag::default());
+        trigger.watch(&path).unwrap();
+
+        // As when the build has removed the library to write it again
+        std::fs::remove_file(&path).unwrap();
+        trigger.inner.0 = true;
+        assert!(!trigger.changed());
+        assert!(!trigger.changed());
+
+        std::fs::write(&path, b"second").unwrap();
+        assert!(trigger.changed());
+        assert!(!trigger.changed());
+
+        std::fs::remove_file(&path).unwrap();
+    }
+}
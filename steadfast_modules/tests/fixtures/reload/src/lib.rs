@@ -0,0 +1,134 @@
+use steadfast_defs::engine::{Application, ApplicationConfig};
+use steadfast_modules::game::GameExports;
+use steadfast_modules::{init_module, module_state, Host, ReloadEvent};
+
+#[cfg(not(feature = "v2"))]
+module_state! {
+    #[repr(C)]
+    struct State {
+        count: u32,
+        /// Allocated by `reload` and freed by `unload`, holding `count`.
+        heap: Option<Box<u32>>,
+    }
+}
+
+#[cfg(feature = "v2")]
+module_state! {
+    #[repr(C)]
+    struct State {
+        count: u32,
+        label: u64,
+    }
+}
+
+#[cfg(not(feature = "v2"))]
+init_module! {
+    state: State,
+    exports: GameExports,
+    init: init,
+    reload: reload,
+    update: update,
+    unload: unload,
+    deinit: deinit,
+}
+
+#[cfg(feature = "v2")]
+init_module! {
+    state: State,
+    version: 2,
+    exports: GameExports,
+    init: init,
+    migrate: migrate,
+    reload: reload,
+    update: update,
+    unload: unload,
+    deinit: deinit,
+}
+
+struct Fixture;
+
+/// Reports the version the fixture was built with as its' title, which
+/// the tests change between builds to tell the libraries apart.
+impl Application for Fixture {
+    fn config(&self) -> ApplicationConfig {
+        ApplicationConfig {
+            title: env!("FIXTURE_VERSION").to_string(),
+            ..ApplicationConfig::default()
+        }
+    }
+}
+
+#[cfg_attr(not(feature = "stripped"), no_mangle)]
+pub fn create_application() -> Box<dyn Application> {
+    Box::new(Fixture)
+}
+
+#[no_mangle]
+fn resume_application(application: *mut ()) -> Box<dyn Application> {
+    unsafe { steadfast_defs::engine::resume::<Fixture>(application) }
+}
+
+#[cfg(not(feature = "v2"))]
+fn init(state: &mut State) {
+    log::info!("Fixture {} initialized", env!("FIXTURE_VERSION"));
+
+    state.count = 1;
+}
+
+#[cfg(feature = "v2")]
+fn init(state: &mut State) {
+    state.count = 1;
+    state.label = 0;
+}
+
+#[cfg(feature = "v2")]
+fn migrate(old_state: &[u8], old_version: u32) -> State {
+    assert_eq!(old_version, 0);
+
+    State {
+        count: u32::from_ne_bytes([old_state[0], old_state[1], old_state[2], old_state[3]]),
+        label: 0xfeed,
+    }
+}
+
+fn reload(state: &mut State) -> GameExports {
+    log::debug!("Fixture {} reloaded", env!("FIXTURE_VERSION"));
+
+    state.count += 1;
+
+    if cfg!(feature = "broken") {
+        panic!("Broken fixture");
+    }
+
+    #[cfg(not(feature = "v2"))]
+    {
+        state.heap = Some(Box::new(state.count));
+    }
+
+    GameExports {
+        create_application,
+        resume_application,
+    }
+}
+
+fn update(_host: &mut Host, event: &ReloadEvent, _state: &mut State) {
+    if cfg!(feature = "faulty") {
+        panic!("Faulty fixture updated by {}", event.module);
+    }
+}
+
+#[cfg(not(feature = "v2"))]
+fn unload(state: &mut State) {
+    state.heap = None;
+}
+
+#[cfg(feature = "v2")]
+fn unload(_state: &mut State) {}
+
+#[cfg(not(feature = "v2"))]
+fn deinit(state: &mut State) {
+    state.heap = None;
+}
+
+#[cfg(feature = "v2")]
+fn deinit(_state: &mut State) {}
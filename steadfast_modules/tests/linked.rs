@@ -0,0 +1,194 @@
+#![cfg(feature = "shipping")]
+
+use std::sync::Mutex;
+use steadfast_modules::game::GameExports;
+use steadfast_modules::{Module, ModuleRegistry};
+
+/// The reload events each module of the chain was updated with, as
+/// `<module> <- <reloaded module>`.
+static UPDATES: Mutex<Vec<String>> = Mutex::new(vec![]);
+
+/// Declares a module which records its' updates in [`UPDATES`].
+macro_rules! chain_module {
+    ($name:ident) => {
+        mod $name {
+            use steadfast_defs::engine::Application;
+            use steadfast_modules::game::GameExports;
+            use steadfast_modules::{init_module, module_state, Host, ReloadEvent};
+
+            module_state! {
+                pub struct State {
+                    pub count: u32,
+                }
+            }
+
+            init_module! {
+                state: State,
+                exports: GameExports,
+                init: init,
+                reload: reload,
+                update: update,
+                unload: unload,
+                deinit: deinit,
+            }
+
+            fn create_application() -> Box<dyn Application> {
+                unimplemented!()
+            }
+
+            fn resume_application(_application: *mut ()) -> Box<dyn Application> {
+                unimplemented!()
+            }
+
+            fn init(_state: &mut State) {}
+
+            fn reload(_state: &mut State) -> GameExports {
+                GameExports {
+                    create_application,
+                    resume_application,
+                }
+            }
+
+            fn update(_host: &mut Host, event: &ReloadEvent, _state: &mut State) {
+                let update = format!("{} <- {}", stringify!($name), event.module);
+                super::UPDATES.lock().unwrap().push(update);
+            }
+
+            fn unload(_state: &mut State) {}
+
+            fn deinit(_state: &mut State) {}
+        }
+    };
+}
+
+chain_module!(libcore);
+chain_module!(libengine);
+chain_module!(libtools);
+
+mod game {
+    use steadfast_defs::engine::{Application, ApplicationConfig};
+    use steadfast_modules::game::GameExports;
+    use steadfast_modules::{init_module, module_state, Host, ReloadEvent};
+
+    module_state! {
+        pub struct State {
+            pub count: u32,
+        }
+    }
+
+    init_module! {
+        state: State,
+        exports: GameExports,
+        init: init,
+        reload: reload,
+        update: update,
+        unload: unload,
+        deinit: deinit,
+    }
+
+    struct Game;
+
+    impl Application for Game {
+        fn config(&self) -> ApplicationConfig {
+            ApplicationConfig {
+                title: String::from("linked"),
+                ..ApplicationConfig::default()
+            }
+        }
+    }
+
+    fn create_application() -> Box<dyn Application> {
+        Box::new(Game)
+    }
+
+    fn resume_application(application: *mut ()) -> Box<dyn Application> {
+        unsafe { steadfast_defs::engine::resume::<Game>(application) }
+    }
+
+    fn init(state: &mut State) {
+        state.count = 1;
+    }
+
+    fn reload(state: &mut State) -> GameExports {
+        state.count += 1;
+
+        GameExports {
+            create_application,
+            resume_application,
+        }
+    }
+
+    fn update(_host: &mut Host, _event: &ReloadEvent, _state: &mut State) {}
+
+    fn unload(_state: &mut State) {}
+
+    fn deinit(_state: &mut State) {}
+}
+
+#[test]
+fn linked_module_follows_lifecycle() {
+    let mut module = Module::linked("libgame", &game::__MODULE).unwrap();
+
+    assert!(module.reload().unwrap().is_some());
+    assert!(module.reload().unwrap().is_none());
+    assert_eq!(module.state[0] as u32, 2);
+
+    let exports = module.symbols.as_ref().unwrap().exports().unwrap();
+    assert_eq!((exports.create_application)().config().title, "linked");
+
+    module.request_reload();
+    assert!(module.reload().unwrap().is_some());
+    assert_eq!(module.generation(), 2);
+    assert_eq!(module.state[0] as u32, 3);
+}
+
+#[test]
+fn registry_links_modules() {
+    let manifest = r#"
+        [[module]]
+        name = "libgame"
+        path = {}
+    "#;
+
+    let mut modules = ModuleRegistry::new(manifest.parse().unwrap());
+    modules.register_linked("libgame", &game::__MODULE);
+    modules.load().unwrap();
+
+    let game = modules.get::<GameExports>().unwrap();
+    assert_eq!((game.create_application)().config().title, "linked");
+}
+
+#[test]
+fn registry_notifies_indirect_dependents() {
+    let manifest = r#"
+        [[module]]
+        name = "libtools"
+        path = {}
+        dependencies = ["libengine"]
+
+        [[module]]
+        name = "libengine"
+        path = {}
+        dependencies = ["libcore"]
+
+        [[module]]
+        name = "libcore"
+        path = {}
+    "#;
+
+    let mut modules = ModuleRegistry::new(manifest.parse().unwrap());
+    modules.register_linked("libcore", &libcore::__MODULE);
+    modules.register_linked("libengine", &libengine::__MODULE);
+    modules.register_linked("libtools", &libtools::__MODULE);
+    modules.load().unwrap();
+
+    UPDATES.lock().unwrap().clear();
+
+    modules.request_reload("libcore").unwrap();
+    modules.reload();
+
+    assert_eq!(
+        *UPDATES.lock().unwrap(),
+        ["libengine <- libcore", "libtools <- libcore"]
+    );
+}
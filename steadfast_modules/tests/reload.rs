@@ -0,0 +1,405 @@
+#![cfg(not(feature = "shipping"))]
+
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+use steadfast_modules::engine::EngineExports;
+use steadfast_modules::game::GameExports;
+use steadfast_modules::{
+    Error, Host, ManualTrigger, Module, ModuleRegistry, ModuleStatus, ReloadCause, ReloadEvent,
+    SharedLogger,
+};
+
+const FIXTURE: &str = "reload_fixture";
+
+/// Held while building and installing the fixture, as the tests share a
+/// target directory.
+static BUILD: Mutex<()> = Mutex::new(());
+
+/// Copies the fixture's crate to [`dir`], with its' dependencies on the
+/// workspace's crates pointed back at them, and returns its' manifest.
+///
+/// The fixture is handed the tests' logger, so it must be built with the
+/// same versions of `log` and `tracing-core` as the workspace's modules
+/// are. It is built from a copy with the workspace's lockfile, so the
+/// lockfile is never written to the source tree.
+fn copy_fixture(dir: &Path) -> PathBuf {
+    let crate_dir = Path::new(env!("CARGO_MANIFEST_DIR"));
+    let fixture = crate_dir.join("tests/fixtures/reload");
+
+    std::fs::create_dir_all(dir.join("src")).unwrap();
+    std::fs::copy(fixture.join("src/lib.rs"), dir.join("src/lib.rs")).unwrap();
+
+    let manifest = std::fs::read_to_string(fixture.join("Cargo.toml"))
+        .unwrap()
+        .replace(
+            r#""../../../../steadfast_defs""#,
+            &format!("{:?}", crate_dir.join("../steadfast_defs")),
+        )
+        .replace(r#""../../..""#, &format!("{:?}", crate_dir));
+    std::fs::write(dir.join("Cargo.toml"), manifest).unwrap();
+
+    let workspace_lock = crate_dir.join("../Cargo.lock");
+    if workspace_lock.exists() {
+        std::fs::copy(workspace_lock, dir.join("Cargo.lock")).unwrap();
+    }
+
+    dir.join("Cargo.toml")
+}
+
+/// Builds the fixture module, reporting [`version`] from its'
+/// `create_application`, and installs it in [`dir`].
+fn build_fixture(dir: &Path, version: u32, features: &str) {
+    let _guard = BUILD.lock().unwrap_or_else(|error| error.into_inner());
+
+    let target_dir = Path::new(env!("CARGO_TARGET_TMPDIR")).join("reload_fixture");
+    let manifest = copy_fixture(&target_dir.join("crate"));
+
+    let status = Command::new(env!("CARGO"))
+        .arg("build")
+        .arg("--quiet")
+        .arg("--manifest-path")
+        .arg(&manifest)
+        .arg("--target-dir")
+        .arg(&target_dir)
+        .arg("--features")
+        .arg(features)
+        .env("FIXTURE_VERSION", version.to_string())
+        .status()
+        .expect("Failed to run cargo");
+
+    assert!(status.success(), "Failed to build the fixture module");
+
+    // Copy the library to where the module is watching it, as cargo does
+    // when it finishes a build.
+    let built = target_dir
+        .join("debug")
+        .join(libloading::library_filename(FIXTURE));
+
+    std::fs::create_dir_all(dir).unwrap();
+    std::fs::copy(built, dir.join(libloading::library_filename(FIXTURE))).unwrap();
+}
+
+fn application_num(module: &Module<GameExports>) -> u32 {
+    let exports = GameExports::new(module.symbols.as_ref().unwrap()).unwrap();
+
+    (exports.create_application)()
+        .config()
+        .title
+        .parse()
+        .unwrap()
+}
+
+#[test]
+fn reload_picks_up_rebuilt_library() {
+    let dir = Path::new(env!("CARGO_TARGET_TMPDIR")).join("reload_module");
+
+    build_fixture(&dir, 1, "");
+
+    let mut module = Module::<GameExports>::new(&dir.join(FIXTURE)).unwrap();
+
+    assert!(module.reload().unwrap().is_some());
+    assert_eq!(application_num(&module), 1);
+    assert_eq!(module.generation(), 1);
+
+    let first_copy = module.symbols.as_ref().unwrap().loaded_path().to_path_buf();
+    assert!(first_copy.exists());
+    assert!(!first_copy.starts_with(&dir));
+
+    build_fixture(&dir, 2, "");
+
+    let deadline = Instant::now() + Duration::from_secs(30);
+    while module.reload().unwrap().is_none() {
+        assert!(
+            Instant::now() < deadline,
+            "The rebuilt library was never reloaded"
+        );
+        std::thread::sleep(Duration::from_millis(100));
+    }
+
+    assert_eq!(application_num(&module), 2);
+    assert_eq!(module.generation(), 2);
+
+    let second_copy = module.symbols.as_ref().unwrap().loaded_path().to_path_buf();
+    assert_ne!(first_copy, second_copy);
+    assert!(!first_copy.exists());
+
+    drop(module);
+    assert!(!second_copy.exists());
+}
+
+#[test]
+fn manual_trigger_reloads_only_when_requested() {
+    let dir = Path::new(env!("CARGO_TARGET_TMPDIR")).join("manual_module");
+
+    build_fixture(&dir, 1, "");
+
+    let mut module =
+        Module::<GameExports>::with_trigger(&dir.join(FIXTURE), ManualTrigger).unwrap();
+    assert!(module.reload().unwrap().is_some());
+
+    build_fixture(&dir, 2, "");
+    assert!(module.reload().unwrap().is_none());
+    assert_eq!(application_num(&module), 1);
+
+    module.request_reload();
+    assert!(module.reload().unwrap().is_some());
+    assert!(module.reload().unwrap().is_none());
+
+    assert_eq!(application_num(&module), 2);
+    assert_eq!(module.generation(), 2);
+}
+
+#[test]
+fn registry_exports_are_replaced_on_reload() {
+    let dir = Path::new(env!("CARGO_TARGET_TMPDIR")).join("registry_module");
+
+    build_fixture(&dir, 1, "");
+
+    let library = dir.join(FIXTURE);
+    let manifest = format!(
+        "[[module]]\nname = \"{0}\"\npath = {{ debug = {1:?}, release = {1:?} }}\n",
+        FIXTURE, library
+    );
+
+    let mut modules = ModuleRegistry::new(manifest.parse().unwrap());
+    modules
+        .register::<GameExports>(FIXTURE)
+        .set_trigger(|| Box::new(ManualTrigger));
+    modules.load().unwrap();
+
+    let title = |modules: &ModuleRegistry| {
+        let game = modules.get::<GameExports>().unwrap();
+
+        (game.create_application)().config().title
+    };
+
+    assert_eq!(title(&modules), "1");
+
+    build_fixture(&dir, 2, "");
+    modules.request_reload(FIXTURE).unwrap();
+    modules.reload();
+
+    assert_eq!(title(&modules), "2");
+}
+
+#[test]
+fn reload_migrates_state_with_changed_layout() {
+    let dir = Path::new(env!("CARGO_TARGET_TMPDIR")).join("migrate_module");
+
+    build_fixture(&dir, 1, "");
+
+    let mut module =
+        Module::<GameExports>::with_trigger(&dir.join(FIXTURE), ManualTrigger).unwrap();
+    module.do_reload().unwrap();
+
+    assert_eq!(state_count(&module), 2);
+
+    build_fixture(&dir, 2, "v2");
+    module.do_reload().unwrap();
+
+    // `migrate` carries the count over before `reload` increments it
+    assert_eq!(application_num(&module), 2);
+    assert_eq!(state_count(&module), 3);
+    assert_eq!(module.state[1], 0xfeed);
+}
+
+#[test]
+fn failed_reload_keeps_previous_library() {
+    let dir = Path::new(env!("CARGO_TARGET_TMPDIR")).join("rollback_module");
+
+    build_fixture(&dir, 1, "");
+
+    let mut module =
+        Module::<GameExports>::with_trigger(&dir.join(FIXTURE), ManualTrigger).unwrap();
+    module.do_reload().unwrap();
+
+    build_fixture(&dir, 2, "broken");
+
+    let error = module.do_reload().unwrap_err();
+    assert!(matches!(
+        error.cause,
+        ReloadCause::Panicked {
+            callback: "reload",
+            ..
+        }
+    ));
+
+    // The count incremented by the panicking `reload` is rolled back, then
+    // incremented by the previous library's `reload`
+    assert_eq!(application_num(&module), 1);
+    assert_eq!(state_count(&module), 3);
+    assert!(!module.is_faulted());
+
+    std::fs::write(
+        dir.join(libloading::library_filename(FIXTURE)),
+        b"not a library",
+    )
+    .unwrap();
+
+    let error = module.do_reload().unwrap_err();
+    assert!(matches!(error.cause, ReloadCause::Load(_)));
+    assert_eq!(application_num(&module), 1);
+
+    build_fixture(&dir, 3, "");
+    module.do_reload().unwrap();
+    assert!(!module.is_faulted());
+
+    assert_eq!(application_num(&module), 3);
+    assert_eq!(state_count(&module), 4);
+}
+
+#[test]
+fn failed_reload_gives_the_state_back_to_the_previous_library() {
+    let dir = Path::new(env!("CARGO_TARGET_TMPDIR")).join("unload_module");
+
+    build_fixture(&dir, 1, "");
+
+    let mut module =
+        Module::<GameExports>::with_trigger(&dir.join(FIXTURE), ManualTrigger).unwrap();
+    module.do_reload().unwrap();
+    assert_eq!(state_heap(&module), Some(2));
+
+    build_fixture(&dir, 2, "broken");
+
+    // `unload` frees the allocation each time, so it must be allocated
+    // again by the previous library, rather than restored from before
+    // `unload` ran and freed twice
+    for count in 3..5 {
+        module.do_reload().unwrap_err();
+
+        assert_eq!(state_count(&module), count);
+        assert_eq!(state_heap(&module), Some(count));
+    }
+
+    build_fixture(&dir, 3, "");
+    module.do_reload().unwrap();
+
+    assert_eq!(application_num(&module), 3);
+    assert_eq!(state_heap(&module), Some(5));
+}
+
+#[test]
+fn faulted_module_recovers_when_rebuilt() {
+    let dir = Path::new(env!("CARGO_TARGET_TMPDIR")).join("faulty_module");
+
+    build_fixture(&dir, 1, "faulty");
+
+    let mut module =
+        Module::<GameExports>::with_trigger(&dir.join(FIXTURE), ManualTrigger).unwrap();
+    module.do_reload().unwrap();
+
+    let mut host = Host::default();
+    let event = ReloadEvent {
+        module: String::from("libengine"),
+        generation: 1,
+    };
+
+    let panic = module.update(&mut host, &event).unwrap_err();
+    assert_eq!(panic.message, "Faulty fixture updated by libengine");
+    assert!(!panic.backtrace.is_empty());
+
+    match module.status() {
+        ModuleStatus::Faulted { callback, panic } => {
+            assert_eq!(*callback, "update");
+            assert_eq!(panic.message, "Faulty fixture updated by libengine");
+        }
+        other => panic!("Expected the module to be faulted, got {:?}", other),
+    }
+
+    // Faulted modules are skipped until they are rebuilt
+    module.update(&mut host, &event).unwrap();
+
+    build_fixture(&dir, 2, "");
+    module.do_reload().unwrap();
+
+    assert!(!module.is_faulted());
+    module.update(&mut host, &event).unwrap();
+    assert_eq!(application_num(&module), 2);
+}
+
+#[test]
+fn mismatched_exports_are_refused() {
+    let dir = Path::new(env!("CARGO_TARGET_TMPDIR")).join("abi_module");
+
+    build_fixture(&dir, 1, "");
+
+    match Module::<EngineExports>::new(&dir.join(FIXTURE)).err() {
+        Some(Error::AbiMismatch { expected, found }) => {
+            assert_eq!(expected.rustc, found.rustc);
+            assert_eq!(expected.crate_version, found.crate_version);
+            assert_ne!(expected.exports, found.exports);
+        }
+        other => panic!("Expected an ABI mismatch, got {:?}", other),
+    }
+}
+
+#[test]
+fn missing_exports_are_listed() {
+    let dir = Path::new(env!("CARGO_TARGET_TMPDIR")).join("stripped_module");
+
+    build_fixture(&dir, 1, "stripped");
+
+    match Module::<GameExports>::new(&dir.join(FIXTURE)).err() {
+        Some(Error::MissingExports(missing)) => assert_eq!(missing, ["create_application"]),
+        other => panic!("Expected missing exports, got {:?}", other),
+    }
+}
+
+/// Reads `heap`, the allocation in the second field of the fixture's state.
+fn state_heap(module: &Module<GameExports>) -> Option<u32> {
+    let heap = module.state[1] as *const u32;
+
+    // Only freed by the module's `unload` or `deinit`
+    unsafe { heap.as_ref().copied() }
+}
+
+/// Keeps the messages logged by the tests.
+struct CaptureLogger(Mutex<Vec<String>>);
+
+impl log::Log for CaptureLogger {
+    fn enabled(&self, _metadata: &log::Metadata) -> bool {
+        true
+    }
+
+    fn log(&self, record: &log::Record) {
+        self.0.lock().unwrap().push(record.args().to_string());
+    }
+
+    fn flush(&self) {}
+}
+
+static CAPTURE: CaptureLogger = CaptureLogger(Mutex::new(vec![]));
+
+#[test]
+fn modules_log_through_the_shared_logger() {
+    let dir = Path::new(env!("CARGO_TARGET_TMPDIR")).join("logging_module");
+
+    log::set_logger(&CAPTURE).unwrap();
+    log::set_max_level(log::LevelFilter::Info);
+
+    build_fixture(&dir, 4, "");
+
+    let mut module =
+        Module::<GameExports>::with_trigger(&dir.join(FIXTURE), ManualTrigger).unwrap();
+
+    // Without it, the library would log to its' own logger, which is unset
+    let logged = |message: &str| CAPTURE.0.lock().unwrap().iter().any(|line| line == message);
+    assert!(logged("Fixture 4 initialized"));
+
+    // Changes the host makes are shared again
+    log::set_max_level(log::LevelFilter::Debug);
+    module.share_logger(SharedLogger::current());
+
+    build_fixture(&dir, 5, "");
+    module.do_reload().unwrap();
+    assert!(logged("Fixture 5 reloaded"));
+}
+
+/// Reads `count`, the first field of the fixture's `#[repr(C)]` state.
+fn state_count(module: &Module<GameExports>) -> u32 {
+    let bytes = module.state[0].to_ne_bytes();
+
+    u32::from_ne_bytes([bytes[0], bytes[1], bytes[2], bytes[3]])
+}
@@ -1,7 +1,309 @@
-struct Application {}
+use crate::time::Clock;
+use steadfast_core::def::engine::ApplicationConfig;
+
+/// Whether the [`Application`] keeps running after a frame.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Control {
+    Continue,
+    Exit,
+}
+
+/// The stages of a frame, which the [`Application`] runs in order.
+pub trait Stages {
+    /// Runs first every frame, e.g. to poll input and hot reloads.
+    fn input(&mut self) -> Control {
+        Control::Continue
+    }
+
+    /// Advances the simulation by [`dt`] seconds, which is the same for
+    /// every call. Runs as many times a frame as needed to keep up with
+    /// real time, including not at all.
+    fn fixed_update(&mut self, _dt: f64) {}
+
+    /// Runs once a frame, after the fixed updates, with the seconds since
+    /// the last frame.
+    fn update(&mut self, _dt: f64) {}
+
+    /// Draws the frame. [`alpha`] is how far real time is between the
+    /// last fixed update and the next, from 0 to 1, to interpolate by.
+    fn render(&mut self, _alpha: f64) {}
+
+    /// Runs once, after a stage exits.
+    fn shutdown(&mut self) {}
+}
+
+/// How the [`Application`] paces its' frames.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct LoopConfig {
+    /// Fixed updates per second.
+    pub update_rate: f64,
+
+    /// The most seconds a single frame may advance the simulation by. A
+    /// slower frame, e.g. after hitting a breakpoint, is treated as this
+    /// long, so the fixed updates can't fall further and further behind.
+    pub max_frame_time: f64,
+
+    /// Frames per second to sleep down to, or `None` to run as fast as
+    /// possible, e.g. when rendering waits for vsync.
+    pub frame_rate_limit: Option<f64>,
+
+    /// Frames to run before exiting, or `None` for no limit.
+    pub max_frames: Option<u64>,
+
+    /// Seconds to run before exiting, or `None` for no limit.
+    pub max_seconds: Option<f64>,
+}
+
+impl From<&ApplicationConfig> for LoopConfig {
+    fn from(config: &ApplicationConfig) -> Self {
+        Self {
+            update_rate: config.update_rate,
+            frame_rate_limit: config.target_frame_rate,
+            ..Self::default()
+        }
+    }
+}
+
+impl Default for LoopConfig {
+    fn default() -> Self {
+        Self {
+            update_rate: 60.0,
+            max_frame_time: 0.25,
+            frame_rate_limit: Some(60.0),
+            max_frames: None,
+            max_seconds: None,
+        }
+    }
+}
+
+/// Runs the frame loop, splitting time into fixed updates and variable
+/// rate renders.
+#[derive(Debug)]
+pub struct Application {
+    config: LoopConfig,
+    clock: Clock,
+
+    /// When the last frame started, or `None` before the first.
+    previous: Option<f64>,
+    /// Real time not yet simulated by fixed updates.
+    accumulator: f64,
+    frame: u64,
+    simulated: f64,
+}
 
 impl Application {
-    fn run(&self) {
-        loop {}
+    pub fn new(config: LoopConfig) -> Self {
+        assert!(config.update_rate > 0.0, "Update rate must be positive");
+        assert!(
+            config.max_frame_time > 0.0,
+            "Max frame time must be positive"
+        );
+
+        Self {
+            config,
+            clock: Clock::new(),
+            previous: None,
+            accumulator: 0.0,
+            frame: 0,
+            simulated: 0.0,
+        }
+    }
+
+    pub fn config(&self) -> &LoopConfig {
+        &self.config
+    }
+
+    pub fn clock(&self) -> &Clock {
+        &self.clock
+    }
+
+    /// The seconds every fixed update advances the simulation by.
+    pub fn fixed_dt(&self) -> f64 {
+        1.0 / self.config.update_rate
+    }
+
+    /// The number of frames run.
+    pub fn frame(&self) -> u64 {
+        self.frame
+    }
+
+    /// The seconds simulated by fixed updates.
+    pub fn simulated(&self) -> f64 {
+        self.simulated
+    }
+
+    /// Whether [`LoopConfig::max_frames`] or [`LoopConfig::max_seconds`]
+    /// have been run.
+    pub fn is_finished(&self) -> bool {
+        let frames = self.config.max_frames.is_some_and(|max| self.frame >= max);
+        let seconds = self
+            .config
+            .max_seconds
+            .is_some_and(|max| self.clock.now() >= max);
+
+        frames || seconds
+    }
+
+    /// Runs frames until a stage exits, or the run is finished.
+    pub fn run(&mut self, stages: &mut impl Stages) {
+        loop {
+            if self.is_finished() {
+                stages.shutdown();
+                return;
+            }
+
+            let start = self.clock.now();
+
+            if self.step(stages, start) == Control::Exit {
+                stages.shutdown();
+                return;
+            }
+
+            if let Some(limit) = self.config.frame_rate_limit {
+                self.clock.sleep_until(start + 1.0 / limit);
+            }
+        }
+    }
+
+    /// Runs a single frame starting at [`now`] seconds, as measured by
+    /// [`clock`].
+    pub fn step(&mut self, stages: &mut impl Stages, now: f64) -> Control {
+        let frame_time = match self.previous.replace(now) {
+            Some(previous) => (now - previous).clamp(0.0, self.config.max_frame_time),
+            None => 0.0,
+        };
+
+        self.frame += 1;
+
+        if stages.input() == Control::Exit {
+            return Control::Exit;
+        }
+
+        let dt = self.fixed_dt();
+        self.accumulator += frame_time;
+
+        while self.accumulator >= dt {
+            stages.fixed_update(dt);
+
+            self.accumulator -= dt;
+            self.simulated += dt;
+        }
+
+        stages.update(frame_time);
+        stages.render(self.accumulator / dt);
+
+        Control::Continue
+    }
+}
+
+impl Default for Application {
+    fn default() -> Self {
+        Self::new(LoopConfig::default())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Application, Control, LoopConfig, Stages};
+
+    #[derive(Default)]
+    struct Recorder {
+        fixed_updates: Vec<f64>,
+        alphas: Vec<f64>,
+        exit: bool,
+        shut_down: bool,
+    }
+
+    impl Stages for Recorder {
+        fn input(&mut self) -> Control {
+            if self.exit {
+                Control::Exit
+            } else {
+                Control::Continue
+            }
+        }
+
+        fn fixed_update(&mut self, dt: f64) {
+            self.fixed_updates.push(dt);
+        }
+
+        fn render(&mut self, alpha: f64) {
+            self.alphas.push(alpha);
+        }
+
+        fn shutdown(&mut self) {
+            self.shut_down = true;
+        }
+    }
+
+    fn application() -> Application {
+        Application::new(LoopConfig {
+            update_rate: 8.0,
+            max_frame_time: 0.5,
+            frame_rate_limit: None,
+            ..LoopConfig::default()
+        })
+    }
+
+    #[test]
+    fn fixed_updates_keep_up_with_real_time() {
+        let mut application = application();
+        let mut recorder = Recorder::default();
+
+        application.step(&mut recorder, 1.0);
+        assert!(recorder.fixed_updates.is_empty());
+
+        application.step(&mut recorder, 1.3125);
+        assert_eq!(recorder.fixed_updates, [0.125, 0.125]);
+        assert_eq!(recorder.alphas[1], 0.5);
+
+        // The leftover time carries over to the next frame
+        application.step(&mut recorder, 1.375);
+        assert_eq!(recorder.fixed_updates.len(), 3);
+        assert_eq!(recorder.alphas[2], 0.0);
+        assert_eq!(application.simulated(), 0.375);
+    }
+
+    #[test]
+    fn long_frames_are_clamped() {
+        let mut application = application();
+        let mut recorder = Recorder::default();
+
+        application.step(&mut recorder, 0.0);
+        application.step(&mut recorder, 30.0);
+
+        assert_eq!(recorder.fixed_updates.len(), 4);
+        assert_eq!(application.frame(), 2);
+    }
+
+    #[test]
+    fn stages_can_exit() {
+        let mut application = application();
+        let mut recorder = Recorder {
+            exit: true,
+            ..Recorder::default()
+        };
+
+        application.run(&mut recorder);
+
+        assert_eq!(application.frame(), 1);
+        assert!(recorder.alphas.is_empty());
+        assert!(recorder.shut_down);
+    }
+
+    #[test]
+    fn runs_end_after_max_frames() {
+        let mut application = Application::new(LoopConfig {
+            frame_rate_limit: None,
+            max_frames: Some(3),
+            ..LoopConfig::default()
+        });
+        let mut recorder = Recorder::default();
+
+        application.run(&mut recorder);
+
+        assert_eq!(application.frame(), 3);
+        assert_eq!(recorder.alphas.len(), 3);
+        assert!(recorder.shut_down);
     }
 }
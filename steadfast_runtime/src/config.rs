@@ -0,0 +1,64 @@
+use crate::paths;
+use std::path::{Path, PathBuf};
+use steadfast_core::console::{self, Cvar, Cvars, Flags};
+use steadfast_core::module::Host;
+
+/// The file in the user's config directory archived cvars are saved to.
+pub const CONFIG_FILE: &str = "config.cfg";
+
+/// Provides the cvar registry to every module, which find it with
+/// `host.resource_mut::<Cvars>()`, with the runtime's cvars registered.
+pub fn insert_cvars(host: &mut Host) {
+    let mut cvars = Cvars::new();
+
+    let log_filter = Cvar::string(crate::log::FILTER_CVAR, crate::log::DEFAULT_FILTER)
+        .with_description("Which modules are logged at which level, e.g. `info,game=debug`")
+        .with_flags(Flags::ARCHIVE);
+    cvars.register(log_filter).unwrap();
+
+    host.insert_resource(cvars);
+}
+
+/// The user's config file for the application named [`app`], which is
+/// the package name rather than the window title, so the file stays put
+/// when a new release changes the title.
+pub fn config_path(app: &str) -> Option<PathBuf> {
+    paths::config_dir(app).map(|dir| dir.join(CONFIG_FILE))
+}
+
+/// Runs the user's config file at [`path`] if there is one, then
+/// [`commands`] from the command line, which take precedence.
+pub fn load(host: &mut Host, path: Option<&Path>, commands: &[Vec<String>]) {
+    let cvars = match host.resource_mut::<Cvars>() {
+        Some(cvars) => cvars,
+        None => return,
+    };
+
+    if let Some(path) = path.filter(|path| path.exists()) {
+        if let Err(error) = console::exec_file(cvars, path) {
+            tracing::warn!("{}", error);
+        }
+    }
+
+    for command in commands {
+        match console::execute_args(cvars, command) {
+            Ok(Some(output)) => tracing::info!("{}", output),
+            Ok(None) => (),
+            Err(error) => tracing::warn!("{}", error),
+        }
+    }
+}
+
+/// Saves the archived cvars to the user's config file at [`path`].
+pub fn save(host: &Host, path: &Path) {
+    if let Some(cvars) = host.resource::<Cvars>() {
+        // Don't create a config file just to say nothing was changed
+        if cvars.archived().next().is_none() && !path.exists() {
+            return;
+        }
+
+        if let Err(error) = console::save_archive(cvars, path) {
+            tracing::warn!("{}", error);
+        }
+    }
+}
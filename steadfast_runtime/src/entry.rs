@@ -1,28 +1,83 @@
 #[macro_export]
 macro_rules! steadfast_entry {
     () => {
+        $crate::steadfast_entry! {
+            libgame   => steadfast_core::module::game::GameExports,
+            libengine => steadfast_core::module::engine::EngineExports,
+        }
+    };
+    ($($libname:ident => $exports:ty,)*) => {
         fn main() {
-            use steadfast_core::log::error;
-            use steadfast_core::module::engine::EngineExports;
-            use steadfast_core::module::game::GameExports;
-            use steadfast_core::module::load_modules;
-            use steadfast_runtime::log::init_logger;
+            use steadfast_runtime::log::{init_logger, LogConfig};
+            use steadfast_runtime::options::{RunOptions, EXIT_FAILURE, EXIT_USAGE};
+
+            // The user's logs and config are kept under the package's name
+            let app = env!("CARGO_PKG_NAME");
+
+            init_logger(&LogConfig::for_app(app));
 
-            init_logger();
+            let mut options = RunOptions::from_env().unwrap_or_else(|error| {
+                eprintln!("{}", error);
+                eprintln!("Usage: [--headless] [--frames N] [--seconds S] [+<command> <args>...]");
+                std::process::exit(EXIT_USAGE);
+            });
+            options.app = Some(app.to_string());
 
-            load_modules! {
-                libgame   => GameExports,
-                libengine => EngineExports,
-            }
+            let mut modules = $crate::__module_registry!($($libname => $exports,)*);
 
-            let mut module_manager = ModuleManager::new();
+            $crate::frame::insert_frame_arena(modules.host_mut());
+            $crate::config::insert_cvars(modules.host_mut());
+            $crate::log::insert_log_buffer(modules.host_mut());
 
-            loop {
-                #[cfg(debug_assertions)]
-                module_manager.reload();
+            modules.load().expect("Failed to load modules");
 
-                std::thread::sleep(std::time::Duration::from_millis(1000));
-            }
+            let runtime = $crate::runtime::Runtime::new(modules, options)
+                .unwrap_or_else(|error| {
+                    eprintln!("{}", error);
+                    std::process::exit(EXIT_FAILURE);
+                });
+
+            std::process::exit(runtime.run());
         }
     };
 }
+
+/// Loads each module from the library built from it, as listed in
+/// `modules.toml`.
+#[cfg(not(feature = "shipping"))]
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __module_registry {
+    ($($libname:ident => $exports:ty,)*) => {{
+        let mut modules = steadfast_core::module::ModuleRegistry::from_file("modules.toml")
+            .expect("Failed to read the module manifest");
+
+        $(
+            modules.register::<$exports>(stringify!($libname));
+        )*
+
+        modules
+    }};
+}
+
+/// Links each module from the crate named after it, which must be a
+/// dependency of the executable. The manifest is embedded so the
+/// executable can be shipped on its' own.
+#[cfg(feature = "shipping")]
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __module_registry {
+    ($($libname:ident => $exports:ty,)*) => {{
+        let manifest = include_str!(concat!(env!("CARGO_MANIFEST_DIR"), "/modules.toml"))
+            .parse()
+            .expect("Failed to read the module manifest");
+
+        let mut modules = steadfast_core::module::ModuleRegistry::new(manifest);
+
+        $(
+            modules.register_linked::<$exports>(stringify!($libname), &$libname::__MODULE);
+        )*
+
+        modules
+    }};
+}
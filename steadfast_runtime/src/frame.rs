@@ -0,0 +1,21 @@
+use steadfast_core::module::Host;
+
+pub use steadfast_allocator::arena::frame::FrameArena;
+
+/// The bytes each frame of the runtime's [`FrameArena`] starts with.
+pub const FRAME_ARENA_CAPACITY: usize = 4 * 1024 * 1024;
+
+/// Provides the runtime's [`FrameArena`] to every module.
+pub fn insert_frame_arena(host: &mut Host) {
+    let arena: FrameArena = FrameArena::new("frame", FRAME_ARENA_CAPACITY);
+
+    host.insert_resource(arena);
+}
+
+/// Frees the memory of the oldest frame in the runtime's [`FrameArena`],
+/// which modules find with `host.resource::<FrameArena>()`.
+pub fn next_frame(host: &mut Host) {
+    if let Some(arena) = host.resource_mut::<FrameArena>() {
+        arena.next_frame();
+    }
+}
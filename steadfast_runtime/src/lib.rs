@@ -3,4 +3,12 @@ pub extern crate tracing;
 #[macro_use]
 mod entry;
 
+pub mod application;
+pub mod config;
+pub mod frame;
 pub mod log;
+pub mod options;
+pub mod paths;
+pub mod platform;
+pub mod runtime;
+pub mod time;
@@ -1,3 +0,0 @@
-pub fn init_logger() {
-    tracing_subscriber::fmt::init();
-}
@@ -0,0 +1,102 @@
+use std::fmt::{self, Write as _};
+use steadfast_core::console::{LogBuffer, LogLine};
+use tracing::field::{Field, Visit};
+use tracing::{Event, Subscriber};
+use tracing_log::{AsLog, NormalizeEvent};
+use tracing_subscriber::layer::{Context, Layer};
+
+/// Keeps the events logged in a [`LogBuffer`], for the in-game console.
+#[derive(Debug)]
+pub struct BufferLayer {
+    buffer: LogBuffer,
+}
+
+impl BufferLayer {
+    pub fn new(buffer: LogBuffer) -> Self {
+        Self { buffer }
+    }
+}
+
+impl<S: Subscriber> Layer<S> for BufferLayer {
+    fn on_event(&self, event: &Event<'_>, _ctx: Context<'_, S>) {
+        // Records from the `log` crate are logged with the target and level
+        // of `tracing-log`, and their own in `log.` fields
+        let normalized = event.normalized_metadata();
+        let metadata = normalized.as_ref().unwrap_or_else(|| event.metadata());
+
+        let mut visitor = LineVisitor::default();
+        event.record(&mut visitor);
+
+        self.buffer.push(LogLine {
+            level: metadata.level().as_log(),
+            target: metadata.target().to_string(),
+            message: visitor.finish(),
+        });
+    }
+}
+
+/// Formats an event's message, followed by its' other fields.
+#[derive(Default)]
+struct LineVisitor {
+    message: String,
+    fields: String,
+}
+
+impl LineVisitor {
+    fn finish(mut self) -> String {
+        self.message.push_str(&self.fields);
+        self.message
+    }
+}
+
+impl Visit for LineVisitor {
+    fn record_debug(&mut self, field: &Field, value: &dyn fmt::Debug) {
+        match field.name() {
+            "message" => write!(self.message, "{:?}", value).unwrap(),
+            name if name.starts_with("log.") => (),
+            name => write!(self.fields, " {}={:?}", name, value).unwrap(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::BufferLayer;
+    use steadfast_core::console::{LogBuffer, LogLine};
+    use steadfast_core::log;
+    use tracing_subscriber::layer::SubscriberExt;
+
+    #[test]
+    fn events_are_kept_as_lines() {
+        let buffer = LogBuffer::new(8);
+        let subscriber = tracing_subscriber::registry().with(BufferLayer::new(buffer.clone()));
+
+        tracing::subscriber::with_default(subscriber, || {
+            tracing::warn!(target: "game::player", health = 0, "Player {} died", 1);
+
+            // As logged by a module which uses the `log` crate
+            let record = log::Record::builder()
+                .args(format_args!("Engine started"))
+                .level(log::Level::Info)
+                .target("engine")
+                .build();
+            tracing_log::format_trace(&record).unwrap();
+        });
+
+        assert_eq!(
+            buffer.lines(),
+            [
+                LogLine {
+                    level: log::Level::Warn,
+                    target: String::from("game::player"),
+                    message: String::from("Player 1 died health=0"),
+                },
+                LogLine {
+                    level: log::Level::Info,
+                    target: String::from("engine"),
+                    message: String::from("Engine started"),
+                },
+            ]
+        );
+    }
+}
@@ -0,0 +1,188 @@
+use std::fs::{self, File};
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use tracing_subscriber::fmt::MakeWriter;
+
+/// A log file which is moved aside when it grows too large.
+///
+/// `game.log` is rotated to `game.1.log`, which is rotated to `game.2.log`
+/// and so on, keeping up to [`max_files`] rotated files. The file is also
+/// rotated when it is opened, so the logs of previous runs are kept.
+#[derive(Debug)]
+pub struct RotatingFile {
+    path: PathBuf,
+    max_size: u64,
+    max_files: usize,
+    file: File,
+    /// The bytes written to [`file`].
+    size: u64,
+}
+
+impl RotatingFile {
+    /// Opens a new file at [`path`], creating its' directory, which is
+    /// rotated once it would grow past [`max_size`] bytes.
+    pub fn open(path: impl Into<PathBuf>, max_size: u64, max_files: usize) -> io::Result<Self> {
+        let path = path.into();
+
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+
+        rotate(&path, max_files)?;
+
+        Ok(Self {
+            file: File::create(&path)?,
+            path,
+            max_size,
+            max_files,
+            size: 0,
+        })
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    fn rotate(&mut self) -> io::Result<()> {
+        self.file.flush()?;
+
+        rotate(&self.path, self.max_files)?;
+
+        self.file = File::create(&self.path)?;
+        self.size = 0;
+
+        Ok(())
+    }
+}
+
+impl Write for RotatingFile {
+    /// Each event is written at once, so rotating before a write never
+    /// splits an event between files. A file always takes the first write,
+    /// however large.
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        if self.size > 0 && self.size + buf.len() as u64 > self.max_size {
+            self.rotate()?;
+        }
+
+        let written = self.file.write(buf)?;
+        self.size += written as u64;
+
+        Ok(written)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.file.flush()
+    }
+}
+
+/// A [`RotatingFile`] shared by every thread which logs.
+#[derive(Clone, Debug)]
+pub struct SharedFile(Arc<Mutex<RotatingFile>>);
+
+impl SharedFile {
+    pub fn new(file: RotatingFile) -> Self {
+        Self(Arc::new(Mutex::new(file)))
+    }
+}
+
+impl MakeWriter for SharedFile {
+    type Writer = Self;
+
+    fn make_writer(&self) -> Self {
+        self.clone()
+    }
+}
+
+impl Write for SharedFile {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let mut file = self.0.lock().unwrap_or_else(|error| error.into_inner());
+
+        file.write(buf)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        let mut file = self.0.lock().unwrap_or_else(|error| error.into_inner());
+
+        file.flush()
+    }
+}
+
+/// Where [`path`] is moved to when it is rotated for the [`index`]th
+/// time, e.g. `game.2.log`.
+fn rotated_path(path: &Path, index: usize) -> PathBuf {
+    let stem = path.file_stem().unwrap_or_default().to_string_lossy();
+
+    let name = match path.extension() {
+        Some(extension) => format!("{}.{}.{}", stem, index, extension.to_string_lossy()),
+        None => format!("{}.{}", stem, index),
+    };
+
+    path.with_file_name(name)
+}
+
+/// Moves each rotated file along one, dropping the oldest, then the file
+/// at [`path`] to the first.
+fn rotate(path: &Path, max_files: usize) -> io::Result<()> {
+    if !path.exists() {
+        return Ok(());
+    }
+
+    if max_files == 0 {
+        return fs::remove_file(path);
+    }
+
+    for index in (1..max_files).rev() {
+        let from = rotated_path(path, index);
+
+        if from.exists() {
+            fs::rename(from, rotated_path(path, index + 1))?;
+        }
+    }
+
+    fs::rename(path, rotated_path(path, 1))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{rotated_path, RotatingFile};
+    use std::io::Write;
+    use std::path::Path;
+
+    #[test]
+    fn files_are_rotated_when_full() {
+        let dir = std::env::temp_dir().join("steadfast_rotating_file");
+        let _ = std::fs::remove_dir_all(&dir);
+
+        let path = dir.join("game.log");
+        let read = |index| std::fs::read_to_string(rotated_path(&path, index)).unwrap();
+
+        let mut file = RotatingFile::open(&path, 8, 2).unwrap();
+
+        for line in &["one\n", "two\n", "three\n", "four\n"] {
+            file.write_all(line.as_bytes()).unwrap();
+        }
+
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), "four\n");
+        assert_eq!(read(1), "three\n");
+        assert_eq!(read(2), "one\ntwo\n");
+
+        // Opening the file again starts a new one, dropping the oldest
+        drop(file);
+        RotatingFile::open(&path, 8, 2).unwrap();
+
+        assert!(std::fs::read_to_string(&path).unwrap().is_empty());
+        assert_eq!(read(1), "four\n");
+        assert_eq!(read(2), "three\n");
+        assert!(!rotated_path(&path, 3).exists());
+    }
+
+    #[test]
+    fn rotated_files_keep_their_extension() {
+        assert_eq!(
+            rotated_path(Path::new("logs/game.log"), 2),
+            Path::new("logs/game.2.log")
+        );
+        assert_eq!(rotated_path(Path::new("game"), 1), Path::new("game.1"));
+    }
+}
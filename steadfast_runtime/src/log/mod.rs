@@ -0,0 +1,169 @@
+mod buffer;
+mod file;
+
+pub use self::buffer::BufferLayer;
+pub use self::file::{RotatingFile, SharedFile};
+
+use crate::paths;
+use std::path::PathBuf;
+use std::sync::OnceLock;
+use steadfast_core::console::LogBuffer;
+use steadfast_core::module::{Host, ModuleRegistry, SharedLogger};
+use thiserror::Error;
+use tracing_subscriber::filter::ParseError;
+use tracing_subscriber::layer::SubscriberExt;
+use tracing_subscriber::util::SubscriberInitExt;
+use tracing_subscriber::{fmt, reload, EnvFilter, Registry};
+
+/// The cvar which changes [`LogConfig::filter`] while the game runs, e.g.
+/// `log_filter "info,game=debug"`.
+pub const FILTER_CVAR: &str = "log_filter";
+
+/// Logs info and above from every module.
+pub const DEFAULT_FILTER: &str = "info";
+
+#[derive(Debug, Error)]
+pub enum LogError {
+    #[error("Invalid log filter `{filter}`: {source}")]
+    InvalidFilter { filter: String, source: ParseError },
+
+    #[error("Logging was not set up with `init_logger`")]
+    Uninitialized,
+
+    #[error("Failed to change the log filter: {0}")]
+    Reload(#[from] reload::Error),
+}
+
+/// How [`init_logger`] sets up logging.
+#[derive(Clone, Debug)]
+pub struct LogConfig {
+    /// Which modules are logged at which level, as in `RUST_LOG`, which
+    /// is used instead when it is set.
+    pub filter: String,
+
+    /// The file to log to, as well as stderr.
+    pub file: Option<PathBuf>,
+
+    /// The size in bytes past which the file is rotated.
+    pub max_file_size: u64,
+
+    /// How many rotated files are kept.
+    pub max_files: usize,
+
+    /// How many of the latest lines are kept for the in-game console.
+    pub buffer_capacity: usize,
+}
+
+impl LogConfig {
+    /// Logs to `logs/<app>.log` in the user's data directory for [`app`].
+    pub fn for_app(app: &str) -> Self {
+        let file = paths::data_dir(app).map(|dir| dir.join("logs").join(format!("{}.log", app)));
+
+        Self {
+            file,
+            ..Self::default()
+        }
+    }
+}
+
+impl Default for LogConfig {
+    fn default() -> Self {
+        Self {
+            filter: DEFAULT_FILTER.to_string(),
+            file: None,
+            max_file_size: 8 * 1024 * 1024,
+            max_files: 4,
+            buffer_capacity: 1024,
+        }
+    }
+}
+
+/// The parts of the subscriber which can be changed once it is set.
+struct Logger {
+    filter: reload::Handle<EnvFilter, Registry>,
+    buffer: LogBuffer,
+}
+
+static LOGGER: OnceLock<Logger> = OnceLock::new();
+
+/// Sets up logging for the process, to stderr, the file and the buffer
+/// in [`config`], with records from the `log` crate passed to `tracing`.
+///
+/// Modules loaded from dynamic libraries log through the same subscriber,
+/// as they are given it by the module registry. Logging can only be set
+/// up once, later calls are ignored.
+pub fn init_logger(config: &LogConfig) {
+    let directives = std::env::var("RUST_LOG").unwrap_or_else(|_| config.filter.clone());
+
+    let filter = EnvFilter::try_new(&directives).unwrap_or_else(|error| {
+        eprintln!("Invalid log filter `{}`: {}", directives, error);
+        EnvFilter::new(DEFAULT_FILTER)
+    });
+
+    let file = config.file.as_ref().and_then(|path| {
+        match RotatingFile::open(path, config.max_file_size, config.max_files) {
+            Ok(file) => Some(file),
+            Err(error) => {
+                eprintln!("Failed to open the log file {}: {}", path.display(), error);
+                None
+            }
+        }
+    });
+
+    let (filter, handle) = reload::Layer::new(filter);
+    let buffer = LogBuffer::new(config.buffer_capacity);
+
+    let result = tracing_subscriber::registry()
+        .with(filter)
+        .with(fmt::layer().with_writer(std::io::stderr))
+        .with(file.map(|file| {
+            fmt::layer()
+                .with_ansi(false)
+                .with_writer(SharedFile::new(file))
+        }))
+        .with(BufferLayer::new(buffer.clone()))
+        .try_init();
+
+    if result.is_ok() {
+        let _ = LOGGER.set(Logger {
+            filter: handle,
+            buffer,
+        });
+    }
+}
+
+/// Changes which modules are logged at which level, as in `RUST_LOG`.
+///
+/// Modules loaded from dynamic libraries cache what they log, so must be
+/// given the logger again with [`share_logger`] to pick up the change.
+pub fn set_filter(filter: &str) -> Result<(), LogError> {
+    let logger = LOGGER.get().ok_or(LogError::Uninitialized)?;
+
+    let new_filter = EnvFilter::try_new(filter).map_err(|source| LogError::InvalidFilter {
+        filter: filter.to_string(),
+        source,
+    })?;
+
+    logger.filter.reload(new_filter)?;
+
+    Ok(())
+}
+
+/// The latest lines logged, if logging was set up with [`init_logger`].
+pub fn log_buffer() -> Option<&'static LogBuffer> {
+    LOGGER.get().map(|logger| &logger.buffer)
+}
+
+/// Provides the latest lines logged to every module, which find them with
+/// `host.resource::<LogBuffer>()` to show them in the in-game console.
+pub fn insert_log_buffer(host: &mut Host) {
+    if let Some(buffer) = log_buffer() {
+        host.insert_resource(buffer.clone());
+    }
+}
+
+/// Has every module log through the process' subscriber and logger, as
+/// they are now.
+pub fn share_logger(modules: &mut ModuleRegistry) {
+    modules.share_logger(SharedLogger::current());
+}
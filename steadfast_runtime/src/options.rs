@@ -0,0 +1,182 @@
+use thiserror::Error;
+
+/// The process exits with this when it ran as long as it was asked to.
+pub const EXIT_SUCCESS: i32 = 0;
+/// The process exits with this when the application stopped running
+/// before the end of a limited run, e.g. by panicking, or couldn't be run
+/// at all.
+pub const EXIT_FAILURE: i32 = 1;
+/// The process exits with this when the command line is invalid.
+pub const EXIT_USAGE: i32 = 2;
+
+#[derive(Debug, Error, PartialEq)]
+pub enum OptionsError {
+    #[error("Unknown option `{0}`")]
+    Unknown(String),
+
+    #[error("`{0}` needs a value")]
+    MissingValue(&'static str),
+
+    #[error("`{value}` is not a valid value for `{option}`")]
+    InvalidValue { option: &'static str, value: String },
+}
+
+/// How the runtime was asked to run from the command line.
+///
+/// ```text
+/// game --headless --frames 600
+/// game --headless --seconds 30
+/// game +set r_vsync 0 +set com_maxfps 144
+/// ```
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct RunOptions {
+    /// Runs with null window, renderer and audio backends.
+    pub headless: bool,
+
+    /// Exits after this many frames.
+    pub frames: Option<u64>,
+
+    /// Exits after this many seconds.
+    pub seconds: Option<f64>,
+
+    /// Console commands to run once the modules are loaded, each given
+    /// as a `+` followed by the command and its' arguments.
+    pub commands: Vec<Vec<String>>,
+
+    /// The name the user's config file is kept under, which the entry
+    /// point sets to the package's name. Runs without one don't load or
+    /// save the user's config.
+    pub app: Option<String>,
+}
+
+impl RunOptions {
+    /// Parses the arguments the process was started with.
+    pub fn from_env() -> Result<Self, OptionsError> {
+        Self::parse(std::env::args().skip(1))
+    }
+
+    /// Parses [`args`], which shouldn't include the executable.
+    pub fn parse(args: impl IntoIterator<Item = String>) -> Result<Self, OptionsError> {
+        let mut options = Self::default();
+        let mut args = args.into_iter().peekable();
+
+        while let Some(arg) = args.next() {
+            if let Some(command) = arg.strip_prefix('+') {
+                let mut command = vec![command.to_string()];
+
+                while let Some(arg) =
+                    args.next_if(|arg| !arg.starts_with('+') && !arg.starts_with("--"))
+                {
+                    command.push(arg);
+                }
+
+                options.commands.push(command);
+                continue;
+            }
+
+            match arg.as_str() {
+                "--headless" => options.headless = true,
+                "--frames" => options.frames = Some(value(&mut args, "--frames")?),
+                "--seconds" => {
+                    let seconds: f64 = value(&mut args, "--seconds")?;
+
+                    if !(seconds >= 0.0 && seconds.is_finite()) {
+                        return Err(OptionsError::InvalidValue {
+                            option: "--seconds",
+                            value: seconds.to_string(),
+                        });
+                    }
+
+                    options.seconds = Some(seconds);
+                }
+                _ => return Err(OptionsError::Unknown(arg)),
+            }
+        }
+
+        Ok(options)
+    }
+
+    /// Whether the run ends after a number of frames or seconds, rather
+    /// than when the application exits.
+    pub fn is_limited(&self) -> bool {
+        self.frames.is_some() || self.seconds.is_some()
+    }
+}
+
+/// Parses the argument after [`option`].
+fn value<T: std::str::FromStr>(
+    args: &mut impl Iterator<Item = String>,
+    option: &'static str,
+) -> Result<T, OptionsError> {
+    let value = args.next().ok_or(OptionsError::MissingValue(option))?;
+
+    value
+        .parse()
+        .map_err(|_| OptionsError::InvalidValue { option, value })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{OptionsError, RunOptions};
+
+    fn parse(args: &[&str]) -> Result<RunOptions, OptionsError> {
+        RunOptions::parse(args.iter().map(|arg| arg.to_string()))
+    }
+
+    #[test]
+    fn options_are_parsed() {
+        assert_eq!(parse(&[]), Ok(RunOptions::default()));
+        assert!(!RunOptions::default().is_limited());
+
+        let options = parse(&["--headless", "--frames", "120", "--seconds", "2.5"]).unwrap();
+
+        assert!(options.headless && options.is_limited());
+        assert_eq!(options.frames, Some(120));
+        assert_eq!(options.seconds, Some(2.5));
+    }
+
+    #[test]
+    fn commands_run_to_the_next_option() {
+        let options = parse(&[
+            "+set",
+            "r_vsync",
+            "0",
+            "+cvarlist",
+            "--headless",
+            "+set",
+            "x",
+            "-1",
+        ])
+        .unwrap();
+
+        assert!(options.headless);
+        assert_eq!(
+            options.commands,
+            [
+                vec!["set", "r_vsync", "0"],
+                vec!["cvarlist"],
+                vec!["set", "x", "-1"]
+            ]
+        );
+    }
+
+    #[test]
+    fn invalid_options_are_errors() {
+        assert_eq!(
+            parse(&["--windowed"]),
+            Err(OptionsError::Unknown(String::from("--windowed")))
+        );
+        assert_eq!(
+            parse(&["--frames"]),
+            Err(OptionsError::MissingValue("--frames"))
+        );
+        assert_eq!(
+            parse(&["--frames", "-1"]),
+            Err(OptionsError::InvalidValue {
+                option: "--frames",
+                value: String::from("-1"),
+            })
+        );
+        assert!(parse(&["--seconds", "NaN"]).is_err());
+    }
+}
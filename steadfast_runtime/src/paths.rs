@@ -0,0 +1,52 @@
+use std::path::PathBuf;
+
+/// The directory to keep [`app`]'s user settings in, e.g.
+/// `~/.config/<app>` on Linux, or `None` if the user has no home
+/// directory.
+pub fn config_dir(app: &str) -> Option<PathBuf> {
+    user_config_dir().map(|dir| dir.join(app))
+}
+
+#[cfg(windows)]
+fn user_config_dir() -> Option<PathBuf> {
+    env_dir("APPDATA")
+}
+
+#[cfg(target_os = "macos")]
+fn user_config_dir() -> Option<PathBuf> {
+    env_dir("HOME").map(|home| home.join("Library/Application Support"))
+}
+
+#[cfg(all(unix, not(target_os = "macos")))]
+fn user_config_dir() -> Option<PathBuf> {
+    env_dir("XDG_CONFIG_HOME").or_else(|| env_dir("HOME").map(|home| home.join(".config")))
+}
+
+/// The directory to keep [`app`]'s logs and other data it writes in,
+/// e.g. `~/.local/share/<app>` on Linux, or `None` if the user has no
+/// home directory.
+pub fn data_dir(app: &str) -> Option<PathBuf> {
+    user_data_dir().map(|dir| dir.join(app))
+}
+
+#[cfg(windows)]
+fn user_data_dir() -> Option<PathBuf> {
+    env_dir("LOCALAPPDATA").or_else(|| env_dir("APPDATA"))
+}
+
+#[cfg(target_os = "macos")]
+fn user_data_dir() -> Option<PathBuf> {
+    user_config_dir()
+}
+
+#[cfg(all(unix, not(target_os = "macos")))]
+fn user_data_dir() -> Option<PathBuf> {
+    env_dir("XDG_DATA_HOME").or_else(|| env_dir("HOME").map(|home| home.join(".local/share")))
+}
+
+/// The absolute directory in the environment variable [`name`].
+fn env_dir(name: &str) -> Option<PathBuf> {
+    std::env::var_os(name)
+        .map(PathBuf::from)
+        .filter(|dir| dir.is_absolute())
+}
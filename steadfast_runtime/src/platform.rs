@@ -0,0 +1,84 @@
+use steadfast_core::def::engine::{Event, WindowConfig};
+
+/// The window the application is shown in, and where its' events come
+/// from.
+pub trait Window {
+    /// Appends the events since the last poll to [`events`].
+    fn poll_events(&mut self, events: &mut Vec<Event>);
+
+    /// The size of the drawable area in pixels.
+    fn size(&self) -> (u32, u32);
+}
+
+pub trait Renderer {
+    fn begin_frame(&mut self);
+
+    /// Presents the frame drawn since [`begin_frame`].
+    fn end_frame(&mut self);
+}
+
+pub trait Audio {
+    /// Mixes the next [`dt`] seconds of audio.
+    fn update(&mut self, dt: f64);
+}
+
+/// A window which is never shown, so never has any events.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct NullWindow {
+    width: u32,
+    height: u32,
+}
+
+impl NullWindow {
+    pub fn new(config: &WindowConfig) -> Self {
+        Self {
+            width: config.width,
+            height: config.height,
+        }
+    }
+}
+
+impl Window for NullWindow {
+    fn poll_events(&mut self, _events: &mut Vec<Event>) {}
+
+    fn size(&self) -> (u32, u32) {
+        (self.width, self.height)
+    }
+}
+
+/// A renderer which draws nothing.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct NullRenderer;
+
+impl Renderer for NullRenderer {
+    fn begin_frame(&mut self) {}
+
+    fn end_frame(&mut self) {}
+}
+
+/// An audio device which plays nothing.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct NullAudio;
+
+impl Audio for NullAudio {
+    fn update(&mut self, _dt: f64) {}
+}
+
+/// The backends the runtime drives every frame.
+pub struct Platform {
+    pub window: Box<dyn Window>,
+    pub renderer: Box<dyn Renderer>,
+    pub audio: Box<dyn Audio>,
+}
+
+impl Platform {
+    /// Null backends for everything, which need no display server or
+    /// audio device, for CI and dedicated servers.
+    pub fn headless(config: &WindowConfig) -> Self {
+        Self {
+            window: Box::new(NullWindow::new(config)),
+            renderer: Box::new(NullRenderer),
+            audio: Box::new(NullAudio),
+        }
+    }
+}
@@ -0,0 +1,310 @@
+use crate::application::{Application, Control, LoopConfig, Stages};
+use crate::log::LogError;
+use crate::options::{RunOptions, EXIT_FAILURE, EXIT_SUCCESS};
+use crate::platform::Platform;
+use std::path::PathBuf;
+use steadfast_core::console::Cvars;
+use steadfast_core::def::engine::{ApplicationConfig, Event};
+use steadfast_core::module::engine::EngineExports;
+use steadfast_core::module::ModuleRegistry;
+use thiserror::Error;
+
+#[derive(Debug, Error, PartialEq)]
+pub enum RuntimeError {
+    #[error("No windowed platform is available yet, run with `--headless`")]
+    NoWindowedPlatform,
+}
+
+/// Has the engine run the application every frame, on the platform's
+/// backends.
+pub struct Runtime {
+    modules: ModuleRegistry,
+    platform: Platform,
+    options: RunOptions,
+    config: LoopConfig,
+    /// The user's config file, which archived cvars are saved to.
+    config_path: Option<PathBuf>,
+    /// The log filter last set from its' cvar.
+    log_filter: Option<String>,
+
+    /// The window events of this frame.
+    events: Vec<Event>,
+    /// Whether the application stopped running before the end of a
+    /// limited run.
+    failed: bool,
+}
+
+impl Runtime {
+    /// Runs on the backends [`options`] asks for, once the config file of
+    /// the user's settings for its' app and its' commands have set the
+    /// cvars.
+    ///
+    /// Only headless runs are supported until there is a windowed
+    /// platform.
+    pub fn new(mut modules: ModuleRegistry, options: RunOptions) -> Result<Self, RuntimeError> {
+        if !options.headless {
+            return Err(RuntimeError::NoWindowedPlatform);
+        }
+
+        let config_path = options.app.as_deref().and_then(crate::config::config_path);
+
+        crate::config::load(
+            modules.host_mut(),
+            config_path.as_deref(),
+            &options.commands,
+        );
+
+        // `RUST_LOG` takes precedence over the cvar, until it is changed
+        let log_filter = match std::env::var_os("RUST_LOG") {
+            Some(_) => log_filter(&modules).map(str::to_string),
+            None => None,
+        };
+
+        // Read again, as the cvars may change it
+        let application = application_config(&modules);
+
+        let config = LoopConfig {
+            max_frames: options.frames,
+            max_seconds: options.seconds,
+            ..LoopConfig::from(&application)
+        };
+
+        let mut runtime = Self {
+            modules,
+            platform: Platform::headless(&application.window),
+            options,
+            config,
+            config_path,
+            log_filter,
+            events: vec![],
+            failed: false,
+        };
+
+        runtime.update_log_filter();
+
+        Ok(runtime)
+    }
+
+    pub fn modules(&self) -> &ModuleRegistry {
+        &self.modules
+    }
+
+    pub fn loop_config(&self) -> &LoopConfig {
+        &self.config
+    }
+
+    /// Runs frames until the application exits, or the run's frame or
+    /// time limit, then saves the archived cvars and unloads the modules.
+    ///
+    /// Returns the status to exit the process with, which is a failure
+    /// if the application stopped running before the end of a limited
+    /// run, e.g. by panicking.
+    pub fn run(mut self) -> i32 {
+        Application::new(self.config).run(&mut self);
+
+        if let Some(path) = &self.config_path {
+            crate::config::save(self.modules.host(), path);
+        }
+
+        if self.failed {
+            EXIT_FAILURE
+        } else {
+            EXIT_SUCCESS
+        }
+    }
+
+    /// Records a failure if the application stopped running during a
+    /// limited run, which only runs to test the application.
+    fn check_running(&mut self) -> Control {
+        let running = engine(&self.modules)
+            .is_some_and(|engine| (engine.engine_running)(self.modules.host()));
+
+        if self.options.is_limited() && !running {
+            tracing::error!("The application stopped running before the end of the run");
+            self.failed = true;
+
+            return Control::Exit;
+        }
+
+        Control::Continue
+    }
+
+    /// Sets the log filter from its' cvar when it changed, and has the
+    /// modules pick it up.
+    fn update_log_filter(&mut self) {
+        let filter = match log_filter(&self.modules) {
+            Some(filter) if self.log_filter.as_deref() != Some(filter) => filter.to_string(),
+            _ => return,
+        };
+
+        match crate::log::set_filter(&filter) {
+            Ok(()) => crate::log::share_logger(&mut self.modules),
+            Err(LogError::Uninitialized) => (),
+            Err(error) => tracing::warn!("{}", error),
+        }
+
+        // Invalid filters are only reported once
+        self.log_filter = Some(filter);
+    }
+}
+
+/// Polls hot reloads, log filter changes and window events, and frees the
+/// oldest frame before each frame.
+impl Stages for Runtime {
+    fn input(&mut self) -> Control {
+        #[cfg(debug_assertions)]
+        self.modules.reload();
+
+        crate::frame::next_frame(self.modules.host_mut());
+        self.update_log_filter();
+
+        if self.check_running() == Control::Exit {
+            return Control::Exit;
+        }
+
+        self.platform.window.poll_events(&mut self.events);
+
+        let mut control = Control::Continue;
+
+        for event in self.events.drain(..) {
+            if event == Event::CloseRequested {
+                control = Control::Exit;
+            }
+
+            if let Some(engine) = engine(&self.modules) {
+                (engine.engine_event)(self.modules.host_mut(), &event);
+            }
+        }
+
+        control
+    }
+
+    fn fixed_update(&mut self, dt: f64) {
+        if let Some(engine) = engine(&self.modules) {
+            (engine.engine_fixed_update)(self.modules.host_mut(), dt);
+        }
+    }
+
+    fn update(&mut self, dt: f64) {
+        if let Some(engine) = engine(&self.modules) {
+            (engine.engine_update)(self.modules.host_mut(), dt);
+        }
+
+        self.platform.audio.update(dt);
+    }
+
+    fn render(&mut self, alpha: f64) {
+        self.platform.renderer.begin_frame();
+
+        if let Some(engine) = engine(&self.modules) {
+            (engine.engine_render)(self.modules.host_mut(), alpha);
+        }
+
+        self.platform.renderer.end_frame();
+    }
+
+    fn shutdown(&mut self) {
+        // The last frame may have stopped the application too
+        if !self.failed {
+            self.check_running();
+        }
+
+        if let Some(engine) = engine(&self.modules) {
+            (engine.engine_shutdown)(self.modules.host_mut());
+        }
+    }
+}
+
+/// The exports of the engine, copied so the host can be passed to them.
+fn engine(modules: &ModuleRegistry) -> Option<EngineExports> {
+    modules.get::<EngineExports>().cloned()
+}
+
+/// The value of the log filter cvar, if the cvars were provided.
+fn log_filter(modules: &ModuleRegistry) -> Option<&str> {
+    modules
+        .host()
+        .resource::<Cvars>()?
+        .str(crate::log::FILTER_CVAR)
+}
+
+/// How the application run by the engine asks to be run.
+fn application_config(modules: &ModuleRegistry) -> ApplicationConfig {
+    match engine(modules) {
+        Some(engine) => (engine.engine_config)(modules.host()),
+        None => ApplicationConfig::default(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Runtime, RuntimeError};
+    use crate::options::{RunOptions, EXIT_FAILURE, EXIT_SUCCESS};
+    use crate::platform::Window;
+    use steadfast_core::def::engine::Event;
+    use steadfast_core::module::ModuleRegistry;
+
+    /// A window which is closed straight away.
+    struct Closing;
+
+    impl Window for Closing {
+        fn poll_events(&mut self, events: &mut Vec<Event>) {
+            events.push(Event::CloseRequested);
+        }
+
+        fn size(&self) -> (u32, u32) {
+            (0, 0)
+        }
+    }
+
+    fn runtime(options: RunOptions) -> Result<Runtime, RuntimeError> {
+        let modules = ModuleRegistry::new("".parse().unwrap());
+
+        Runtime::new(modules, options)
+    }
+
+    #[test]
+    fn windowed_runs_are_refused() {
+        assert_eq!(
+            runtime(RunOptions::default()).err(),
+            Some(RuntimeError::NoWindowedPlatform)
+        );
+    }
+
+    #[test]
+    fn limits_are_passed_to_the_loop() {
+        let runtime = runtime(RunOptions {
+            headless: true,
+            frames: Some(10),
+            seconds: Some(1.5),
+            ..RunOptions::default()
+        })
+        .unwrap();
+
+        assert_eq!(runtime.loop_config().max_frames, Some(10));
+        assert_eq!(runtime.loop_config().max_seconds, Some(1.5));
+    }
+
+    #[test]
+    fn limited_runs_fail_without_an_application() {
+        let options = RunOptions {
+            headless: true,
+            frames: Some(10),
+            ..RunOptions::default()
+        };
+
+        assert_eq!(runtime(options).unwrap().run(), EXIT_FAILURE);
+    }
+
+    #[test]
+    fn unlimited_runs_exit_successfully() {
+        let mut runtime = runtime(RunOptions {
+            headless: true,
+            ..RunOptions::default()
+        })
+        .unwrap();
+        runtime.platform.window = Box::new(Closing);
+
+        assert_eq!(runtime.run(), EXIT_SUCCESS);
+    }
+}
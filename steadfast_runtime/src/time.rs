@@ -0,0 +1,36 @@
+use std::time::{Duration, Instant};
+
+/// Measures time in seconds since it was started.
+#[derive(Clone, Copy, Debug)]
+pub struct Clock {
+    start: Instant,
+}
+
+impl Clock {
+    pub fn new() -> Self {
+        Self {
+            start: Instant::now(),
+        }
+    }
+
+    /// The seconds since the clock was started.
+    pub fn now(&self) -> f64 {
+        self.start.elapsed().as_secs_f64()
+    }
+
+    /// Sleeps until [`time`] seconds after the clock was started, returning
+    /// straight away if that has passed.
+    pub fn sleep_until(&self, time: f64) {
+        let remaining = time - self.now();
+
+        if remaining > 0.0 {
+            std::thread::sleep(Duration::from_secs_f64(remaining));
+        }
+    }
+}
+
+impl Default for Clock {
+    fn default() -> Self {
+        Self::new()
+    }
+}